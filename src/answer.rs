@@ -1,13 +1,24 @@
-use crate::Name;
+use crate::svcb::check_svc_params;
+use crate::{Error, ExtendableBuffer, Name, SvcParams};
+use core::net::{Ipv4Addr, Ipv6Addr};
 use derive_more::Display;
 
 #[derive(Copy, Clone, Debug, Display, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u16)]
 pub enum AClass {
     IN = 1,
     CS = 2,
     CH = 3,
     HS = 4,
+    /// Used by UPDATE to require an RRset not to exist or to delete a single record.
+    ///
+    /// According to [RFC 2136 Section 2.4](https://tools.ietf.org/rfc/rfc2136#section-2.4).
+    NONE = 254,
+    /// Used by UPDATE to require an RRset to exist or to delete whole RRsets.
+    ///
+    /// According to [RFC 2136 Section 2.4](https://tools.ietf.org/rfc/rfc2136#section-2.4).
+    ANY = 255,
     Unknown,
 }
 
@@ -18,6 +29,8 @@ impl From<u16> for AClass {
             2 => AClass::CS,
             3 => AClass::CH,
             4 => AClass::HS,
+            254 => AClass::NONE,
+            255 => AClass::ANY,
             _ => AClass::Unknown,
         }
     }
@@ -30,12 +43,19 @@ impl From<AClass> for u16 {
             AClass::CS => 2,
             AClass::CH => 3,
             AClass::HS => 4,
+            AClass::NONE => 254,
+            AClass::ANY => 255,
             AClass::Unknown => panic!("Unknown AClass"),
         }
     }
 }
 
+/// The type and data of a record.
+///
+/// New record types may be added in minor releases, so matches need a wildcard arm. Records of
+/// types without a variant are returned as `Unknown` with their raw data.
 #[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum AType<'a> {
     /// A host address
     A(u32),
@@ -55,10 +75,47 @@ pub enum AType<'a> {
     AAAA([u8; 16]),
     /// Location information
     SRV(u16, u16, u16, Name<'a>),
-    /// OPT pseudo-RR
+    /// OPT pseudo-RR with the UDP payload size, extended RCODE, version and options.
+    ///
+    /// According to [RFC 6891 Section 6.1.2](https://tools.ietf.org/rfc/rfc6891#section-6.1.2),
+    /// all but the options are carried in the class and TTL of the record. [`AType::parse`] only
+    /// sees the data and leaves them zero, [`Answer::parse`] fills them in.
     OPT(u16, u8, u8, &'a [u8]),
-    /// Unknown
-    Unknown,
+    /// The next owner name and the type bitmaps of an authenticated denial of existence,
+    /// used by mDNS to assert that no records of other types exist
+    NSEC(Name<'a>, &'a [u8]),
+    /// A digest of a `DNSKEY` of a delegated zone with its key tag, algorithm, digest type and digest.
+    ///
+    /// According to [RFC 4034 Section 5.1](https://tools.ietf.org/rfc/rfc4034#section-5.1).
+    DS(u16, u8, u8, &'a [u8]),
+    /// A signature over the records of a type with its fixed fields, signer name and signature.
+    ///
+    /// According to [RFC 4034 Section 3.1](https://tools.ietf.org/rfc/rfc4034#section-3.1).
+    RRSIG(RrsigFields, Name<'a>, &'a [u8]),
+    /// A public key with its flags, protocol, algorithm and key material.
+    ///
+    /// According to [RFC 4034 Section 2.1](https://tools.ietf.org/rfc/rfc4034#section-2.1).
+    DNSKEY(u16, u8, u8, &'a [u8]),
+    /// The hash algorithm, flags, iterations, salt, next hashed owner name and type bitmaps of
+    /// a hashed authenticated denial of existence.
+    ///
+    /// According to [RFC 5155 Section 3.2](https://tools.ietf.org/rfc/rfc5155#section-3.2).
+    NSEC3(u8, u8, u16, &'a [u8], &'a [u8], &'a [u8]),
+    /// The hash algorithm, flags, iterations and salt of the `NSEC3` records of a zone.
+    ///
+    /// According to [RFC 5155 Section 4.2](https://tools.ietf.org/rfc/rfc5155#section-4.2).
+    NSEC3PARAM(u8, u8, u16, &'a [u8]),
+    /// The priority, target name and SvcParams of a service binding, see [`AType::svc_params`].
+    ///
+    /// According to [RFC 9460 Section 2.2](https://tools.ietf.org/rfc/rfc9460#section-2.2).
+    SVCB(u16, Name<'a>, &'a [u8]),
+    /// A service binding for HTTPS origins, with the same fields as `SVCB`.
+    ///
+    /// According to [RFC 9460 Section 9](https://tools.ietf.org/rfc/rfc9460#section-9).
+    HTTPS(u16, Name<'a>, &'a [u8]),
+    /// A record of a type without a dedicated variant, or without data as in UPDATE messages,
+    /// with its type and raw data
+    Unknown(u16, &'a [u8]),
 }
 impl<'a> AType<'a> {
     pub fn type_id(&self) -> Result<u16, ()> {
@@ -73,18 +130,66 @@ impl<'a> AType<'a> {
             AType::AAAA(_) => Ok(28),
             AType::SRV(_, _, _, _) => Ok(33),
             AType::OPT(_, _, _, _) => Ok(41),
-            AType::Unknown => Err(()),
+            AType::NSEC(_, _) => Ok(47),
+            AType::DS(_, _, _, _) => Ok(43),
+            AType::RRSIG(_, _, _) => Ok(46),
+            AType::DNSKEY(_, _, _, _) => Ok(48),
+            AType::NSEC3(_, _, _, _, _, _) => Ok(50),
+            AType::NSEC3PARAM(_, _, _, _) => Ok(51),
+            AType::SVCB(_, _, _) => Ok(64),
+            AType::HTTPS(_, _, _) => Ok(65),
+            AType::Unknown(atype, _) => Ok(*atype),
+        }
+    }
+
+    /// Returns the address of an `A` record.
+    #[inline(always)]
+    pub fn ipv4_addr(&self) -> Option<Ipv4Addr> {
+        match self {
+            AType::A(addr) => Some(Ipv4Addr::from(*addr)),
+            _ => None,
+        }
+    }
+
+    /// Returns the address of an `AAAA` record.
+    #[inline(always)]
+    pub fn ipv6_addr(&self) -> Option<Ipv6Addr> {
+        match self {
+            AType::AAAA(addr) => Some(Ipv6Addr::from(*addr)),
+            _ => None,
         }
     }
 
-    pub fn parse(atype: u16, data_len: u16, bytes: &'a [u8], i: &mut usize) -> Result<Self, ()> {
+    /// Returns the types listed in the bitmaps of an `NSEC` or `NSEC3` record.
+    ///
+    /// According to [RFC 4034 Section 4.1.2](https://tools.ietf.org/rfc/rfc4034#section-4.1.2).
+    #[inline(always)]
+    pub fn nsec_types(&self) -> NsecTypes<'a> {
+        let bitmaps = match self {
+            AType::NSEC(_, bitmaps) | AType::NSEC3(_, _, _, _, _, bitmaps) => bitmaps,
+            _ => &[][..],
+        };
+
+        NsecTypes { bitmaps, i: 0, bit: 0 }
+    }
+
+    /// Returns the SvcParams of an `SVCB` or `HTTPS` record.
+    #[inline(always)]
+    pub fn svc_params(&self) -> SvcParams<'a> {
+        let params = match self {
+            AType::SVCB(_, _, params) | AType::HTTPS(_, _, params) => params,
+            _ => &[][..],
+        };
+
+        SvcParams { params, i: 0 }
+    }
+
+    /// Parses the data of a record, failing if it is malformed or runs past the end of the message.
+    pub fn parse(atype: u16, data_len: u16, bytes: &'a [u8], i: &mut usize) -> Result<Self, Error> {
+        let data = bytes.get(*i..*i + data_len as usize).ok_or(Error)?;
         match atype {
             1 => {
-                if data_len != 4 {
-                    return Err(());
-                }
-                let addr =
-                    u32::from_be_bytes([bytes[*i], bytes[*i + 1], bytes[*i + 2], bytes[*i + 3]]);
+                let addr = u32::from_be_bytes(data.try_into().map_err(|_| Error)?);
                 *i += 4;
                 Ok(AType::A(addr))
             }
@@ -92,7 +197,7 @@ impl<'a> AType<'a> {
                 let mut j = *i;
                 let name = Name::parse(bytes, &mut j)?;
                 if j - *i != data_len as usize {
-                    return Err(());
+                    return Err(Error);
                 }
                 *i = j;
 
@@ -102,7 +207,7 @@ impl<'a> AType<'a> {
                 let mut j = *i;
                 let name = Name::parse(bytes, &mut j)?;
                 if j - *i != data_len as usize {
-                    return Err(());
+                    return Err(Error);
                 }
                 *i = j;
 
@@ -112,23 +217,12 @@ impl<'a> AType<'a> {
                 let mut j = *i;
                 let mname = Name::parse(bytes, &mut j)?;
                 let rname = Name::parse(bytes, &mut j)?;
-                let serial =
-                    u32::from_be_bytes([bytes[j], bytes[j + 1], bytes[j + 2], bytes[j + 3]]);
-                j += 4;
-                let refresh =
-                    u32::from_be_bytes([bytes[j], bytes[j + 1], bytes[j + 2], bytes[j + 3]]);
-                j += 4;
-                let retry =
-                    u32::from_be_bytes([bytes[j], bytes[j + 1], bytes[j + 2], bytes[j + 3]]);
-                j += 4;
-                let expire =
-                    u32::from_be_bytes([bytes[j], bytes[j + 1], bytes[j + 2], bytes[j + 3]]);
-                j += 4;
-                let minimum =
-                    u32::from_be_bytes([bytes[j], bytes[j + 1], bytes[j + 2], bytes[j + 3]]);
-                j += 4;
+                let fields = bytes.get(j..j + 20).ok_or(Error)?;
+                let u32_at = |k: usize| u32::from_be_bytes([fields[k], fields[k + 1], fields[k + 2], fields[k + 3]]);
+                let (serial, refresh, retry, expire, minimum) = (u32_at(0), u32_at(4), u32_at(8), u32_at(12), u32_at(16));
+                j += 20;
                 if j - *i != data_len as usize {
-                    return Err(());
+                    return Err(Error);
                 }
                 *i = j;
 
@@ -140,19 +234,18 @@ impl<'a> AType<'a> {
                 let mut j = *i;
                 let name = Name::parse(bytes, &mut j)?;
                 if j - *i != data_len as usize {
-                    return Err(());
+                    return Err(Error);
                 }
                 *i = j;
 
                 Ok(AType::PTR(name))
             }
             15 => {
-                let mut j = *i;
-                let preference = u16::from_be_bytes([bytes[j], bytes[j + 1]]);
-                j += 2;
+                let preference = u16::from_be_bytes([*data.first().ok_or(Error)?, *data.get(1).ok_or(Error)?]);
+                let mut j = *i + 2;
                 let exchange = Name::parse(bytes, &mut j)?;
                 if j - *i != data_len as usize {
-                    return Err(());
+                    return Err(Error);
                 }
                 *i = j;
 
@@ -160,62 +253,255 @@ impl<'a> AType<'a> {
             }
             16 => {
                 if data_len < 1 {
-                    return Err(());
+                    return Err(Error);
                 }
-                let txt = &bytes[*i..*i + data_len as usize];
                 *i += data_len as usize;
 
-                Ok(AType::TXT(txt))
+                Ok(AType::TXT(data))
             }
             28 => {
-                if data_len != 16 {
-                    return Err(());
-                }
-                let mut addr = [0; 16];
-                addr.copy_from_slice(&bytes[*i..*i + 16]);
+                let addr = data.try_into().map_err(|_| Error)?;
                 *i += 16;
                 Ok(AType::AAAA(addr))
             }
             33 => {
-                let mut j = *i;
-                let priority = u16::from_be_bytes([bytes[j], bytes[j + 1]]);
-                j += 2;
-                let weight = u16::from_be_bytes([bytes[j], bytes[j + 1]]);
-                j += 2;
-                let port = u16::from_be_bytes([bytes[j], bytes[j + 1]]);
-                j += 2;
+                let fields = data.get(..6).ok_or(Error)?;
+                let priority = u16::from_be_bytes([fields[0], fields[1]]);
+                let weight = u16::from_be_bytes([fields[2], fields[3]]);
+                let port = u16::from_be_bytes([fields[4], fields[5]]);
+                let mut j = *i + 6;
                 let target = Name::parse(bytes, &mut j)?;
                 if j - *i != data_len as usize {
-                    return Err(());
+                    return Err(Error);
                 }
                 *i = j;
 
                 Ok(AType::SRV(priority, weight, port, target))
             }
             41 => {
-                if data_len < 4 {
-                    return Err(());
+                *i += data_len as usize;
+
+                // The remaining fields are carried in the class and TTL, see `Answer::parse`.
+                Ok(AType::OPT(0, 0, 0, data))
+            }
+            47 => {
+                let end = *i + data_len as usize;
+                let mut j = *i;
+                let next = Name::parse(bytes, &mut j)?;
+                if j > end {
+                    return Err(Error);
+                }
+                *i = end;
+
+                Ok(AType::NSEC(next, &bytes[j..end]))
+            }
+            43 | 48 => {
+                if data.len() < 4 {
+                    return Err(Error);
+                }
+                let first = u16::from_be_bytes([data[0], data[1]]);
+                *i += data_len as usize;
+
+                match atype {
+                    43 => Ok(AType::DS(first, data[2], data[3], &data[4..])),
+                    _ => Ok(AType::DNSKEY(first, data[2], data[3], &data[4..])),
+                }
+            }
+            46 => {
+                let end = *i + data_len as usize;
+                let fields = RrsigFields::parse(data)?;
+                let mut j = *i + RrsigFields::LEN;
+                // The signer must not be compressed.
+                let signer = Name::parse_uncompressed(bytes, &mut j)?;
+                if j > end {
+                    return Err(Error);
+                }
+                *i = end;
+
+                Ok(AType::RRSIG(fields, signer, &bytes[j..end]))
+            }
+            50 | 51 => {
+                if data.len() < 5 {
+                    return Err(Error);
+                }
+                let iterations = u16::from_be_bytes([data[2], data[3]]);
+                let salt_end = 5 + data[4] as usize;
+                let salt = data.get(5..salt_end).ok_or(Error)?;
+                let record = match atype {
+                    50 => {
+                        let hash_end = salt_end + 1 + *data.get(salt_end).ok_or(Error)? as usize;
+                        let next = data.get(salt_end + 1..hash_end).ok_or(Error)?;
+                        AType::NSEC3(data[0], data[1], iterations, salt, next, &data[hash_end..])
+                    }
+                    _ if salt_end == data.len() => AType::NSEC3PARAM(data[0], data[1], iterations, salt),
+                    _ => return Err(Error),
+                };
+                *i += data_len as usize;
+
+                Ok(record)
+            }
+            64 | 65 => {
+                let end = *i + data_len as usize;
+                if data_len < 3 {
+                    return Err(Error);
+                }
+                let priority = u16::from_be_bytes([data[0], data[1]]);
+                let mut j = *i + 2;
+                // The target must not be compressed.
+                let target = Name::parse_uncompressed(bytes, &mut j)?;
+                if j > end {
+                    return Err(Error);
+                }
+                let params = &bytes[j..end];
+                check_svc_params(params)?;
+                *i = end;
+
+                match atype {
+                    64 => Ok(AType::SVCB(priority, target, params)),
+                    _ => Ok(AType::HTTPS(priority, target, params)),
                 }
-                let udp_payload_size = u16::from_be_bytes([bytes[*i], bytes[*i + 1]]);
-                *i += 2;
-                let extended_rcode = bytes[*i];
-                *i += 1;
-                let version = bytes[*i];
-                *i += 1;
-                let data_len = data_len as usize - 4;
-                let data = &bytes[*i..*i + data_len];
-                *i += data_len;
-
-                Ok(AType::OPT(udp_payload_size, extended_rcode, version, data))
             }
             _ => {
                 *i += data_len as usize;
-                Ok(AType::Unknown)
+
+                Ok(AType::Unknown(atype, data))
+            }
+        }
+    }
+}
+
+/// The fixed fields of an `RRSIG` record, which precede the signer name.
+///
+/// According to [RFC 4034 Section 3.1](https://tools.ietf.org/rfc/rfc4034#section-3.1).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RrsigFields {
+    /// The type of the signed records
+    pub type_covered: u16,
+    pub algorithm: u8,
+    /// The number of labels of the owner name, not counting a wildcard label
+    pub labels: u8,
+    pub original_ttl: u32,
+    /// The end of the validity period in seconds since the epoch, modulo 2^32
+    pub expiration: u32,
+    /// The start of the validity period in seconds since the epoch, modulo 2^32
+    pub inception: u32,
+    pub key_tag: u16,
+}
+
+impl RrsigFields {
+    pub(crate) const LEN: usize = 18;
+
+    pub(crate) fn parse(bytes: &[u8]) -> Result<Self, ()> {
+        let bytes: &[u8; Self::LEN] = bytes.get(..Self::LEN).ok_or(())?.try_into().map_err(|_| ())?;
+        let u32_at = |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+
+        Ok(Self {
+            type_covered: u16::from_be_bytes([bytes[0], bytes[1]]),
+            algorithm: bytes[2],
+            labels: bytes[3],
+            original_ttl: u32_at(4),
+            expiration: u32_at(8),
+            inception: u32_at(12),
+            key_tag: u16::from_be_bytes([bytes[16], bytes[17]]),
+        })
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; Self::LEN] {
+        let mut bytes = [0; Self::LEN];
+        bytes[0..2].copy_from_slice(&self.type_covered.to_be_bytes());
+        bytes[2] = self.algorithm;
+        bytes[3] = self.labels;
+        bytes[4..8].copy_from_slice(&self.original_ttl.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.expiration.to_be_bytes());
+        bytes[12..16].copy_from_slice(&self.inception.to_be_bytes());
+        bytes[16..18].copy_from_slice(&self.key_tag.to_be_bytes());
+        bytes
+    }
+}
+
+/// An iterator over the types listed in `NSEC` type bitmaps, see [`AType::nsec_types`].
+pub struct NsecTypes<'a> {
+    bitmaps: &'a [u8],
+    i: usize,
+    bit: usize,
+}
+
+impl Iterator for NsecTypes<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let window = *self.bitmaps.get(self.i)?;
+            let len = *self.bitmaps.get(self.i + 1)? as usize;
+            let bitmap = self.bitmaps.get(self.i + 2..self.i + 2 + len)?;
+            while self.bit < len * 8 {
+                let bit = self.bit;
+                self.bit += 1;
+                if bitmap[bit / 8] & (0b1000_0000 >> (bit % 8)) != 0 {
+                    return Some(((window as u16) << 8) | bit as u16);
+                }
             }
+            self.i += 2 + len;
+            self.bit = 0;
+        }
+    }
+}
+
+/// Writes the `NSEC` type bitmaps listing the types, which may be given in any order.
+pub(crate) fn write_nsec_types<B: ExtendableBuffer + ?Sized>(
+    types: impl Iterator<Item = u16> + Clone,
+    buffer: &mut B,
+) -> Result<(), ()> {
+    for window in 0..=255u8 {
+        let mut bitmap = [0u8; 32];
+        let mut len = 0;
+        for atype in types.clone().filter(|atype| (atype >> 8) as u8 == window) {
+            let bit = (atype & 0xff) as usize;
+            bitmap[bit / 8] |= 0b1000_0000 >> (bit % 8);
+            len = len.max(bit / 8 + 1);
+        }
+        if len > 0 {
+            buffer.extend_from_slice(&[window, len as u8])?;
+            buffer.extend_from_slice(&bitmap[..len])?;
         }
     }
+
+    Ok(())
+}
+
+/// Writes the fields shared by `NSEC3` and `NSEC3PARAM` records, failing on salts longer
+/// than 255 bytes.
+pub(crate) fn write_nsec3_params<B: ExtendableBuffer + ?Sized>(
+    hash_algorithm: u8,
+    flags: u8,
+    iterations: u16,
+    salt: &[u8],
+    buffer: &mut B,
+) -> Result<(), ()> {
+    let salt_len = u8::try_from(salt.len()).map_err(|_| ())?;
+    buffer.extend_from_slice(&[hash_algorithm, flags])?;
+    buffer.extend_from_slice(&iterations.to_be_bytes())?;
+    buffer.extend_from_slice(&[salt_len])?;
+    buffer.extend_from_slice(salt)
+}
+
+/// Writes the data of an `NSEC3` or `NSEC3PARAM` record.
+pub(crate) fn write_nsec3<B: ExtendableBuffer + ?Sized>(atype: &AType<'_>, buffer: &mut B) -> Result<(), ()> {
+    match atype {
+        AType::NSEC3(hash_algorithm, flags, iterations, salt, next, bitmaps) => {
+            write_nsec3_params(*hash_algorithm, *flags, *iterations, salt, buffer)?;
+            buffer.extend_from_slice(&[u8::try_from(next.len()).map_err(|_| ())?])?;
+            buffer.extend_from_slice(next)?;
+            buffer.extend_from_slice(bitmaps)
+        }
+        AType::NSEC3PARAM(hash_algorithm, flags, iterations, salt) => {
+            write_nsec3_params(*hash_algorithm, *flags, *iterations, salt, buffer)
+        }
+        _ => Err(()),
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct Answer<'a> {
     name: Name<'a>,
     atype: AType<'a>,
@@ -225,18 +511,27 @@ pub struct Answer<'a> {
 }
 
 impl<'a> Answer<'a> {
-    pub fn parse(bytes: &'a [u8], i: &mut usize) -> Result<Self, ()> {
+    pub fn parse(bytes: &'a [u8], i: &mut usize) -> Result<Self, Error> {
         let name = Name::parse(bytes, i)?;
-        let atype = u16::from_be_bytes([bytes[*i], bytes[*i + 1]]);
-        *i += 2;
-        let cache_flush = (bytes[*i] & 0b1000_0000) != 0;
-        let aclass = AClass::from(u16::from_be_bytes([bytes[*i] & 0b0111_1111, bytes[*i + 1]]));
-        *i += 2;
-        let ttl = u32::from_be_bytes([bytes[*i], bytes[*i + 1], bytes[*i + 2], bytes[*i + 3]]);
-        *i += 4;
-        let data_len = u16::from_be_bytes([bytes[*i], bytes[*i + 1]]);
-        *i += 2;
-        let atype = AType::parse(atype, data_len, bytes, i)?;
+        let fields = bytes.get(*i..*i + 10).ok_or(Error)?;
+        let atype = u16::from_be_bytes([fields[0], fields[1]]);
+        let class = u16::from_be_bytes([fields[2], fields[3]]);
+        let mut cache_flush = (class & 0x8000) != 0;
+        let aclass = AClass::from(class & 0x7fff);
+        let ttl = u32::from_be_bytes([fields[4], fields[5], fields[6], fields[7]]);
+        let data_len = u16::from_be_bytes([fields[8], fields[9]]);
+        *i += 10;
+        let mut atype = match (aclass, data_len) {
+            // UPDATE refers to whole RRsets through records without data.
+            (AClass::NONE | AClass::ANY, 0) => AType::Unknown(atype, &[]),
+            _ => AType::parse(atype, data_len, bytes, i)?,
+        };
+        if let AType::OPT(udp_payload_size, extended_rcode, version, _) = &mut atype {
+            // The top bit of the class is part of the payload size, not the cache flush bit.
+            *udp_payload_size = class;
+            [*extended_rcode, *version, _, _] = ttl.to_be_bytes();
+            cache_flush = false;
+        }
 
         Ok(Answer {
             name,
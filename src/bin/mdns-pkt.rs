@@ -0,0 +1,346 @@
+//! Decodes mDNS packets given as hex, base64, raw bytes or a pcap capture and prints them in
+//! presentation format or as JSON.
+
+use base64::Engine;
+use clap::{Parser, ValueEnum};
+use mdns_pkt::{AClass, Answer, Capture, Header, HeaderKind, Message, Name, QClass, QType, Question};
+use std::fmt::{Display, Write};
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Format {
+    /// One hex encoded packet per line, whitespace and colons are ignored.
+    Hex,
+    /// One base64 encoded packet per line.
+    Base64,
+    /// A single packet.
+    Raw,
+    /// The UDP payloads from or to the mDNS port in a pcap or pcapng capture.
+    Pcap,
+}
+
+/// Decodes mDNS packets and prints them in presentation format.
+#[derive(Parser)]
+#[command(name = "mdns-pkt", version)]
+struct Args {
+    /// The input file, standard input if omitted or `-`.
+    input: Option<PathBuf>,
+    /// The format of the input.
+    #[arg(short, long, value_enum, default_value = "hex")]
+    format: Format,
+    /// Print each packet as a line of JSON.
+    #[arg(long, conflicts_with = "offsets")]
+    json: bool,
+    /// Print every field with its byte offset.
+    #[arg(long)]
+    offsets: bool,
+    /// Fail on malformed records, trailing bytes and section counts not matching the records.
+    /// Packets cut off within a record always fail.
+    #[arg(long)]
+    strict: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let packets = match read_input(&args).and_then(|input| packets(input, args.format)) {
+        Ok(packets) => packets,
+        Err(error) => {
+            eprintln!("mdns-pkt: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut failed = false;
+    let mut separate = false;
+    for (index, packet) in packets.iter().enumerate() {
+        match decode(packet, &args) {
+            Ok(output) => {
+                if separate && !args.json {
+                    println!();
+                }
+                print!("{}", output);
+                separate = true;
+            }
+            Err(error) => {
+                eprintln!("mdns-pkt: packet {}: {}", index + 1, error);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn read_input(args: &Args) -> Result<Vec<u8>, String> {
+    let mut input = Vec::new();
+    match args.input.as_deref() {
+        Some(path) if path.as_os_str() != "-" => {
+            input = std::fs::read(path).map_err(|error| format!("{}: {}", path.display(), error))?
+        }
+        _ => {
+            std::io::stdin().read_to_end(&mut input).map_err(|error| error.to_string())?;
+        }
+    }
+
+    Ok(input)
+}
+
+fn packets(input: Vec<u8>, format: Format) -> Result<Vec<Vec<u8>>, String> {
+    let lines = |input: &[u8]| -> Result<Vec<String>, String> {
+        let text = std::str::from_utf8(input).map_err(|_| "input is not text".to_string())?;
+        Ok(text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect())
+    };
+
+    match format {
+        Format::Hex => lines(&input)?
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let line = line.strip_prefix("0x").unwrap_or(line);
+                let digits: String = line.chars().filter(|c| !c.is_whitespace() && *c != ':').collect();
+                hex::decode(digits).map_err(|error| format!("line {}: {}", index + 1, error))
+            })
+            .collect(),
+        Format::Base64 => lines(&input)?
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(line)
+                    .map_err(|error| format!("line {}: {}", index + 1, error))
+            })
+            .collect(),
+        Format::Raw => Ok(vec![input]),
+        Format::Pcap => pcap_payloads(&input),
+    }
+}
+
+fn decode(packet: &[u8], args: &Args) -> Result<String, String> {
+    let message = Message::new(packet).map_err(|_| "shorter than a header".to_string())?;
+    let walked = walk(packet, |_, _, _, _| Ok(()));
+    match &walked {
+        Err(WalkError::Truncated(error)) => return Err(error.clone()),
+        Err(WalkError::Malformed(error)) if args.strict => return Err(error.clone()),
+        _ => {}
+    }
+
+    let mut output = String::new();
+    if args.json {
+        output = serde_json::to_string(&message).map_err(|error| error.to_string())?;
+        output.push('\n');
+    } else if args.offsets {
+        let result = walk(packet, |offset, section, field, value| {
+            writeln!(output, "{:04x}  {:<10} {:<8} {}", offset, section, field, value)
+        });
+        if let Err(error) = result.or(walked) {
+            writeln!(output, ";; {}", error).map_err(|error| error.to_string())?;
+        }
+    } else {
+        write!(output, "{}", message.display()).map_err(|_| "shorter than a header".to_string())?;
+    }
+
+    Ok(output)
+}
+
+/// Why a packet could not be walked.
+#[derive(Debug, PartialEq)]
+enum WalkError {
+    /// A record runs past the end of the packet.
+    Truncated(String),
+    Malformed(String),
+}
+
+impl Display for WalkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalkError::Truncated(error) | WalkError::Malformed(error) => f.write_str(error),
+        }
+    }
+}
+
+/// Walks over the fields of a packet, calling `field` with the offset, section, name and value
+/// of each. Fails on malformed records and trailing bytes.
+fn walk(
+    packet: &[u8],
+    mut field: impl FnMut(usize, &str, &str, &dyn Display) -> std::fmt::Result,
+) -> Result<(), WalkError> {
+    let header = Message::new(packet)
+        .and_then(|message| message.header().copied())
+        .map_err(|_| WalkError::Truncated("shorter than a header".to_string()))?;
+    let mut field = |offset: usize, section: &str, name: &str, value: &dyn Display| {
+        field(offset, section, name, value)
+            .map_err(|_| WalkError::Malformed(format!("malformed {} {} at offset {}", section, name, offset)))
+    };
+
+    field(0, "header", "id", &header.id())?;
+    field(2, "header", "flags", &Flags(&header))?;
+    field(4, "header", "qdcount", &header.question_count())?;
+    field(6, "header", "ancount", &header.answer_count())?;
+    field(8, "header", "nscount", &header.name_server_count())?;
+    field(10, "header", "arcount", &header.additional_records_count())?;
+
+    let mut i = 12;
+    for _ in 0..header.question_count() {
+        let start = i;
+        let question = Question::parse(packet, &mut i).map_err(|_| record_error(packet, "question", start, 4))?;
+        let name_end = i - 4;
+        field(start, "question", "name", &format_args!("{}.", question.name()))?;
+        field(name_end, "question", "type", &Type(u16_at(packet, name_end).unwrap_or_default()))?;
+        let qclass = match question.qclass() {
+            QClass::Reserved => format!("CLASS{}", u16_at(packet, name_end + 2).unwrap_or_default() & 0x7fff),
+            qclass => qclass.to_string(),
+        };
+        let qu = if question.unicast_response() { " QU" } else { "" };
+        field(name_end + 2, "question", "class", &format_args!("{}{}", qclass, qu))?;
+    }
+
+    let sections = [
+        ("answer", header.answer_count()),
+        ("authority", header.name_server_count()),
+        ("additional", header.additional_records_count()),
+    ];
+    for (section, count) in sections {
+        for _ in 0..count {
+            let start = i;
+            let answer = Answer::parse(packet, &mut i).map_err(|_| record_error(packet, section, start, 10))?;
+            let mut name_end = start;
+            Name::parse(packet, &mut name_end).map_err(|_| record_error(packet, section, start, 10))?;
+            field(start, section, "name", &format_args!("{}.", answer.name()))?;
+            field(name_end, section, "type", &Type(u16_at(packet, name_end).unwrap_or_default()))?;
+            let aclass = match answer.aclass() {
+                AClass::Unknown => format!("CLASS{}", u16_at(packet, name_end + 2).unwrap_or_default()),
+                aclass => aclass.to_string(),
+            };
+            let cache_flush = if answer.cache_flush() { " cache-flush" } else { "" };
+            field(name_end + 2, section, "class", &format_args!("{}{}", aclass, cache_flush))?;
+            field(name_end + 4, section, "ttl", &answer.ttl())?;
+            field(name_end + 8, section, "rdlength", &(i - name_end - 10))?;
+            field(name_end + 10, section, "rdata", answer.atype())?;
+        }
+    }
+
+    if i != packet.len() {
+        return Err(WalkError::Malformed(format!("{} trailing bytes at offset {}", packet.len() - i, i)));
+    }
+
+    Ok(())
+}
+
+/// Tells whether a record at `start` which failed to parse was cut off, within its name, its
+/// `fixed` bytes of type, class and such, or its data.
+fn record_error(packet: &[u8], section: &str, start: usize, fixed: usize) -> WalkError {
+    let mut i = start;
+    let truncated = match Name::parse(packet, &mut i) {
+        Ok(_) if fixed == 10 => u16_at(packet, i + 8).is_none_or(|len| packet.len() < i + 10 + len as usize),
+        Ok(_) => packet.len() < i + fixed,
+        // A name cut off before its terminating label or pointer.
+        Err(_) => {
+            while let Some(&len) = packet.get(i).filter(|&&len| len != 0 && len & 0b1100_0000 == 0) {
+                i += 1 + len as usize;
+            }
+            match packet.get(i) {
+                Some(&len) => len != 0 && packet.len() < i + 2,
+                None => true,
+            }
+        }
+    };
+
+    match truncated {
+        true => WalkError::Truncated(format!("truncated {} at offset {}", section, start)),
+        false => WalkError::Malformed(format!("malformed {} at offset {}", section, start)),
+    }
+}
+
+struct Flags<'h>(&'h Header);
+
+impl Display for Flags<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header = self.0;
+        let flags = [
+            (header.kind() == HeaderKind::Response, "qr"),
+            (header.authoritative_answer(), "aa"),
+            (header.truncated(), "tc"),
+            (header.recursion_desired(), "rd"),
+            (header.recursion_available(), "ra"),
+        ];
+        write!(f, "opcode {:?}, rcode {:?}", header.opcode(), header.response_code())?;
+        for (_, flag) in flags.iter().filter(|(set, _)| *set) {
+            write!(f, ", {}", flag)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Type(u16);
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match QType::from(self.0) {
+            QType::Reserved if self.0 == 41 => f.write_str("OPT"),
+            QType::Reserved => write!(f, "TYPE{}", self.0),
+            qtype => write!(f, "{}", qtype),
+        }
+    }
+}
+
+/// Extracts the mDNS payloads out of a pcap or pcapng capture.
+fn pcap_payloads(capture: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    Capture::new(capture)
+        .map_err(|_| "not a pcap or pcapng capture".to_string())?
+        .map(|message| message.map(|message| message.payload.to_vec()))
+        .collect::<Result<_, _>>()
+        .map_err(|_| "malformed capture".to_string())
+}
+
+fn u16_at(bytes: &[u8], i: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(i..i + 2)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_with(hex: &str, flags: &[&str]) -> Result<String, String> {
+        let args = Args::parse_from(["mdns-pkt"].iter().chain(flags));
+        decode(&hex::decode(hex).unwrap(), &args)
+    }
+
+    #[test]
+    fn decode_truncated() {
+        let answer = "12348400000000010000000004686f7374056c6f63616c0000010001000000780004c0a80001";
+        for flags in [&[][..], &["--strict"], &["--json"], &["--offsets"]] {
+            assert!(decode_with(answer, flags).unwrap().contains("host.local"));
+            for len in 12..answer.len() / 2 {
+                assert_eq!(
+                    decode_with(&answer[..len * 2], flags),
+                    Err("truncated answer at offset 12".to_string()),
+                    "cut at {}",
+                    len,
+                );
+            }
+        }
+        assert_eq!(decode_with("1234", &[]), Err("shorter than a header".to_string()));
+        assert_eq!(
+            decode_with("000000000001000000000000046d646e73056c6f63616c0000ff", &["--strict"]),
+            Err("truncated question at offset 12".to_string()),
+        );
+    }
+
+    #[test]
+    fn decode_malformed() {
+        // An answer whose name points past itself, and a trailing byte.
+        let pointer = "000084000000000100000000c00c0001000100000078000400000000";
+        assert_eq!(decode_with(pointer, &["--strict"]), Err("malformed answer at offset 12".to_string()));
+        assert!(decode_with(pointer, &["--offsets"]).unwrap().contains(";; malformed answer at offset 12"));
+        let trailing = "00000000000000000000000000";
+        assert!(decode_with(trailing, &[]).is_ok());
+        assert_eq!(decode_with(trailing, &["--strict"]), Err("1 trailing bytes at offset 12".to_string()));
+    }
+}
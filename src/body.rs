@@ -1,4 +1,4 @@
-use crate::{Answer, Buffer, ExtendableBuffer, Header, Question, QuestionBuilder, AnswerBuilder};
+use crate::{AClass, Answer, Buffer, ExtendableBuffer, Header, NamePart, QType, Question, QuestionBuilder, AnswerBuilder};
 use core::mem::size_of;
 
 pub trait Section {}
@@ -6,6 +6,21 @@ pub struct QuestionsSection;
 impl Section for QuestionsSection {}
 pub struct AnswersSection;
 impl Section for AnswersSection {}
+pub struct AuthoritySection;
+impl Section for AuthoritySection {}
+pub struct AdditionalSection;
+impl Section for AdditionalSection {}
+/// The zone section of an UPDATE message, in place of the questions.
+///
+/// According to [RFC 2136 Section 2](https://tools.ietf.org/rfc/rfc2136#section-2).
+pub struct ZoneSection;
+impl Section for ZoneSection {}
+/// The prerequisite section of an UPDATE message, in place of the answers.
+pub struct PrerequisiteSection;
+impl Section for PrerequisiteSection {}
+/// The update section of an UPDATE message, in place of the authority records.
+pub struct UpdateSection;
+impl Section for UpdateSection {}
 
 pub struct MessageBody<
     'a,                 // Lifetime of the buffer
@@ -14,11 +29,12 @@ pub struct MessageBody<
     const WRITE: bool,  // Whether the buffer is mutable
 > {
     _phantom: core::marker::PhantomData<(&'a B, S)>,
-    pub(crate) header: *const Header,
     pub(crate) buffer: *const B,
     pub(crate) position: usize,
     pub(crate) question_count: u16,
     pub(crate) answer_count: u16,
+    pub(crate) authority_count: u16,
+    pub(crate) additional_count: u16,
 }
 
 impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, QuestionsSection, WRITE> {
@@ -26,11 +42,12 @@ impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, QuestionsSect
     pub(crate) unsafe fn new(header: *const Header, buffer: *const B) -> Self {
         Self {
             _phantom: core::marker::PhantomData,
-            header,
             buffer,
             position: size_of::<Header>(),
             question_count: unsafe { &*header }.question_count(),
             answer_count: unsafe { &*header }.answer_count(),
+            authority_count: unsafe { &*header }.name_server_count(),
+            additional_count: unsafe { &*header }.additional_records_count(),
         }
     }
 
@@ -48,15 +65,40 @@ impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, QuestionsSect
         let _ = self.questions().count();
         MessageBody {
             _phantom: core::marker::PhantomData,
-            header: self.header,
             buffer: self.buffer,
             position: self.position,
             question_count: self.question_count,
             answer_count: self.answer_count,
+            authority_count: self.authority_count,
+            additional_count: self.additional_count,
         }
     }
 }
 
+impl<'a, B: Buffer + ?Sized, S: Section, const WRITE: bool> MessageBody<'a, B, S, WRITE> {
+    /// Reads the body as another section, without moving past any records.
+    #[inline(always)]
+    pub(crate) fn into_section<T: Section>(self) -> MessageBody<'a, B, T, WRITE> {
+        MessageBody {
+            _phantom: core::marker::PhantomData,
+            buffer: self.buffer,
+            position: self.position,
+            question_count: self.question_count,
+            answer_count: self.answer_count,
+            authority_count: self.authority_count,
+            additional_count: self.additional_count,
+        }
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized, S: Section> MessageBody<'a, B, S, true> {
+    /// Returns the header through the buffer, as an extendable buffer may move its contents.
+    #[inline(always)]
+    pub(crate) unsafe fn header_mut(&mut self) -> &mut Header {
+        Header::from_bytes(unsafe { &mut *(self.buffer as *mut B) }.bytes_mut())
+    }
+}
+
 impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, QuestionsSection, true> {
     #[inline(always)]
     pub fn append_question(self) -> QuestionBuilder<
@@ -72,8 +114,8 @@ impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, QuestionsSection, true
         QuestionBuilder::new(
             unsafe { &mut *(self.buffer as *mut B) },
             self,
-            |parent| {
-                let header = unsafe { &mut *(parent.header as *mut Header) };
+            |mut parent| {
+                let header = unsafe { parent.header_mut() };
                 header.set_question_count(header.question_count() + 1);
 
                 Ok(parent)
@@ -93,6 +135,22 @@ impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, AnswersSectio
     }
 }
 
+impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, AnswersSection, WRITE> {
+    #[inline(always)]
+    pub fn to_authority_section(mut self) -> MessageBody<'a, B, AuthoritySection, WRITE> {
+        let _ = self.answers().count();
+        MessageBody {
+            _phantom: core::marker::PhantomData,
+            buffer: self.buffer,
+            position: self.position,
+            question_count: self.question_count,
+            answer_count: self.answer_count,
+            authority_count: self.authority_count,
+            additional_count: self.additional_count,
+        }
+    }
+}
+
 impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, AnswersSection, true> {
     #[inline(always)]
     pub fn append_answer(self) -> AnswerBuilder<
@@ -110,8 +168,8 @@ impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, AnswersSection, true>
         AnswerBuilder::new(
             unsafe { &mut *(self.buffer as *mut B) },
             self,
-            |parent| {
-                let header = unsafe { &mut *(parent.header as *mut Header) };
+            |mut parent| {
+                let header = unsafe { parent.header_mut() };
                 header.set_answer_count(header.answer_count() + 1);
 
                 Ok(parent)
@@ -120,6 +178,300 @@ impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, AnswersSection, true>
     }
 }
 
+impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, AuthoritySection, WRITE> {
+    #[inline(always)]
+    pub fn authorities(&mut self) -> Answers {
+        Answers {
+            buffer: unsafe { &*self.buffer }.bytes(),
+            position: &mut self.position,
+            count: &mut self.authority_count,
+        }
+    }
+
+    #[inline(always)]
+    pub fn to_additional_section(mut self) -> MessageBody<'a, B, AdditionalSection, WRITE> {
+        let _ = self.authorities().count();
+        MessageBody {
+            _phantom: core::marker::PhantomData,
+            buffer: self.buffer,
+            position: self.position,
+            question_count: self.question_count,
+            answer_count: self.answer_count,
+            authority_count: self.authority_count,
+            additional_count: self.additional_count,
+        }
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, AuthoritySection, true> {
+    #[inline(always)]
+    pub fn append_authority(self) -> AnswerBuilder<
+        'a,
+        B,
+        MessageBody<'a, B, AuthoritySection, true>,
+        Result<MessageBody<'a, B, AuthoritySection, true>, ()>,
+        fn(MessageBody<'a, B, AuthoritySection, true>) -> Result<MessageBody<'a, B, AuthoritySection, true>, ()>,
+        false,
+        false,
+        false,
+        false,
+        false,
+    > {
+        AnswerBuilder::new(
+            unsafe { &mut *(self.buffer as *mut B) },
+            self,
+            |mut parent| {
+                let header = unsafe { parent.header_mut() };
+                header.set_name_server_count(header.name_server_count() + 1);
+
+                Ok(parent)
+            },
+        )
+    }
+}
+
+impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, AdditionalSection, WRITE> {
+    #[inline(always)]
+    pub fn additionals(&mut self) -> Answers {
+        Answers {
+            buffer: unsafe { &*self.buffer }.bytes(),
+            position: &mut self.position,
+            count: &mut self.additional_count,
+        }
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, AdditionalSection, true> {
+    #[inline(always)]
+    pub fn append_additional(self) -> AnswerBuilder<
+        'a,
+        B,
+        MessageBody<'a, B, AdditionalSection, true>,
+        Result<MessageBody<'a, B, AdditionalSection, true>, ()>,
+        fn(MessageBody<'a, B, AdditionalSection, true>) -> Result<MessageBody<'a, B, AdditionalSection, true>, ()>,
+        false,
+        false,
+        false,
+        false,
+        false,
+    > {
+        AnswerBuilder::new(
+            unsafe { &mut *(self.buffer as *mut B) },
+            self,
+            |mut parent| {
+                let header = unsafe { parent.header_mut() };
+                header.set_additional_records_count(header.additional_records_count() + 1);
+
+                Ok(parent)
+            },
+        )
+    }
+}
+
+impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, ZoneSection, WRITE> {
+    /// Returns the zone of the update, which is a question for the `SOA` of the zone.
+    #[inline(always)]
+    pub fn zones(&mut self) -> Questions {
+        Questions {
+            buffer: unsafe { &*self.buffer }.bytes(),
+            position: &mut self.position,
+            count: &mut self.question_count,
+        }
+    }
+
+    #[inline(always)]
+    pub fn to_prerequisite_section(mut self) -> MessageBody<'a, B, PrerequisiteSection, WRITE> {
+        let _ = self.zones().count();
+        self.into_section()
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, ZoneSection, true> {
+    /// Appends the zone, whose type has to be [`QType::SOA`].
+    #[inline(always)]
+    pub fn append_zone(self) -> QuestionBuilder<
+        'a,
+        B,
+        MessageBody<'a, B, ZoneSection, true>,
+        Result<MessageBody<'a, B, ZoneSection, true>, ()>,
+        fn(MessageBody<'a, B, ZoneSection, true>) -> Result<MessageBody<'a, B, ZoneSection, true>, ()>,
+        false,
+        false,
+        false,
+    > {
+        QuestionBuilder::new(
+            unsafe { &mut *(self.buffer as *mut B) },
+            self,
+            |mut parent| {
+                let header = unsafe { parent.header_mut() };
+                header.set_question_count(header.question_count() + 1);
+
+                Ok(parent)
+            },
+        )
+    }
+}
+
+impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, PrerequisiteSection, WRITE> {
+    #[inline(always)]
+    pub fn prerequisites(&mut self) -> Answers {
+        Answers {
+            buffer: unsafe { &*self.buffer }.bytes(),
+            position: &mut self.position,
+            count: &mut self.answer_count,
+        }
+    }
+
+    #[inline(always)]
+    pub fn to_update_section(mut self) -> MessageBody<'a, B, UpdateSection, WRITE> {
+        let _ = self.prerequisites().count();
+        self.into_section()
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, PrerequisiteSection, true> {
+    /// Appends a prerequisite. Records with the class of the zone and a TTL of 0 require
+    /// an RRset to exist with exactly these records.
+    #[inline(always)]
+    pub fn append_prerequisite(self) -> AnswerBuilder<
+        'a,
+        B,
+        MessageBody<'a, B, PrerequisiteSection, true>,
+        Result<MessageBody<'a, B, PrerequisiteSection, true>, ()>,
+        fn(MessageBody<'a, B, PrerequisiteSection, true>) -> Result<MessageBody<'a, B, PrerequisiteSection, true>, ()>,
+        false,
+        false,
+        false,
+        false,
+        false,
+    > {
+        AnswerBuilder::new(
+            unsafe { &mut *(self.buffer as *mut B) },
+            self,
+            |mut parent| {
+                let header = unsafe { parent.header_mut() };
+                header.set_answer_count(header.answer_count() + 1);
+
+                Ok(parent)
+            },
+        )
+    }
+
+    /// Requires an RRset of the type to exist at the name, whatever its records.
+    ///
+    /// According to [RFC 2136 Section 2.4.1](https://tools.ietf.org/rfc/rfc2136#section-2.4.1).
+    #[inline(always)]
+    pub fn rrset_exists(self, name: impl NamePart, atype: QType) -> Result<Self, ()> {
+        append_empty(self.append_prerequisite(), name, atype, AClass::ANY)?
+    }
+
+    /// Requires no RRset of the type to exist at the name.
+    ///
+    /// According to [RFC 2136 Section 2.4.3](https://tools.ietf.org/rfc/rfc2136#section-2.4.3).
+    #[inline(always)]
+    pub fn rrset_does_not_exist(self, name: impl NamePart, atype: QType) -> Result<Self, ()> {
+        append_empty(self.append_prerequisite(), name, atype, AClass::NONE)?
+    }
+
+    /// Requires the name to own at least one record.
+    ///
+    /// According to [RFC 2136 Section 2.4.4](https://tools.ietf.org/rfc/rfc2136#section-2.4.4).
+    #[inline(always)]
+    pub fn name_is_in_use(self, name: impl NamePart) -> Result<Self, ()> {
+        append_empty(self.append_prerequisite(), name, QType::ALL, AClass::ANY)?
+    }
+
+    /// Requires the name to own no records.
+    ///
+    /// According to [RFC 2136 Section 2.4.5](https://tools.ietf.org/rfc/rfc2136#section-2.4.5).
+    #[inline(always)]
+    pub fn name_is_not_in_use(self, name: impl NamePart) -> Result<Self, ()> {
+        append_empty(self.append_prerequisite(), name, QType::ALL, AClass::NONE)?
+    }
+}
+
+impl<'a, B: Buffer + ?Sized, const WRITE: bool> MessageBody<'a, B, UpdateSection, WRITE> {
+    #[inline(always)]
+    pub fn updates(&mut self) -> Answers {
+        Answers {
+            buffer: unsafe { &*self.buffer }.bytes(),
+            position: &mut self.position,
+            count: &mut self.authority_count,
+        }
+    }
+
+    #[inline(always)]
+    pub fn to_additional_section(mut self) -> MessageBody<'a, B, AdditionalSection, WRITE> {
+        let _ = self.updates().count();
+        self.into_section()
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, UpdateSection, true> {
+    /// Appends an update. Records with the class of the zone are added, records with
+    /// [`AClass::NONE`] and a TTL of 0 are deleted from their RRset.
+    ///
+    /// According to [RFC 2136 Section 2.5](https://tools.ietf.org/rfc/rfc2136#section-2.5).
+    #[inline(always)]
+    pub fn append_update(self) -> AnswerBuilder<
+        'a,
+        B,
+        MessageBody<'a, B, UpdateSection, true>,
+        Result<MessageBody<'a, B, UpdateSection, true>, ()>,
+        fn(MessageBody<'a, B, UpdateSection, true>) -> Result<MessageBody<'a, B, UpdateSection, true>, ()>,
+        false,
+        false,
+        false,
+        false,
+        false,
+    > {
+        AnswerBuilder::new(
+            unsafe { &mut *(self.buffer as *mut B) },
+            self,
+            |mut parent| {
+                let header = unsafe { parent.header_mut() };
+                header.set_name_server_count(header.name_server_count() + 1);
+
+                Ok(parent)
+            },
+        )
+    }
+
+    /// Deletes the RRset of the type at the name.
+    ///
+    /// According to [RFC 2136 Section 2.5.2](https://tools.ietf.org/rfc/rfc2136#section-2.5.2).
+    #[inline(always)]
+    pub fn delete_rrset(self, name: impl NamePart, atype: QType) -> Result<Self, ()> {
+        append_empty(self.append_update(), name, atype, AClass::ANY)?
+    }
+
+    /// Deletes all RRsets at the name.
+    ///
+    /// According to [RFC 2136 Section 2.5.3](https://tools.ietf.org/rfc/rfc2136#section-2.5.3).
+    #[inline(always)]
+    pub fn delete_all_rrsets(self, name: impl NamePart) -> Result<Self, ()> {
+        append_empty(self.append_update(), name, QType::ALL, AClass::ANY)?
+    }
+}
+
+/// Writes a record without data and with a TTL of 0, referring to a whole RRset.
+fn append_empty<'a, B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O>(
+    builder: AnswerBuilder<'a, B, P, O, F, false, false, false, false, false>,
+    name: impl NamePart,
+    atype: QType,
+    aclass: AClass,
+) -> Result<O, ()> {
+    Ok(builder.name()
+        .label(name)?
+        .finish()??
+        .atype()
+        .empty(atype)??
+        .cache_flush(false)?
+        .aclass(aclass)?
+        .ttl(0)?
+        .finish())
+}
+
 pub struct Questions<'a> {
     buffer: &'a [u8],
     position: &'a mut usize,
@@ -1,14 +1,20 @@
 mod builder;
 mod type_a;
+mod type_aaaa;
 mod type_ptr;
 mod type_txt;
 mod type_srv;
+mod type_rrsig;
+mod type_svcb;
 
 pub use builder::*;
 pub use type_a::*;
+pub use type_aaaa::*;
 pub use type_ptr::*;
 pub use type_txt::*;
 pub use type_srv::*;
+pub use type_rrsig::*;
+pub use type_svcb::*;
 
 use crate::{AClass, ExtendableBuffer, NameBuilder};
 use crate::builder::answer::builder::AnswerTypeBuilder;
@@ -100,7 +106,7 @@ impl<
         TTL,
     >, ()> {
         // Set the cache flush bit if true and clear it if false.
-        let byte = self.buffer.bytes_mut_at(self.name_end, 1)?;
+        let byte = self.buffer.bytes_mut_at(self.name_end + 2, 1)?;
         byte[0] = (byte[0] & 0b0111_1111) | ((value as u8) << 7);
 
         Ok(AnswerBuilder {
@@ -143,7 +149,7 @@ impl<
         let value: u16 = value.into();
         let mut value = value.to_be_bytes();
         value[0] &= 0b0111_1111; // Clear the cache flush bit.
-        value[0] |= self.buffer.bytes()[self.name_end] & 0b1000_0000; // Copy the cache flush bit.
+        value[0] |= self.buffer.bytes()[self.name_end + 2] & 0b1000_0000; // Copy the cache flush bit.
         self.buffer.bytes_mut_at(self.name_end + 2, 2)?
             .copy_from_slice(&value);
 
@@ -155,6 +161,30 @@ impl<
         })
     }
 
+    /// Writes the UDP payload size of an `OPT` record, which takes the place of the class and
+    /// the cache flush bit.
+    ///
+    /// According to [RFC 6891 Section 6.1.2](https://tools.ietf.org/rfc/rfc6891#section-6.1.2).
+    #[inline(always)]
+    pub fn udp_payload_size(self, value: u16) -> Result<AnswerBuilder<
+        'a, B, P, O, F,
+        true,
+        true,
+        TYPE,
+        true,
+        TTL,
+    >, ()> {
+        self.buffer.bytes_mut_at(self.name_end + 2, 2)?
+            .copy_from_slice(&value.to_be_bytes());
+
+        Ok(AnswerBuilder {
+            parent: self.parent,
+            finalizer: self.finalizer,
+            name_end: self.name_end,
+            buffer: self.buffer,
+        })
+    }
+
     #[inline(always)]
     pub fn atype(self) -> AnswerTypeBuilder<
         'a, B,
@@ -1,4 +1,5 @@
-use crate::{AnswerTypeABuilder, AnswerTypePtrBuilder, AnswerTypeSrvBuilder, AnswerTypeTxtBuilder, ExtendableBuffer};
+use crate::answer::{write_nsec3, write_nsec3_params, write_nsec_types};
+use crate::{AType, AnswerTypeABuilder, AnswerTypeAaaaBuilder, AnswerTypePtrBuilder, AnswerTypeRrsigBuilder, AnswerTypeSrvBuilder, AnswerTypeSvcbBuilder, AnswerTypeTxtBuilder, ExtendableBuffer, NamePart, QType, RrsigFields};
 
 pub struct AnswerTypeBuilder<
     'a,
@@ -32,6 +33,14 @@ impl<
         AnswerTypeABuilder::new(self.buffer, self.parent, self.finalizer)
     }
 
+    #[inline(always)]
+    pub fn aaaa(self) -> AnswerTypeAaaaBuilder<'a, B, P, O, F, false, > {
+        let buffer_pos = self.buffer.len();
+        self.buffer.bytes_mut_at(buffer_pos - 9, 1).unwrap()
+            .copy_from_slice(&[28]);
+        AnswerTypeAaaaBuilder::new(self.buffer, self.parent, self.finalizer)
+    }
+
     #[inline(always)]
     pub fn ptr(self) -> AnswerTypePtrBuilder<'a, B, P, O, F, false, > {
         let buffer_pos = self.buffer.len();
@@ -55,4 +64,170 @@ impl<
             .copy_from_slice(&[33]);
         AnswerTypeSrvBuilder::new(self.buffer, self.parent, self.finalizer)
     }
+
+    #[inline(always)]
+    pub fn svcb(self) -> AnswerTypeSvcbBuilder<'a, B, P, O, F, false, false, > {
+        let buffer_pos = self.buffer.len();
+        self.buffer.bytes_mut_at(buffer_pos - 9, 1).unwrap()
+            .copy_from_slice(&[64]);
+        AnswerTypeSvcbBuilder::new(self.buffer, self.parent, self.finalizer)
+    }
+
+    #[inline(always)]
+    pub fn https(self) -> AnswerTypeSvcbBuilder<'a, B, P, O, F, false, false, > {
+        let buffer_pos = self.buffer.len();
+        self.buffer.bytes_mut_at(buffer_pos - 9, 1).unwrap()
+            .copy_from_slice(&[65]);
+        AnswerTypeSvcbBuilder::new(self.buffer, self.parent, self.finalizer)
+    }
+
+    /// Writes the type without record data, referring to a whole RRset in the prerequisites
+    /// and deletions of UPDATE messages. [`QType::ALL`] refers to all RRsets of the name.
+    ///
+    /// According to [RFC 2136 Section 2.4](https://tools.ietf.org/rfc/rfc2136#section-2.4).
+    pub fn empty(self, atype: QType) -> Result<O, ()> {
+        if atype == QType::Reserved {
+            return Err(());
+        }
+
+        self.write_with(atype.into(), |_| Ok(()))
+    }
+
+    /// Writes an `RRSIG` record, starting with its fixed fields.
+    ///
+    /// According to [RFC 4034 Section 3.1](https://tools.ietf.org/rfc/rfc4034#section-3.1).
+    #[inline(always)]
+    pub fn rrsig(self, fields: &RrsigFields) -> Result<AnswerTypeRrsigBuilder<'a, B, P, O, F, false, false, >, ()> {
+        let buffer_pos = self.buffer.len();
+        self.buffer.bytes_mut_at(buffer_pos - 9, 1)?
+            .copy_from_slice(&[46]);
+        AnswerTypeRrsigBuilder::new(self.buffer, self.parent, self.finalizer, fields)
+    }
+
+    /// Writes a `DNSKEY` record.
+    ///
+    /// According to [RFC 4034 Section 2.1](https://tools.ietf.org/rfc/rfc4034#section-2.1).
+    pub fn dnskey(self, flags: u16, protocol: u8, algorithm: u8, public_key: &[u8]) -> Result<O, ()> {
+        self.write_with(48, |buffer| {
+            buffer.extend_from_slice(&flags.to_be_bytes())?;
+            buffer.extend_from_slice(&[protocol, algorithm])?;
+            buffer.extend_from_slice(public_key)
+        })
+    }
+
+    /// Writes a `DS` record.
+    ///
+    /// According to [RFC 4034 Section 5.1](https://tools.ietf.org/rfc/rfc4034#section-5.1).
+    pub fn ds(self, key_tag: u16, algorithm: u8, digest_type: u8, digest: &[u8]) -> Result<O, ()> {
+        self.write_with(43, |buffer| {
+            buffer.extend_from_slice(&key_tag.to_be_bytes())?;
+            buffer.extend_from_slice(&[algorithm, digest_type])?;
+            buffer.extend_from_slice(digest)
+        })
+    }
+
+    /// Writes an `NSEC3` record with the hashed next owner name and the types, which may be
+    /// given in any order. Fails if the salt or hash is longer than 255 bytes.
+    ///
+    /// According to [RFC 5155 Section 3.2](https://tools.ietf.org/rfc/rfc5155#section-3.2).
+    pub fn nsec3(
+        self,
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: &[u8],
+        next_hashed: &[u8],
+        types: impl Iterator<Item = u16> + Clone,
+    ) -> Result<O, ()> {
+        self.write_with(50, |buffer| {
+            write_nsec3_params(hash_algorithm, flags, iterations, salt, buffer)?;
+            buffer.extend_from_slice(&[u8::try_from(next_hashed.len()).map_err(|_| ())?])?;
+            buffer.extend_from_slice(next_hashed)?;
+            write_nsec_types(types, buffer)
+        })
+    }
+
+    /// Writes an `NSEC3PARAM` record. Fails if the salt is longer than 255 bytes.
+    ///
+    /// According to [RFC 5155 Section 4.2](https://tools.ietf.org/rfc/rfc5155#section-4.2).
+    pub fn nsec3param(self, hash_algorithm: u8, flags: u8, iterations: u16, salt: &[u8]) -> Result<O, ()> {
+        self.write_with(51, |buffer| write_nsec3_params(hash_algorithm, flags, iterations, salt, buffer))
+    }
+
+    /// Writes the type and lets the closure append the record data.
+    pub(crate) fn write_with(self, atype: u16, data: impl FnOnce(&mut B) -> Result<(), ()>) -> Result<O, ()> {
+        let buffer_pos = self.buffer.len();
+        self.buffer.bytes_mut_at(buffer_pos - 10, 2)?
+            .copy_from_slice(&atype.to_be_bytes());
+        data(self.buffer)?;
+
+        Ok((self.finalizer)(self.parent))
+    }
+
+    /// Writes the type and data of a parsed record, e.g. to copy it into another message.
+    ///
+    /// Names are written uncompressed, as their pointers refer to the message they were parsed from.
+    /// Of an `OPT` record only the options are written, its other fields belong to the class and TTL.
+    pub fn rdata(self, atype: &AType<'_>) -> Result<O, ()> {
+        let buffer_pos = self.buffer.len();
+        self.buffer.bytes_mut_at(buffer_pos - 10, 2)?
+            .copy_from_slice(&atype.type_id()?.to_be_bytes());
+
+        match atype {
+            AType::A(addr) => self.buffer.extend_from_slice(&addr.to_be_bytes())?,
+            AType::NS(name) | AType::CNAME(name) | AType::PTR(name) => {
+                name.to_bytes(self.buffer)?;
+                self.buffer.extend_from_slice(&[0])?;
+            }
+            AType::SOA(mname, rname, serial, refresh, retry, expire, minimum) => {
+                mname.to_bytes(self.buffer)?;
+                self.buffer.extend_from_slice(&[0])?;
+                rname.to_bytes(self.buffer)?;
+                self.buffer.extend_from_slice(&[0])?;
+                for value in [serial, refresh, retry, expire, minimum] {
+                    self.buffer.extend_from_slice(&value.to_be_bytes())?;
+                }
+            }
+            AType::MX(preference, exchange) => {
+                self.buffer.extend_from_slice(&preference.to_be_bytes())?;
+                exchange.to_bytes(self.buffer)?;
+                self.buffer.extend_from_slice(&[0])?;
+            }
+            AType::TXT(txt) => self.buffer.extend_from_slice(txt)?,
+            AType::AAAA(addr) => self.buffer.extend_from_slice(addr)?,
+            AType::SRV(priority, weight, port, target) => {
+                self.buffer.extend_from_slice(&priority.to_be_bytes())?;
+                self.buffer.extend_from_slice(&weight.to_be_bytes())?;
+                self.buffer.extend_from_slice(&port.to_be_bytes())?;
+                target.to_bytes(self.buffer)?;
+                self.buffer.extend_from_slice(&[0])?;
+            }
+            AType::NSEC(next, bitmaps) => {
+                next.to_bytes(self.buffer)?;
+                self.buffer.extend_from_slice(&[0])?;
+                self.buffer.extend_from_slice(bitmaps)?;
+            }
+            AType::RRSIG(fields, signer, signature) => {
+                self.buffer.extend_from_slice(&fields.to_bytes())?;
+                signer.to_bytes(self.buffer)?;
+                self.buffer.extend_from_slice(&[0])?;
+                self.buffer.extend_from_slice(signature)?;
+            }
+            AType::DS(first, a, b, data) | AType::DNSKEY(first, a, b, data) => {
+                self.buffer.extend_from_slice(&first.to_be_bytes())?;
+                self.buffer.extend_from_slice(&[*a, *b])?;
+                self.buffer.extend_from_slice(data)?;
+            }
+            AType::NSEC3(..) | AType::NSEC3PARAM(..) => write_nsec3(atype, self.buffer)?,
+            AType::SVCB(priority, target, params) | AType::HTTPS(priority, target, params) => {
+                self.buffer.extend_from_slice(&priority.to_be_bytes())?;
+                target.to_bytes(self.buffer)?;
+                self.buffer.extend_from_slice(&[0])?;
+                self.buffer.extend_from_slice(params)?;
+            }
+            AType::OPT(_, _, _, data) | AType::Unknown(_, data) => self.buffer.extend_from_slice(data)?,
+        }
+
+        Ok((self.finalizer)(self.parent))
+    }
 }
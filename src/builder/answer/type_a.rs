@@ -1,4 +1,5 @@
 use crate::ExtendableBuffer;
+use core::net::Ipv4Addr;
 
 pub struct AnswerTypeABuilder<
     'a,
@@ -38,6 +39,11 @@ impl<
             start: self.start
         })
     }
+
+    #[inline(always)]
+    pub fn ipv4(self, ip: Ipv4Addr) -> Result<AnswerTypeABuilder<'a, B, P, O, F, true>, ()> {
+        self.ip(&ip.octets())
+    }
 }
 
 impl<
@@ -0,0 +1,58 @@
+use crate::ExtendableBuffer;
+use core::net::Ipv6Addr;
+
+pub struct AnswerTypeAaaaBuilder<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+    const IP: bool,
+> {
+    parent: P,
+    finalizer: F,
+    buffer: &'a mut B,
+    start: usize,
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+    const IP: bool,
+> AnswerTypeAaaaBuilder<'a, B, P, O, F, IP> {
+    #[inline(always)]
+    pub(crate) fn new(
+        buffer: &'a mut B,
+        parent: P,
+        finalizer: F,
+    ) -> AnswerTypeAaaaBuilder<'a, B, P, O, F, IP> {
+        let start = buffer.len();
+        AnswerTypeAaaaBuilder { parent, finalizer, buffer, start }
+    }
+
+    #[inline(always)]
+    pub fn ip(self, ip: &[u8; 16]) -> Result<AnswerTypeAaaaBuilder<'a, B, P, O, F, true>, ()> {
+        self.buffer.bytes_mut_at(self.start, 16)?.copy_from_slice(ip);
+        Ok(AnswerTypeAaaaBuilder {
+            parent: self.parent,
+            finalizer: self.finalizer,
+            buffer: self.buffer,
+            start: self.start
+        })
+    }
+
+    #[inline(always)]
+    pub fn ipv6(self, ip: Ipv6Addr) -> Result<AnswerTypeAaaaBuilder<'a, B, P, O, F, true>, ()> {
+        self.ip(&ip.octets())
+    }
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+> AnswerTypeAaaaBuilder<'a, B, P, O, F, true> {
+    #[inline(always)]
+    pub fn finish(self) -> O {
+        (self.finalizer)(self.parent)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{ExtendableBuffer, NameBuilder, RrsigFields};
+
+pub struct AnswerTypeRrsigBuilder<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+    const SIGNER: bool,
+    const SIGNATURE: bool,
+> {
+    parent: P,
+    finalizer: F,
+    buffer: &'a mut B,
+    start: usize,
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+> AnswerTypeRrsigBuilder<'a, B, P, O, F, false, false> {
+    #[inline(always)]
+    pub fn new(
+        buffer: &'a mut B,
+        parent: P,
+        finalizer: F,
+        fields: &RrsigFields,
+    ) -> Result<AnswerTypeRrsigBuilder<
+        'a, B, P, O, F,
+        false,
+        false,
+    >, ()> {
+        let start = buffer.len();
+        buffer.extend_from_slice(&fields.to_bytes())?;
+
+        Ok(AnswerTypeRrsigBuilder { parent, finalizer, buffer, start })
+    }
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+> AnswerTypeRrsigBuilder<'a, B, P, O, F, false, false> {
+    /// Writes the name of the zone holding the signing key, which must not be compressed.
+    ///
+    /// According to [RFC 4034 Section 3.1.7](https://tools.ietf.org/rfc/rfc4034#section-3.1.7).
+    #[inline(always)]
+    pub fn signer(self) -> NameBuilder<
+        'a,
+        B,
+        Self,
+        AnswerTypeRrsigBuilder<'a, B, P, O, F, true, false>,
+        fn(AnswerTypeRrsigBuilder<'a, B, P, O, F, false, false>) -> AnswerTypeRrsigBuilder<'a, B, P, O, F, true, false>,
+    > {
+        let buffer_ptr = unsafe { &mut *(self.buffer as *mut B) };
+        buffer_ptr.truncate(self.start + RrsigFields::LEN); // In case we already have a name we need to truncate it
+
+        NameBuilder::new_uncompressed(
+            buffer_ptr,
+            self,
+            |builder| {
+                AnswerTypeRrsigBuilder {
+                    parent: builder.parent,
+                    finalizer: builder.finalizer,
+                    buffer: builder.buffer,
+                    start: builder.start
+                }
+            },
+        )
+    }
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+> AnswerTypeRrsigBuilder<'a, B, P, O, F, true, false> {
+    #[inline(always)]
+    pub fn signature(self, signature: &[u8]) -> Result<AnswerTypeRrsigBuilder<
+        'a, B, P, O, F,
+        true,
+        true,
+    >, ()> {
+        self.buffer.extend_from_slice(signature)?;
+
+        Ok(AnswerTypeRrsigBuilder { parent: self.parent, finalizer: self.finalizer, buffer: self.buffer, start: self.start })
+    }
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+> AnswerTypeRrsigBuilder<'a, B, P, O, F, true, true> {
+    #[inline(always)]
+    pub fn finish(self) -> O {
+        (self.finalizer)(self.parent)
+    }
+}
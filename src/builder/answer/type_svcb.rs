@@ -0,0 +1,190 @@
+use crate::{
+    ExtendableBuffer, NameBuilder, SVC_PARAM_ALPN, SVC_PARAM_ECH, SVC_PARAM_IPV4HINT, SVC_PARAM_IPV6HINT,
+    SVC_PARAM_MANDATORY, SVC_PARAM_NO_DEFAULT_ALPN, SVC_PARAM_PORT,
+};
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// Builds an `SVCB` or `HTTPS` record. The SvcParams are written after the target, in
+/// strictly increasing order of their keys.
+///
+/// According to [RFC 9460 Section 2.2](https://tools.ietf.org/rfc/rfc9460#section-2.2).
+pub struct AnswerTypeSvcbBuilder<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+    const PRIORITY: bool,
+    const TARGET: bool,
+> {
+    parent: P,
+    finalizer: F,
+    buffer: &'a mut B,
+    start: usize,
+    last_key: Option<u16>,
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+> AnswerTypeSvcbBuilder<'a, B, P, O, F, false, false> {
+    #[inline(always)]
+    pub fn new(
+        buffer: &'a mut B,
+        parent: P,
+        finalizer: F,
+    ) -> AnswerTypeSvcbBuilder<
+        'a, B, P, O, F,
+        false,
+        false,
+    > {
+        let start = buffer.len();
+        AnswerTypeSvcbBuilder { parent, finalizer, buffer, start, last_key: None }
+    }
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+    const PRIORITY: bool,
+    const TARGET: bool,
+> AnswerTypeSvcbBuilder<'a, B, P, O, F, PRIORITY, TARGET> {
+    /// Sets the priority, 0 marks an alias to the target.
+    #[inline(always)]
+    pub fn priority(self, priority: u16) -> Result<AnswerTypeSvcbBuilder<
+        'a, B, P, O, F,
+        true,
+        TARGET,
+    >, ()> {
+        self.buffer.bytes_mut_at(self.start, 2)?.copy_from_slice(&priority.to_be_bytes());
+
+        Ok(AnswerTypeSvcbBuilder { parent: self.parent, finalizer: self.finalizer, buffer: self.buffer, start: self.start, last_key: self.last_key })
+    }
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+    const TARGET: bool,
+> AnswerTypeSvcbBuilder<'a, B, P, O, F, true, TARGET> {
+    /// Writes the target name after the priority, which must not be compressed. Removes the
+    /// SvcParams written so far.
+    #[inline(always)]
+    pub fn target(self) -> NameBuilder<
+        'a,
+        B,
+        Self,
+        AnswerTypeSvcbBuilder<'a, B, P, O, F, true, true>,
+        fn(AnswerTypeSvcbBuilder<'a, B, P, O, F, true, TARGET>) -> AnswerTypeSvcbBuilder<'a, B, P, O, F, true, true>,
+    > {
+        let buffer_ptr = unsafe { &mut *(self.buffer as *mut B) };
+        buffer_ptr.truncate(self.start + 2); // In case we already have a name we need to truncate it
+
+        NameBuilder::new_uncompressed(
+            buffer_ptr,
+            self,
+            |builder| {
+                AnswerTypeSvcbBuilder {
+                    parent: builder.parent,
+                    finalizer: builder.finalizer,
+                    buffer: builder.buffer,
+                    start: builder.start,
+                    last_key: None,
+                }
+            },
+        )
+    }
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+    const PRIORITY: bool,
+> AnswerTypeSvcbBuilder<'a, B, P, O, F, PRIORITY, true> {
+    /// Writes the keys of the parameters a client must understand, in increasing order.
+    pub fn mandatory(self, keys: &[u16]) -> Result<Self, ()> {
+        self.param_with(SVC_PARAM_MANDATORY, keys.len() * 2, |buffer| {
+            keys.iter().try_for_each(|key| buffer.extend_from_slice(&key.to_be_bytes()))
+        })
+    }
+
+    /// Writes the ALPN identifiers of the supported protocols, e.g. `h2` and `h3`.
+    pub fn alpn(self, ids: &[&[u8]]) -> Result<Self, ()> {
+        if ids.iter().any(|id| id.is_empty() || id.len() > 255) {
+            return Err(());
+        }
+        let len = ids.iter().map(|id| 1 + id.len()).sum();
+        self.param_with(SVC_PARAM_ALPN, len, |buffer| {
+            ids.iter().try_for_each(|id| {
+                buffer.extend_from_slice(&[id.len() as u8])?;
+                buffer.extend_from_slice(id)
+            })
+        })
+    }
+
+    #[inline(always)]
+    pub fn no_default_alpn(self) -> Result<Self, ()> {
+        self.param(SVC_PARAM_NO_DEFAULT_ALPN, &[])
+    }
+
+    #[inline(always)]
+    pub fn port(self, port: u16) -> Result<Self, ()> {
+        self.param(SVC_PARAM_PORT, &port.to_be_bytes())
+    }
+
+    pub fn ipv4hint(self, addrs: &[Ipv4Addr]) -> Result<Self, ()> {
+        self.param_with(SVC_PARAM_IPV4HINT, addrs.len() * 4, |buffer| {
+            addrs.iter().try_for_each(|addr| buffer.extend_from_slice(&addr.octets()))
+        })
+    }
+
+    /// Writes an encrypted ClientHello configuration list.
+    #[inline(always)]
+    pub fn ech(self, config_list: &[u8]) -> Result<Self, ()> {
+        self.param(SVC_PARAM_ECH, config_list)
+    }
+
+    pub fn ipv6hint(self, addrs: &[Ipv6Addr]) -> Result<Self, ()> {
+        self.param_with(SVC_PARAM_IPV6HINT, addrs.len() * 16, |buffer| {
+            addrs.iter().try_for_each(|addr| buffer.extend_from_slice(&addr.octets()))
+        })
+    }
+
+    /// Writes a parameter with its raw value. Fails if the key is not greater than the key
+    /// of the previous parameter.
+    #[inline(always)]
+    pub fn param(self, key: u16, value: &[u8]) -> Result<Self, ()> {
+        self.param_with(key, value.len(), |buffer| buffer.extend_from_slice(value))
+    }
+
+    fn param_with(mut self, key: u16, len: usize, value: impl FnOnce(&mut B) -> Result<(), ()>) -> Result<Self, ()> {
+        if self.last_key.is_some_and(|last| key <= last) {
+            return Err(());
+        }
+        let len = u16::try_from(len).map_err(|_| ())?;
+        let start = self.buffer.len();
+        let result = self.buffer.extend_from_slice(&key.to_be_bytes())
+            .and_then(|_| self.buffer.extend_from_slice(&len.to_be_bytes()))
+            .and_then(|_| value(self.buffer));
+        if result.is_err() {
+            self.buffer.truncate(start);
+            return Err(());
+        }
+        self.last_key = Some(key);
+
+        Ok(self)
+    }
+}
+
+impl<
+    'a,
+    B: ExtendableBuffer + ?Sized,
+    P, O, F: Fn(P) -> O,
+> AnswerTypeSvcbBuilder<'a, B, P, O, F, true, true> {
+    #[inline(always)]
+    pub fn finish(self) -> O {
+        (self.finalizer)(self.parent)
+    }
+}
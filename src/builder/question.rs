@@ -75,9 +75,23 @@ impl<
         TYPE,
         true,
     >, ()> {
-        let value: u16 = value.into();
+        self.qclass_value(value.into())
+    }
+
+    /// Sets the class from its raw value, e.g. to echo a question of a class without a
+    /// [`QClass`] variant.
+    #[inline(always)]
+    pub(crate) fn qclass_value(self, value: u16) -> Result<QuestionBuilder<
+        'a, B, P, O, F,
+        true,
+        TYPE,
+        true,
+    >, ()> {
+        let mut value = value.to_be_bytes();
+        value[0] &= 0b0111_1111; // Clear the unicast response bit.
+        value[0] |= self.buffer.bytes().get(self.name_end + 2).map_or(0, |byte| byte & 0b1000_0000); // Copy the unicast response bit.
         self.buffer.bytes_mut_at(self.name_end + 2, 2)?
-            .copy_from_slice(&value.to_be_bytes());
+            .copy_from_slice(&value);
 
         Ok(QuestionBuilder {
             parent: self.parent,
@@ -87,6 +101,15 @@ impl<
         })
     }
 
+    /// Sets the mDNS QU bit, asking for a unicast response.
+    #[inline(always)]
+    pub fn unicast_response(self, value: bool) -> Result<Self, ()> {
+        let byte = self.buffer.bytes_mut_at(self.name_end + 2, 1)?;
+        byte[0] = (byte[0] & 0b0111_1111) | ((value as u8) << 7);
+
+        Ok(self)
+    }
+
     #[inline(always)]
     pub fn qtype(self, value: QType) -> Result<QuestionBuilder<
         'a, B, P, O, F,
@@ -94,7 +117,18 @@ impl<
         true,
         CLASS,
     >, ()> {
-        let value: u16 = value.into();
+        self.qtype_value(value.into())
+    }
+
+    /// Sets the type from its raw value, e.g. to echo a question of a type without a
+    /// [`QType`] variant.
+    #[inline(always)]
+    pub(crate) fn qtype_value(self, value: u16) -> Result<QuestionBuilder<
+        'a, B, P, O, F,
+        true,
+        true,
+        CLASS,
+    >, ()> {
         self.buffer.bytes_mut_at(self.name_end, 2)?
             .copy_from_slice(&value.to_be_bytes());
 
@@ -0,0 +1,330 @@
+use crate::{
+    answer::write_nsec3,
+    AClass, Buffer, Message, AType, AdditionalSection, Answer, AnswersSection, AuthoritySection, ExtendableBuffer, MessageBody,
+    Name, NamePart, Question, QuestionsSection,
+};
+use arrayvec::ArrayVec;
+
+/// The most names remembered as compression targets, later names are written without
+/// pointing into them.
+const MAX_TARGETS: usize = 256;
+
+/// The most labels of a name, the longest name of 255 bytes has 127 single-byte labels.
+const MAX_LABELS: usize = 128;
+
+/// Writes names compressed against the names already in a message.
+///
+/// According to [RFC 1035 Section 4.1.4](https://tools.ietf.org/rfc/rfc1035#section-4.1.4).
+pub(crate) struct NameCompressor {
+    targets: ArrayVec<u16, MAX_TARGETS>,
+}
+
+impl NameCompressor {
+    /// Collects the names of the questions and records of a message, including the names in
+    /// the data of records which may be compressed in mDNS.
+    ///
+    /// According to [RFC 6762 Section 18.14](https://tools.ietf.org/rfc/rfc6762#section-18.14).
+    pub(crate) fn new(bytes: &[u8]) -> Result<Self, ()> {
+        let mut compressor = Self { targets: ArrayVec::new() };
+        let count = |i: usize| Ok::<_, ()>(u16::from_be_bytes(bytes.get(i..i + 2).ok_or(())?.try_into().map_err(|_| ())?));
+        let records = count(6)? as usize + count(8)? as usize + count(10)? as usize;
+
+        let mut i = 12;
+        for _ in 0..count(4)? {
+            compressor.push(i);
+            Name::parse(bytes, &mut i)?;
+            i += 4;
+        }
+        for _ in 0..records {
+            compressor.push(i);
+            Name::parse(bytes, &mut i)?;
+            let atype = count(i)?;
+            let data = i + 10;
+            match atype {
+                // NS, CNAME, PTR and NSEC.
+                2 | 5 | 12 | 47 => compressor.push(data),
+                // SOA.
+                6 => {
+                    let mut j = data;
+                    compressor.push(j);
+                    Name::parse(bytes, &mut j)?;
+                    compressor.push(j);
+                }
+                // MX.
+                15 => compressor.push(data + 2),
+                // SRV.
+                33 => compressor.push(data + 6),
+                _ => {}
+            }
+            i = data + count(i + 8)? as usize;
+        }
+
+        Ok(compressor)
+    }
+
+    fn push(&mut self, offset: usize) {
+        // Pointers have 14 bits.
+        if offset < 0x4000 {
+            let _ = self.targets.try_push(offset as u16);
+        }
+    }
+
+    /// Writes a name, pointing to the longest suffix it shares with a known name, and
+    /// remembers it as a target for later names.
+    pub(crate) fn write<B: ExtendableBuffer + ?Sized>(&mut self, name: &Name<'_>, buffer: &mut B) -> Result<(), ()> {
+        let mut labels = ArrayVec::<&[u8], MAX_LABELS>::new();
+        for label in name.labels() {
+            labels.try_push(label).map_err(|_| ())?;
+        }
+
+        let bytes = buffer.bytes();
+        let mut best: Option<(usize, u16)> = None;
+        for target in &self.targets {
+            // Every label of the uncompressed part of a name starts a suffix.
+            let mut j = *target as usize;
+            while let Some(&len) = bytes.get(j) {
+                // Suffixes past the 14 bits of a pointer cannot be pointed to.
+                if len == 0 || len & 0b1100_0000 != 0 || j >= 0x4000 {
+                    break;
+                }
+                let mut suffix = ArrayVec::<&[u8], MAX_LABELS>::new();
+                for label in Name::parse(bytes, &mut j.clone())?.labels() {
+                    suffix.try_push(label).map_err(|_| ())?;
+                }
+                if let Some(start) = labels.len().checked_sub(suffix.len()) {
+                    if labels[start..] == suffix[..] && best.is_none_or(|(best, _)| start < best) {
+                        best = Some((start, j as u16));
+                    }
+                }
+                j += 1 + len as usize;
+            }
+        }
+
+        let offset = buffer.len();
+        let (literal, pointer) = match best {
+            Some((start, pointer)) => (start, Some(pointer)),
+            None => (labels.len(), None),
+        };
+        for label in &labels[..literal] {
+            label.to_bytes(buffer)?;
+        }
+        match pointer {
+            Some(pointer) => buffer.extend_from_slice(&(pointer | 0xc000).to_be_bytes())?,
+            None => buffer.extend_from_slice(&[0])?,
+        }
+        if literal > 0 {
+            self.push(offset);
+        }
+
+        Ok(())
+    }
+}
+
+/// Appends to the message in the buffer with a compressor knowing its names. Nothing is
+/// written if it fails.
+fn append<B: ExtendableBuffer + ?Sized>(
+    buffer: &mut B,
+    write: impl FnOnce(&mut B, &mut NameCompressor) -> Result<(), ()>,
+) -> Result<(), ()> {
+    let start = buffer.len();
+    let result = NameCompressor::new(buffer.bytes()).and_then(|mut compressor| write(buffer, &mut compressor));
+    if result.is_err() {
+        buffer.truncate(start);
+    }
+
+    result
+}
+
+fn write_question<B: ExtendableBuffer + ?Sized>(
+    buffer: &mut B,
+    question: &Question<'_>,
+    compressor: &mut NameCompressor,
+) -> Result<(), ()> {
+    compressor.write(question.name(), buffer)?;
+    let class = question.qclass_value() | if question.unicast_response() { 0x8000 } else { 0 };
+    buffer.extend_from_slice(&question.qtype_value().to_be_bytes())?;
+    buffer.extend_from_slice(&class.to_be_bytes())
+}
+
+fn write_record<B: ExtendableBuffer + ?Sized>(
+    buffer: &mut B,
+    record: &Answer<'_>,
+    compressor: &mut NameCompressor,
+) -> Result<(), ()> {
+    compressor.write(record.name(), buffer)?;
+
+    let class = match record.atype() {
+        // The class of an OPT record holds the payload size.
+        AType::OPT(udp_payload_size, _, _, _) => *udp_payload_size,
+        _ if record.aclass() == AClass::Unknown => return Err(()),
+        _ => u16::from(record.aclass()) | if record.cache_flush() { 0x8000 } else { 0 },
+    };
+    buffer.extend_from_slice(&record.atype().type_id()?.to_be_bytes())?;
+    buffer.extend_from_slice(&class.to_be_bytes())?;
+    buffer.extend_from_slice(&record.ttl().to_be_bytes())?;
+    buffer.extend_from_slice(&[0, 0])?;
+
+    let data = buffer.len();
+    match record.atype() {
+        AType::NS(name) | AType::CNAME(name) | AType::PTR(name) => compressor.write(name, buffer)?,
+        AType::SOA(mname, rname, serial, refresh, retry, expire, minimum) => {
+            compressor.write(mname, buffer)?;
+            compressor.write(rname, buffer)?;
+            for value in [serial, refresh, retry, expire, minimum] {
+                buffer.extend_from_slice(&value.to_be_bytes())?;
+            }
+        }
+        AType::MX(preference, exchange) => {
+            buffer.extend_from_slice(&preference.to_be_bytes())?;
+            compressor.write(exchange, buffer)?;
+        }
+        AType::SRV(priority, weight, port, target) => {
+            buffer.extend_from_slice(&priority.to_be_bytes())?;
+            buffer.extend_from_slice(&weight.to_be_bytes())?;
+            buffer.extend_from_slice(&port.to_be_bytes())?;
+            compressor.write(target, buffer)?;
+        }
+        AType::NSEC(next, bitmaps) => {
+            compressor.write(next, buffer)?;
+            buffer.extend_from_slice(bitmaps)?;
+        }
+        // Names in the data of DNSSEC and service binding records must not be compressed.
+        AType::RRSIG(fields, signer, signature) => {
+            buffer.extend_from_slice(&fields.to_bytes())?;
+            signer.to_bytes(buffer)?;
+            buffer.extend_from_slice(&[0])?;
+            buffer.extend_from_slice(signature)?;
+        }
+        AType::DS(first, a, b, data) | AType::DNSKEY(first, a, b, data) => {
+            buffer.extend_from_slice(&first.to_be_bytes())?;
+            buffer.extend_from_slice(&[*a, *b])?;
+            buffer.extend_from_slice(data)?;
+        }
+        AType::NSEC3(..) | AType::NSEC3PARAM(..) => write_nsec3(record.atype(), buffer)?,
+        AType::SVCB(priority, target, params) | AType::HTTPS(priority, target, params) => {
+            buffer.extend_from_slice(&priority.to_be_bytes())?;
+            target.to_bytes(buffer)?;
+            buffer.extend_from_slice(&[0])?;
+            buffer.extend_from_slice(params)?;
+        }
+        AType::A(addr) => buffer.extend_from_slice(&addr.to_be_bytes())?,
+        AType::AAAA(addr) => buffer.extend_from_slice(addr)?,
+        AType::TXT(data) | AType::OPT(_, _, _, data) | AType::Unknown(_, data) => buffer.extend_from_slice(data)?,
+    }
+
+    let len = u16::try_from(buffer.len() - data).map_err(|_| ())?;
+    buffer.bytes_mut_at(data - 2, 2)?.copy_from_slice(&len.to_be_bytes());
+
+    Ok(())
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, QuestionsSection, true> {
+    /// Appends a parsed question, e.g. from another message, compressing its name against
+    /// the names already in this message.
+    pub fn append_question_from(mut self, question: &Question<'_>) -> Result<Self, ()> {
+        append(unsafe { &mut *(self.buffer as *mut B) }, |buffer, compressor| {
+            write_question(buffer, question, compressor)
+        })?;
+        let header = unsafe { self.header_mut() };
+        header.set_question_count(header.question_count() + 1);
+        Ok(self)
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, AnswersSection, true> {
+    /// Appends a parsed record, e.g. from another message, compressing its names against the
+    /// names already in this message.
+    pub fn append_record(mut self, record: &Answer<'_>) -> Result<Self, ()> {
+        append(unsafe { &mut *(self.buffer as *mut B) }, |buffer, compressor| {
+            write_record(buffer, record, compressor)
+        })?;
+        let header = unsafe { self.header_mut() };
+        header.set_answer_count(header.answer_count() + 1);
+
+        Ok(self)
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, AuthoritySection, true> {
+    /// Appends a parsed record like [`MessageBody::append_record`] of the answer section.
+    pub fn append_record(mut self, record: &Answer<'_>) -> Result<Self, ()> {
+        append(unsafe { &mut *(self.buffer as *mut B) }, |buffer, compressor| {
+            write_record(buffer, record, compressor)
+        })?;
+        let header = unsafe { self.header_mut() };
+        header.set_name_server_count(header.name_server_count() + 1);
+
+        Ok(self)
+    }
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> MessageBody<'a, B, AdditionalSection, true> {
+    /// Appends a parsed record like [`MessageBody::append_record`] of the answer section.
+    pub fn append_record(mut self, record: &Answer<'_>) -> Result<Self, ()> {
+        append(unsafe { &mut *(self.buffer as *mut B) }, |buffer, compressor| {
+            write_record(buffer, record, compressor)
+        })?;
+        let header = unsafe { self.header_mut() };
+        header.set_additional_records_count(header.additional_records_count() + 1);
+
+        Ok(self)
+    }
+}
+
+/// Writes a message into an empty buffer with every name pointing to the longest suffix it
+/// shares with a name written before it, returning how many bytes smaller the copy is. It is
+/// only larger if the original pointed into data this crate does not know to hold names.
+///
+/// The header is copied as is. Fails if the message is malformed or holds questions or
+/// records whose class cannot be written, leaving the buffer empty.
+pub fn recompress<B: Buffer + ?Sized, const WRITE: bool, O: ExtendableBuffer + ?Sized>(
+    message: &Message<'_, B, WRITE>,
+    buffer: &mut O,
+) -> Result<isize, ()> {
+    let result = write_recompressed(message, buffer);
+    if result.is_err() {
+        buffer.truncate(0);
+    }
+
+    result
+}
+
+fn write_recompressed<B: Buffer + ?Sized, const WRITE: bool, O: ExtendableBuffer + ?Sized>(
+    message: &Message<'_, B, WRITE>,
+    buffer: &mut O,
+) -> Result<isize, ()> {
+    *Message::new_mut(&mut *buffer)?.header_mut()? = *message.header()?;
+    // The counts of the copied header do not match the buffer yet.
+    let mut compressor = NameCompressor { targets: ArrayVec::new() };
+
+    let mut body = message.body()?;
+    for question in body.questions() {
+        write_question(buffer, &question, &mut compressor)?;
+    }
+    if body.question_count != 0 {
+        return Err(());
+    }
+    let mut body = body.to_answer_section();
+    for record in body.answers() {
+        write_record(buffer, &record, &mut compressor)?;
+    }
+    if body.answer_count != 0 {
+        return Err(());
+    }
+    let mut body = body.to_authority_section();
+    for record in body.authorities() {
+        write_record(buffer, &record, &mut compressor)?;
+    }
+    if body.authority_count != 0 {
+        return Err(());
+    }
+    let mut body = body.to_additional_section();
+    for record in body.additionals() {
+        write_record(buffer, &record, &mut compressor)?;
+    }
+    if body.additional_count != 0 {
+        return Err(());
+    }
+
+    Ok(message.bytes().len() as isize - buffer.len() as isize)
+}
@@ -0,0 +1,122 @@
+use crate::{Message, Responder, ResponderState, ResponseMode, MDNS_IPV4, MDNS_IPV6, MDNS_PORT};
+use arrayvec::ArrayVec;
+use core::net::SocketAddr;
+use embassy_futures::select::{select, Either};
+use embassy_net::udp::{RecvError, SendError, UdpSocket};
+use embassy_net::{IpEndpoint, Stack};
+use embassy_time::{Instant, Timer};
+
+/// A UDP socket bound to the mDNS port and joined to the multicast group, as used by
+/// [`run_responder`].
+///
+/// It is implemented for `embassy_net::udp::UdpSocket`, see [`bind_mdns`]. `recv_from` has
+/// to be cancel-safe, as it is raced against the timers of the responder.
+#[allow(async_fn_in_trait)]
+pub trait MdnsTransport {
+    type Error;
+
+    /// Receives a datagram, returning its length and source.
+    async fn recv_from(&mut self, buffer: &mut [u8]) -> Result<(usize, SocketAddr), Self::Error>;
+
+    /// Sends a datagram to the given address.
+    async fn send_to(&mut self, datagram: &[u8], destination: SocketAddr) -> Result<(), Self::Error>;
+}
+
+impl MdnsTransport for UdpSocket<'_> {
+    type Error = SendError;
+
+    /// Receives a datagram, dropping those that do not fit the buffer.
+    async fn recv_from(&mut self, buffer: &mut [u8]) -> Result<(usize, SocketAddr), Self::Error> {
+        loop {
+            match UdpSocket::recv_from(self, buffer).await {
+                Ok((len, meta)) => return Ok((len, SocketAddr::new(meta.endpoint.addr.into(), meta.endpoint.port))),
+                Err(RecvError::Truncated) => continue,
+            }
+        }
+    }
+
+    async fn send_to(&mut self, datagram: &[u8], destination: SocketAddr) -> Result<(), Self::Error> {
+        UdpSocket::send_to(self, datagram, IpEndpoint::from(destination)).await
+    }
+}
+
+/// Binds a socket to the mDNS port, if it is not bound yet, and joins the IPv4 and IPv6 mDNS
+/// multicast groups on the stack. Multicasts are sent with a hop limit of 255.
+pub fn bind_mdns(stack: Stack<'_>, socket: &mut UdpSocket<'_>) -> Result<(), ()> {
+    if !socket.is_open() {
+        socket.bind(MDNS_PORT).map_err(|_| ())?;
+    }
+    socket.set_hop_limit(Some(255));
+    stack.join_multicast_group(MDNS_IPV4).map_err(|_| ())?;
+    stack.join_multicast_group(MDNS_IPV6).map_err(|_| ())
+}
+
+/// Why [`run_responder`] stopped.
+#[derive(Debug, PartialEq)]
+pub enum RunError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// Another host uses one of the names of the responder.
+    Conflict,
+    /// A probe or announcement does not fit in the buffers.
+    BufferTooSmall,
+}
+
+impl<E> From<E> for RunError<E> {
+    #[inline(always)]
+    fn from(error: E) -> Self {
+        RunError::Transport(error)
+    }
+}
+
+/// Runs a [`Responder`] on a transport until it fails or a conflict is detected, probing,
+/// announcing and answering queries.
+///
+/// Datagrams of up to `N` bytes are received and sent, both buffers live in the future.
+/// Malformed messages and responses that do not fit are dropped, while probes and
+/// announcements that do not fit stop the responder.
+pub async fn run_responder<T: MdnsTransport, const N: usize>(
+    mut responder: Responder<'_>,
+    mut transport: T,
+) -> Result<(), RunError<T::Error>> {
+    let group = SocketAddr::new(MDNS_IPV4.into(), MDNS_PORT);
+    let mut rx = [0; N];
+    let mut tx = ArrayVec::<u8, N>::new();
+
+    loop {
+        if responder.state() == ResponderState::Conflict {
+            return Err(RunError::Conflict);
+        }
+
+        let now = Instant::now().as_millis();
+        match responder.poll(now, &mut tx) {
+            Ok(Some(message)) => {
+                transport.send_to(message.bytes(), group).await?;
+                continue;
+            }
+            Ok(None) => {}
+            // The deadline has not moved, so retrying would spin on the same message.
+            Err(()) => return Err(RunError::BufferTooSmall),
+        }
+
+        let timer = match responder.deadline() {
+            Some(deadline) => Timer::at(Instant::from_millis(deadline)),
+            None => Timer::at(Instant::MAX),
+        };
+        let (len, source) = match select(transport.recv_from(&mut rx), timer).await {
+            Either::First(received) => received?,
+            Either::Second(()) => continue,
+        };
+
+        let Ok(message) = Message::new(&rx[..len]) else {
+            continue;
+        };
+        if let Ok(Some((response, mode))) = responder.handle(&message, source, &mut tx) {
+            let destination = match mode {
+                ResponseMode::Multicast => group,
+                _ => source,
+            };
+            transport.send_to(response.bytes(), destination).await?;
+        }
+    }
+}
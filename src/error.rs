@@ -0,0 +1,31 @@
+use core::fmt::{Display, Formatter};
+
+/// The error of a failed parse or write.
+///
+/// The parsers, e.g. [`Message::new`](crate::Message::new) and [`Answer::parse`](crate::Answer::parse),
+/// return it and it implements `std::error::Error` with the `std` feature. The builders report
+/// failures as `()`, which converts into it, so `?` can be used on both in functions returning
+/// `Result<_, Error>`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Error;
+
+impl From<()> for Error {
+    #[inline(always)]
+    fn from(_: ()) -> Self {
+        Error
+    }
+}
+
+impl From<Error> for () {
+    #[inline(always)]
+    fn from(_: Error) -> Self {}
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("malformed mDNS packet or insufficient buffer space")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,156 @@
+use crate::{Buffer, Error, ExtendableBuffer, Message, MutBuffer};
+use core::mem::size_of;
+
+/// The size of the length prefix of a message sent over a stream.
+pub const FRAME_PREFIX_LEN: usize = size_of::<u16>();
+
+/// A message written after a 2-byte length prefix, as sent over TCP, e.g. to retry a query
+/// whose response was truncated.
+///
+/// The prefix is reserved at the end of the wrapped buffer, so several messages can be
+/// written into one stream buffer, and is kept up to date as the message is written. The
+/// wrapper is empty, so it can be passed to [`Message::new_mut`]. Writes fail once the
+/// message would exceed 65535 bytes.
+///
+/// According to [RFC 1035 Section 4.2.2](https://tools.ietf.org/rfc/rfc1035#section-4.2.2).
+pub struct Framed<'a, B: ExtendableBuffer + ?Sized> {
+    buffer: &'a mut B,
+    start: usize,
+}
+
+impl<'a, B: ExtendableBuffer + ?Sized> Framed<'a, B> {
+    /// Reserves the length prefix at the end of the buffer.
+    pub fn new(buffer: &'a mut B) -> Result<Self, ()> {
+        let start = buffer.len();
+        buffer.extend_from_slice(&[0; FRAME_PREFIX_LEN])?;
+
+        Ok(Self { buffer, start })
+    }
+
+    /// Returns the message with its length prefix.
+    #[inline(always)]
+    pub fn frame(&self) -> &[u8] {
+        &self.buffer.bytes()[self.start..]
+    }
+
+    /// Returns the wrapped buffer, which ends with the finished frame.
+    #[inline(always)]
+    pub fn into_inner(self) -> &'a mut B {
+        self.buffer
+    }
+
+    #[inline(always)]
+    fn data(&self) -> usize {
+        self.start + FRAME_PREFIX_LEN
+    }
+
+    /// Fails if the message would exceed 65535 bytes, otherwise writes its new length.
+    fn set_len(&mut self, len: usize) -> Result<(), ()> {
+        let len = u16::try_from(len).map_err(|_| ())?;
+        self.buffer.bytes_mut_at(self.start, FRAME_PREFIX_LEN)?
+            .copy_from_slice(&len.to_be_bytes());
+
+        Ok(())
+    }
+}
+
+impl<B: ExtendableBuffer + ?Sized> Buffer for Framed<'_, B> {
+    fn len(&self) -> usize {
+        self.buffer.len() - self.data()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.buffer.bytes()[self.data()..]
+    }
+
+    fn bytes_at(&self, offset: usize, size: usize) -> Result<&[u8], ()> {
+        self.buffer.bytes_at(self.data() + offset, size)
+    }
+}
+
+impl<B: ExtendableBuffer + ?Sized> MutBuffer for Framed<'_, B> {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        let data = self.data();
+        &mut self.buffer.bytes_mut()[data..]
+    }
+}
+
+impl<B: ExtendableBuffer + ?Sized> ExtendableBuffer for Framed<'_, B> {
+    fn bytes_mut_at(&mut self, offset: usize, size: usize) -> Result<&mut [u8], ()> {
+        self.set_len(self.len().max(offset + size))?;
+        let data = self.data();
+        self.buffer.bytes_mut_at(data + offset, size)
+    }
+
+    fn extend_from_slice(&mut self, slice: &[u8]) -> Result<(), ()> {
+        let len = self.len();
+        self.set_len(len + slice.len())?;
+        let result = self.buffer.extend_from_slice(slice);
+        if result.is_err() {
+            self.set_len(len)?;
+        }
+
+        result
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            let data = self.data();
+            self.buffer.truncate(data + len);
+            // The length fits the prefix, as it only shrinks.
+            let _ = self.set_len(len);
+        }
+    }
+}
+
+/// An iterator over the complete length-prefixed messages at the start of a stream buffer.
+///
+/// A message that was only partly received ends the iteration, its bytes are left in
+/// [`Frames::remainder`] until more data is read. Frames too short for a header yield an
+/// error and are skipped.
+pub struct Frames<'a> {
+    bytes: &'a [u8],
+    consumed: usize,
+}
+
+impl<'a> Frames<'a> {
+    #[inline(always)]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, consumed: 0 }
+    }
+
+    /// Returns the number of bytes of the frames returned so far, which the caller can drop
+    /// from the stream buffer.
+    #[inline(always)]
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+
+    /// Returns the bytes after the frames returned so far.
+    #[inline(always)]
+    pub fn remainder(&self) -> &'a [u8] {
+        &self.bytes[self.consumed..]
+    }
+
+    /// Returns how many more bytes have to be read to complete the next frame, or at least
+    /// its prefix, and 0 if it is complete already.
+    pub fn missing(&self) -> usize {
+        match self.remainder() {
+            [high, low, data @ ..] => (u16::from_be_bytes([*high, *low]) as usize).saturating_sub(data.len()),
+            rest => FRAME_PREFIX_LEN - rest.len(),
+        }
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<Message<'a, [u8], false>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remainder = self.remainder();
+        let len = u16::from_be_bytes([*remainder.first()?, *remainder.get(1)?]) as usize;
+        let frame = remainder.get(FRAME_PREFIX_LEN..FRAME_PREFIX_LEN + len)?;
+        self.consumed += FRAME_PREFIX_LEN + len;
+
+        Some(Message::new(frame))
+    }
+}
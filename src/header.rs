@@ -1,7 +1,7 @@
 use derive_more::Display;
 
 /// A mDNS header.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 #[repr(C)]
 pub struct Header {
     id: [u8; 2],
@@ -87,7 +87,7 @@ impl Header {
 
     #[inline(always)]
     pub fn opcode(&self) -> HeaderOpcode {
-        (self.flags[0] & 0b01111000).into()
+        ((self.flags[0] & 0b01111000) >> 3).into()
     }
 
     #[inline(always)]
@@ -238,6 +238,7 @@ impl core::fmt::Debug for Header {
 
 /// The kind of a DNS header.
 #[derive(Copy, Clone, Debug, Display, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HeaderKind {
     Query,
     Response,
@@ -245,6 +246,7 @@ pub enum HeaderKind {
 
 /// A DNS opcode.
 #[derive(Copy, Clone, Debug, Display, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HeaderOpcode {
     Query,
     InverseQuery,
@@ -282,6 +284,7 @@ impl From<HeaderOpcode> for u8 {
 
 /// A DNS response code.
 #[derive(Copy, Clone, Debug, Display, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HeaderResponseCode {
     NoError,
     FormatError,
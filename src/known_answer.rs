@@ -0,0 +1,113 @@
+use crate::{AType, Answer, Buffer, MessageBody, QuestionsSection};
+
+/// The known-answer section of a query.
+///
+/// According to [RFC 6762 Section 7.1](https://tools.ietf.org/rfc/rfc6762#section-7.1)
+/// a responder must not answer with a record the querier already listed in its answer
+/// section, as long as the listed TTL is at least half of the true TTL.
+pub struct KnownAnswers<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    count: u16,
+}
+
+impl<'a> KnownAnswers<'a> {
+    /// Reads the known answers of a parsed query, skipping any remaining questions.
+    pub fn new<B: Buffer + ?Sized>(query: MessageBody<'a, B, QuestionsSection, false>) -> Self {
+        let body = query.to_answer_section();
+
+        Self {
+            bytes: unsafe { &*body.buffer }.bytes(),
+            position: body.position,
+            count: body.answer_count,
+        }
+    }
+
+    /// Whether the querier already knows about the record, so it must not be sent.
+    pub fn suppresses(&self, record: &Answer<'_>) -> bool {
+        let mut position = self.position;
+        for _ in 0..self.count {
+            let known = match Answer::parse(self.bytes, &mut position) {
+                Ok(known) => known,
+                Err(_) => return false,
+            };
+
+            if known.aclass() == record.aclass()
+                && known.name().eq_ignore_ascii_case(record.name())
+                && rdata_eq(known.atype(), record.atype())
+                && known.ttl() as u64 * 2 >= record.ttl() as u64
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Filters out the records the querier already knows about.
+    #[inline(always)]
+    pub fn filter<'r, I: IntoIterator<Item = Answer<'r>>>(self, records: I) -> KnownAnswerFilter<'a, I::IntoIter> {
+        KnownAnswerFilter {
+            known_answers: self,
+            records: records.into_iter(),
+        }
+    }
+}
+
+/// Filters the records of a response by the known answers of a query.
+///
+/// Names are compared case-insensitively and the record data regardless of compression.
+#[inline(always)]
+pub fn suppress_known_answers<'a, 'r, B: Buffer + ?Sized, I: IntoIterator<Item = Answer<'r>>>(
+    query: MessageBody<'a, B, QuestionsSection, false>,
+    records: I,
+) -> KnownAnswerFilter<'a, I::IntoIter> {
+    KnownAnswers::new(query).filter(records)
+}
+
+/// An iterator over the records which are not suppressed by known answers.
+pub struct KnownAnswerFilter<'a, I> {
+    known_answers: KnownAnswers<'a>,
+    records: I,
+}
+
+impl<'r, I: Iterator<Item = Answer<'r>>> Iterator for KnownAnswerFilter<'_, I> {
+    type Item = Answer<'r>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.by_ref().find(|record| !self.known_answers.suppresses(record))
+    }
+}
+
+/// Compares record data, treating embedded names case-insensitively.
+pub(crate) fn rdata_eq(a: &AType<'_>, b: &AType<'_>) -> bool {
+    match (a, b) {
+        (AType::NS(a), AType::NS(b))
+        | (AType::CNAME(a), AType::CNAME(b))
+        | (AType::PTR(a), AType::PTR(b)) => a.eq_ignore_ascii_case(b),
+        (AType::MX(a_preference, a), AType::MX(b_preference, b)) => {
+            a_preference == b_preference && a.eq_ignore_ascii_case(b)
+        }
+        (AType::SRV(a_priority, a_weight, a_port, a), AType::SRV(b_priority, b_weight, b_port, b)) => {
+            a_priority == b_priority && a_weight == b_weight && a_port == b_port && a.eq_ignore_ascii_case(b)
+        }
+        (
+            AType::SOA(a_mname, a_rname, a_serial, a_refresh, a_retry, a_expire, a_minimum),
+            AType::SOA(b_mname, b_rname, b_serial, b_refresh, b_retry, b_expire, b_minimum),
+        ) => {
+            a_mname.eq_ignore_ascii_case(b_mname)
+                && a_rname.eq_ignore_ascii_case(b_rname)
+                && (a_serial, a_refresh, a_retry, a_expire, a_minimum)
+                    == (b_serial, b_refresh, b_retry, b_expire, b_minimum)
+        }
+        (AType::NSEC(a, a_bitmaps), AType::NSEC(b, b_bitmaps)) => a_bitmaps == b_bitmaps && a.eq_ignore_ascii_case(b),
+        (AType::RRSIG(a_fields, a, a_signature), AType::RRSIG(b_fields, b, b_signature)) => {
+            a_fields == b_fields && a_signature == b_signature && a.eq_ignore_ascii_case(b)
+        }
+        (AType::SVCB(a_priority, a, a_params), AType::SVCB(b_priority, b, b_params))
+        | (AType::HTTPS(a_priority, a, a_params), AType::HTTPS(b_priority, b, b_params)) => {
+            a_priority == b_priority && a_params == b_params && a.eq_ignore_ascii_case(b)
+        }
+        (a, b) => a == b,
+    }
+}
@@ -1,20 +1,82 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 mod message;
+mod message_mut;
+mod copy;
+mod error;
+mod slice_writer;
+mod framed;
 mod header;
 mod name;
 mod question;
 mod body;
 mod answer;
+mod svcb;
 mod builder;
+mod service;
+mod known_answer;
+mod response;
+mod reverse;
+mod presentation;
+mod zone;
+mod responder;
+mod srp;
+#[cfg(feature = "alloc")]
+mod owned;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "pcap")]
+mod pcap;
+#[cfg(feature = "socket")]
+mod socket;
+#[cfg(feature = "tokio")]
+mod tokio_socket;
+#[cfg(feature = "smoltcp")]
+mod smoltcp_socket;
+#[cfg(feature = "embassy")]
+mod embassy;
+#[cfg(feature = "embedded-nal")]
+mod nal;
 
 pub use message::*;
+pub use message_mut::*;
+pub use copy::*;
+pub use error::*;
+pub use slice_writer::*;
+pub use framed::*;
 pub use header::*;
 pub use name::*;
 pub use question::*;
 pub use body::*;
 pub use answer::*;
+pub use svcb::*;
 pub use builder::*;
+pub use service::*;
+pub use known_answer::*;
+pub use response::*;
+pub use presentation::*;
+pub use zone::*;
+pub use responder::*;
+pub use srp::*;
+#[cfg(feature = "alloc")]
+pub use owned::*;
+#[cfg(feature = "pcap")]
+pub use pcap::*;
+#[cfg(feature = "socket")]
+pub use socket::*;
+#[cfg(feature = "tokio")]
+pub use tokio_socket::*;
+#[cfg(feature = "smoltcp")]
+pub use smoltcp_socket::*;
+#[cfg(feature = "embassy")]
+pub use embassy::*;
+#[cfg(feature = "embedded-nal")]
+pub use nal::*;
 
 #[cfg(test)]
 mod tests {
@@ -318,4 +380,1997 @@ mod tests {
 
         assert_eq!(write_buffer.as_slice(), data);
     }
+
+    #[test]
+    fn mdns_cache_flush_written_to_type_field() {
+        let mut write_buffer = ArrayVec::<u8, 256>::new_const();
+        let mut message = Message::new_mut(&mut write_buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section();
+        body.append_answer()
+            .name()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ip(&[192, 168, 0, 1]).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+
+        // The type stays A, the cache flush bit is the top bit of the class.
+        assert_eq!(&write_buffer[19..23], &[0x00, 0x01, 0x80, 0x01]);
+        let message = Message::new(write_buffer.as_slice()).unwrap();
+        let mut body = message.body().unwrap().to_answer_section();
+        let answer = body.answers().next().unwrap();
+        assert_eq!(answer.atype(), &AType::A(0xc0a8_0001));
+        assert!(answer.cache_flush());
+        assert_eq!(answer.aclass(), AClass::IN);
+    }
+
+    #[test]
+    fn mdns_service_name() {
+        let mut write_buffer = ArrayVec::<u8, 256>::new_const();
+        let mut message = Message::new_mut(&mut write_buffer).unwrap();
+        let body = message.body_mut().unwrap();
+        body.append_question()
+            .name()
+            .label(ServiceNamePart::new("Bob's Printer. Café".as_bytes(), b"_ipp._tcp", b"local.")).unwrap()
+            .finish().unwrap()
+            .qtype(QType::TXT).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .append_question()
+            .name()
+            .label(ServiceNamePart::service_type(b"_universal._sub._ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .append_question()
+            .name()
+            .label(b"Caf\xc3\xa9").unwrap()
+            .finish().unwrap()
+            .qtype(QType::A).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+
+        let message = Message::new(write_buffer.as_slice()).unwrap();
+        let mut body = message.body().unwrap();
+        let mut questions = body.questions();
+
+        let question = questions.next().unwrap();
+        assert_eq!(question.name(), "Bob's Printer. Café._ipp._tcp.local".as_bytes());
+        let service = ServiceName::parse(question.name()).unwrap();
+        assert_eq!(service.instance(), Some("Bob's Printer. Café".as_bytes()));
+        assert_eq!(service.subtype(), None);
+        assert_eq!(service.service(), b"_ipp");
+        assert_eq!(service.protocol(), b"_tcp");
+        assert_eq!(service.service_type(), b"_ipp._tcp.local".as_slice());
+        assert_eq!(service.domain(), b"local".as_slice());
+
+        let question = questions.next().unwrap();
+        let service = ServiceName::parse(question.name()).unwrap();
+        assert_eq!(service.instance(), None);
+        assert_eq!(service.subtype(), Some(b"_universal".as_slice()));
+        assert_eq!(service.service(), b"_ipp");
+        assert_eq!(service.service_type(), b"_universal._sub._ipp._tcp.local".as_slice());
+        assert_eq!(service.domain(), b"local".as_slice());
+
+        // A name ending in UTF-8 must still be terminated.
+        let question = questions.next().unwrap();
+        assert_eq!(question.name(), "Café".as_bytes());
+        assert_eq!(question.qtype(), QType::A);
+        assert!(ServiceName::parse(question.name()).is_err());
+    }
+
+    #[test]
+    fn mdns_known_answer_suppression() {
+        let mut query_buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut query_buffer).unwrap();
+        let body = message.body_mut().unwrap();
+        let body = body.append_question()
+            .name()
+            .label(ServiceNamePart::service_type(b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .to_answer_section();
+        let body = body.append_answer()
+            .name()
+            .label(ServiceNamePart::service_type(b"_IPP._TCP", b"LOCAL")).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .ptr()
+            .name()
+            .label(ServiceNamePart::new(b"Printer A", b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(2250).unwrap()
+            .finish().unwrap();
+        body.append_answer()
+            .name()
+            .label(ServiceNamePart::service_type(b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .ptr()
+            .name()
+            .label(ServiceNamePart::new(b"Printer B", b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(1000).unwrap()
+            .finish().unwrap();
+
+        let mut response_buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut response_buffer).unwrap();
+        let body = message.body_mut().unwrap();
+        let mut body = body.to_answer_section();
+        for instance in [b"Printer A", b"Printer B", b"Printer C"] {
+            body = body.append_answer()
+                .name()
+                .label(ServiceNamePart::service_type(b"_ipp._tcp", b"local")).unwrap()
+                .finish().unwrap().unwrap()
+                .atype()
+                .ptr()
+                .name()
+                .label(ServiceNamePart::new(instance, b"_ipp._tcp", b"local")).unwrap()
+                .finish().unwrap()
+                .finish().unwrap()
+                .cache_flush(false).unwrap()
+                .aclass(AClass::IN).unwrap()
+                .ttl(4500).unwrap()
+                .finish().unwrap();
+        }
+
+        let query = Message::new(query_buffer.as_slice()).unwrap();
+        let response = Message::new(response_buffer.as_slice()).unwrap();
+        let mut records = response.body().unwrap().to_answer_section();
+        let mut records = suppress_known_answers(query.body().unwrap(), records.answers());
+        // Printer A is known with half its TTL, Printer B's known TTL is too low.
+        let record = records.next().unwrap();
+        assert_eq!(record.atype(), &AType::PTR(Name::parse(b"\x09Printer B\x04_ipp\x04_tcp\x05local\x00", &mut 0).unwrap()));
+        let record = records.next().unwrap();
+        assert_eq!(record.atype(), &AType::PTR(Name::parse(b"\x09Printer C\x04_ipp\x04_tcp\x05local\x00", &mut 0).unwrap()));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn mdns_legacy_unicast_response() {
+        let mut query_buffer = ArrayVec::<u8, 256>::new_const();
+        let mut message = Message::new_mut(&mut query_buffer).unwrap();
+        message.header_mut().unwrap().set_id(0x1234);
+        let body = message.body_mut().unwrap();
+        let body = body.append_question()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .unicast_response(true).unwrap()
+            .qtype(QType::AAAA).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+        // CAA has no QType variant.
+        body.append_question()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .unicast_response(true).unwrap()
+            .qtype_value(257).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+
+        let mut records_buffer = ArrayVec::<u8, 256>::new_const();
+        let mut message = Message::new_mut(&mut records_buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section();
+        let body = body.append_answer().name();
+        let name_ptr = body.ptr();
+        let body = body
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ip(&[169, 254, 0, 1]).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+        body.append_answer()
+            .name()
+            .label(&name_ptr).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .rdata(&AType::AAAA([0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1])).unwrap().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+
+        let query = Message::new(query_buffer.as_slice()).unwrap();
+        assert_eq!(ResponseMode::classify(&query, "169.254.0.2:5353".parse().unwrap()), Ok(ResponseMode::Unicast));
+        assert_eq!(ResponseMode::classify(&query, "169.254.0.2:49152".parse().unwrap()), Ok(ResponseMode::LegacyUnicast));
+
+        let records = Message::new(records_buffer.as_slice()).unwrap();
+        let mut records = records.body().unwrap().to_answer_section();
+        let mut response_buffer = ArrayVec::<u8, 256>::new_const();
+        legacy_unicast_response(&query, records.answers(), &mut response_buffer).unwrap();
+
+        let response = Message::new(response_buffer.as_slice()).unwrap();
+        let header = response.header().unwrap();
+        assert_eq!(header.id(), 0x1234);
+        assert_eq!(header.kind(), HeaderKind::Response);
+        assert!(header.authoritative_answer());
+        assert_eq!(header.question_count(), 2);
+        assert_eq!(header.answer_count(), 2);
+        let mut body = response.body().unwrap();
+        let mut questions = body.questions();
+        let question = questions.next().unwrap();
+        assert_eq!(question.name(), b"host.local".as_slice());
+        assert_eq!(question.qtype(), QType::AAAA);
+        assert_eq!(question.qclass(), QClass::IN);
+        let question = questions.next().unwrap();
+        assert_eq!(question.qtype(), QType::Reserved);
+        assert_eq!(question.qtype_value(), 257);
+        assert_eq!(question.qclass(), QClass::IN);
+        let mut body = body.to_answer_section();
+        let mut answers = body.answers();
+        let answer = answers.next().unwrap();
+        assert_eq!(answer.name(), b"host.local".as_slice());
+        assert_eq!(answer.atype(), &AType::A(u32::from_be_bytes([169, 254, 0, 1])));
+        assert!(!answer.cache_flush());
+        assert_eq!(answer.ttl(), 10);
+        let answer = answers.next().unwrap();
+        assert_eq!(answer.name(), b"host.local".as_slice());
+        assert_eq!(answer.atype(), &AType::AAAA([0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]));
+        assert!(!answer.cache_flush());
+        assert_eq!(answer.ttl(), 10);
+    }
+
+    #[test]
+    fn mdns_reverse_names() {
+        let addresses: [core::net::IpAddr; 2] = [
+            "169.254.12.3".parse().unwrap(),
+            "fe80::1:abcd".parse().unwrap(),
+        ];
+
+        let mut write_buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut write_buffer).unwrap();
+        let mut body = message.body_mut().unwrap();
+        for addr in addresses {
+            body = body.append_question()
+                .name()
+                .reverse(addr).unwrap()
+                .finish().unwrap()
+                .qtype(QType::PTR).unwrap()
+                .qclass(QClass::IN).unwrap()
+                .finish().unwrap();
+        }
+
+        let message = Message::new(write_buffer.as_slice()).unwrap();
+        let mut body = message.body().unwrap();
+        let mut questions = body.questions();
+        let question = questions.next().unwrap();
+        assert_eq!(question.name(), b"3.12.254.169.in-addr.arpa".as_slice());
+        assert_eq!(question.name().reverse_address(), Ok(addresses[0]));
+        assert!(question.name().is_link_local_reverse());
+        let question = questions.next().unwrap();
+        assert_eq!(
+            question.name(),
+            b"d.c.b.a.1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.e.f.ip6.arpa".as_slice()
+        );
+        assert_eq!(question.name().reverse_address(), Ok(addresses[1]));
+        assert!(question.name().is_link_local_reverse());
+
+        let name = Name::parse(b"\x011\x03168\x03192\x07in-addr\x04arpa\x00", &mut 0).unwrap();
+        assert_eq!(name.reverse_address(), Err(()));
+        let name = Name::parse(b"\x011\x010\x03168\x03192\x07in-addr\x04arpa\x00", &mut 0).unwrap();
+        assert_eq!(name.reverse_address(), Ok("192.168.0.1".parse().unwrap()));
+        assert!(!name.is_link_local_reverse());
+    }
+
+    #[test]
+    fn mdns_ip_addr_records() {
+        let ipv4: core::net::Ipv4Addr = "192.168.0.1".parse().unwrap();
+        let ipv6: core::net::Ipv6Addr = "fe80::1".parse().unwrap();
+
+        let mut write_buffer = ArrayVec::<u8, 256>::new_const();
+        let mut message = Message::new_mut(&mut write_buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section();
+        let body = body.append_answer().name();
+        let name_ptr = body.ptr();
+        let body = body
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ipv4(ipv4).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+        body.append_answer()
+            .name()
+            .label(&name_ptr).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .aaaa()
+            .ipv6(ipv6).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+
+        let message = Message::new(write_buffer.as_slice()).unwrap();
+        let mut body = message.body().unwrap().to_answer_section();
+        let mut answers = body.answers();
+        let answer = answers.next().unwrap();
+        assert_eq!(answer.atype().ipv4_addr(), Some(ipv4));
+        assert_eq!(answer.atype().ipv6_addr(), None);
+        let answer = answers.next().unwrap();
+        assert_eq!(answer.name(), b"host.local".as_slice());
+        assert_eq!(answer.atype().ipv6_addr(), Some(ipv6));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn mdns_vec_buffer() {
+        fn write(buffer: &mut alloc::vec::Vec<u8>) -> Result<(), Error> {
+            let mut message = Message::new_mut(buffer)?;
+            message.header_mut()?.set_id(8);
+            message.body_mut()?
+                .append_question()
+                .name()
+                .label(b"_airplay")?
+                .label(b"_tcp")?
+                .label(b"local")?
+                .finish()?
+                .qtype(QType::PTR)?
+                .qclass(QClass::IN)?
+                .finish()?;
+
+            Ok(())
+        }
+
+        let mut buffer = alloc::vec::Vec::new();
+        write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 12 + 21 + 4);
+        let message = Message::new(&buffer).unwrap();
+        assert_eq!(message.header().unwrap().id(), 8);
+        let mut body = message.body().unwrap();
+        assert_eq!(body.questions().next().unwrap().name(), b"_airplay._tcp.local".as_slice());
+        assert_eq!(Message::new(&buffer[..4]).err(), Some(Error));
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn mdns_bytes_buffer() {
+        let mut buffer = bytes::BytesMut::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.header_mut().unwrap().set_id(8);
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"_airplay").unwrap()
+            .label(b"_tcp").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+
+        let buffer = buffer.freeze();
+        let message = Message::new(&buffer).unwrap();
+        assert_eq!(message.header().unwrap().id(), 8);
+        let mut body = message.body().unwrap();
+        assert_eq!(body.questions().next().unwrap().name(), b"_airplay._tcp.local".as_slice());
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn mdns_heapless_buffer() {
+        let mut buffer = heapless::Vec::<u8, 32>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.header_mut().unwrap().set_id(8);
+        let body = message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"_airplay").unwrap()
+            .label(b"_tcp").unwrap()
+            .label(b"local").unwrap()
+            .finish();
+        // The question does not fit, the null label needs the 33rd byte.
+        assert!(body.is_err());
+
+        let mut buffer = heapless::Vec::<u8, 37>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"_airplay").unwrap()
+            .label(b"_tcp").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+
+        let message = Message::new(&buffer).unwrap();
+        let mut body = message.body().unwrap();
+        assert_eq!(body.questions().next().unwrap().name(), b"_airplay._tcp.local".as_slice());
+    }
+
+    #[test]
+    fn mdns_slice_writer() {
+        let mut dma = [0xffu8; 37];
+        let mut writer = SliceWriter::new(&mut dma);
+        let mut message = Message::new_mut(&mut writer).unwrap();
+        message.header_mut().unwrap().set_id(8);
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"_airplay").unwrap()
+            .label(b"_tcp").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+        assert_eq!(writer.remaining(), 0);
+        assert_eq!(writer.write(&[0]), Err(BufferFull));
+
+        let packet = writer.into_bytes();
+        assert_eq!(packet.len(), 37);
+        let message = Message::new(packet).unwrap();
+        let header = message.header().unwrap();
+        assert_eq!(header.id(), 8);
+        assert_eq!(header.answer_count(), 0);
+        let mut body = message.body().unwrap();
+        let question = body.questions().next().unwrap();
+        assert_eq!(question.name(), b"_airplay._tcp.local".as_slice());
+        assert_eq!(question.qtype(), QType::PTR);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn mdns_serde() {
+        let mut buffer = ArrayVec::<u8, 512>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let header = message.header_mut().unwrap();
+        header.set_kind(HeaderKind::Response);
+        header.set_authoritative_answer(true);
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"my\\ tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::A).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .to_answer_section()
+            .append_answer()
+            .name()
+            .label(b"my\\ tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ip(&[192, 168, 1, 2]).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap()
+            .to_authority_section()
+            .append_authority()
+            .name()
+            .label(b"tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .srv()
+            .priority(0).unwrap()
+            .weight(0).unwrap()
+            .port(7000).unwrap()
+            .name()
+            .label(b"my\\ tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap()
+            .to_additional_section()
+            .append_additional()
+            .name()
+            .label(b"tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .txt()
+            .txt(b"\x05a=1.2\x04b=\xc3\xa9").unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(4500).unwrap()
+            .finish().unwrap()
+            .append_additional()
+            .name()
+            .finish().unwrap().unwrap()
+            .atype()
+            .rdata(&AType::OPT(0, 0, 0, &[0, 2, 0, 4, 0, 0, 0x0e, 0x10])).unwrap().unwrap()
+            .udp_payload_size(1440).unwrap()
+            .ttl(0x0000_8000).unwrap()
+            .finish().unwrap();
+
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["header"]["kind"], "Response");
+        assert_eq!(json["header"]["name_server_count"], 1);
+        assert_eq!(json["questions"][0]["name"], "my\\\\\\032tv.local");
+        assert_eq!(json["answers"][0]["atype"]["A"], "192.168.1.2");
+        assert_eq!(json["authorities"][0]["atype"]["SRV"]["port"], 7000);
+        assert_eq!(json["additionals"][0]["atype"]["TXT"][1], "b=\u{e9}");
+        assert_eq!(json["additionals"][1]["atype"]["OPT"]["udp_payload_size"], 1440);
+
+        let owned: OwnedMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(owned, OwnedMessage::parse(&message).unwrap());
+        assert_eq!(owned.answers[0].atype, OwnedAType::A(core::net::Ipv4Addr::new(192, 168, 1, 2)));
+
+        let mut rewritten = alloc::vec::Vec::new();
+        owned.write(&mut rewritten).unwrap();
+        assert_eq!(OwnedMessage::parse(&Message::new(&rewritten).unwrap()).unwrap(), owned);
+        assert_eq!(&rewritten[rewritten.len() - 19..], &buffer[buffer.len() - 19..]);
+
+        for len in 12..buffer.len() {
+            let message = Message::new(&buffer[..len]).unwrap();
+            assert!(OwnedMessage::parse(&message).is_err());
+            assert!(serde_json::to_value(&message).is_err());
+        }
+
+        // TXT records with non-UTF-8 or overrunning strings cannot be kept as text.
+        let txt = buffer.windows(4).position(|window| window == b"\x04b=\xc3").unwrap();
+        for (at, byte) in [(txt + 3, 0xff), (txt, 5)] {
+            let mut malformed = buffer.clone();
+            malformed[at] = byte;
+            let message = Message::new(malformed.as_slice()).unwrap();
+            assert!(OwnedMessage::parse(&message).is_err());
+            assert!(serde_json::to_value(&message).is_err());
+        }
+
+        // The question name points to the header, which points back to the name.
+        let looping = b"\xc0\x0c\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\xc0\x00\x00\x01\x00\x01";
+        let message = Message::new(looping.as_slice()).unwrap();
+        assert!(OwnedMessage::parse(&message).is_err());
+        assert!(serde_json::to_value(&message).is_err());
+    }
+
+    #[test]
+    fn mdns_message_display() {
+        let mut buffer = ArrayVec::<u8, 256>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let header = message.header_mut().unwrap();
+        header.set_id(7);
+        header.set_kind(HeaderKind::Response);
+        header.set_authoritative_answer(true);
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .unicast_response(true).unwrap()
+            .qtype(QType::A).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .to_answer_section()
+            .append_answer()
+            .name()
+            .label(b"tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ip(&[10, 0, 0, 1]).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap()
+            .to_authority_section()
+            .to_additional_section()
+            .append_additional()
+            .name()
+            .label(b"tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .txt()
+            .txt(b"\x03a=\"\x02b\x01").unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(4500).unwrap()
+            .finish().unwrap();
+        // An OPT record with a payload size of 1440, the DO bit and a single option.
+        buffer.try_extend_from_slice(b"\x00\x00\x29\x05\xa0\x00\x00\x80\x00\x00\x06\x00\x04\x00\x02\xab\xcd").unwrap();
+        buffer[11] = 2;
+
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let mut output = arrayvec::ArrayString::<512>::new();
+        core::fmt::write(&mut output, format_args!("{}", message.display())).unwrap();
+        assert_eq!(output.as_str(), concat!(
+            ";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 7\n",
+            ";; flags: qr aa; QUERY: 1, ANSWER: 1, AUTHORITY: 0, ADDITIONAL: 2\n",
+            "\n",
+            ";; OPT PSEUDOSECTION:\n",
+            "; EDNS: version: 0, flags: do; udp: 1440\n",
+            "; OPT=4: ab cd\n",
+            "\n",
+            ";; QUESTION SECTION:\n",
+            ";tv.local.\t\tIN\tA\t; QU\n",
+            "\n",
+            ";; ANSWER SECTION:\n",
+            "tv.local.\t120\tIN\tA\t10.0.0.1\t; cache-flush\n",
+            "\n",
+            ";; ADDITIONAL SECTION:\n",
+            "tv.local.\t4500\tIN\tTXT\t\"a=\\\"\" \"b\\001\"\n",
+        ));
+
+        // Malformed data is written in the generic form, malformed owner names end the section.
+        let mut buffer = ArrayVec::<u8, 64>::new();
+        buffer.try_extend_from_slice(b"\xc0\x0c\x00\x00\x00\x00\x00\x03\x00\x00\x00\x00").unwrap();
+        buffer.try_extend_from_slice(b"\x00\x00\x10\x00\x01\x00\x00\x00\x00\x00\x02\x05x").unwrap();
+        buffer.try_extend_from_slice(b"\x00\x00\x0c\x00\x01\x00\x00\x00\x00\x00\x02\xc0\x00").unwrap();
+        buffer.try_extend_from_slice(b"\xc0\x00\x00\x01\x00\x01\x00\x00\x00\x00\x00\x04\x0a\x00\x00\x01").unwrap();
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let mut output = arrayvec::ArrayString::<512>::new();
+        core::fmt::write(&mut output, format_args!("{}", message.display())).unwrap();
+        assert!(output.ends_with(concat!(
+            ";; ANSWER SECTION:\n",
+            ".\t0\tIN\tTXT\t\\# 2 0578\n",
+            ".\t0\tIN\tPTR\t\\# 2 c000\n",
+            ";; malformed name\n",
+        )));
+
+        let mut buffer = ArrayVec::<u8, 64>::new();
+        buffer.try_extend_from_slice(b"\xc0\x0c\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\xc0\x00\x00\x01\x00\x01").unwrap();
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let mut output = arrayvec::ArrayString::<512>::new();
+        core::fmt::write(&mut output, format_args!("{}", message.display())).unwrap();
+        assert!(output.ends_with(";; QUESTION SECTION:\n;; malformed name\n"));
+    }
+
+    #[test]
+    fn mdns_zone_file() {
+        let zone = concat!(
+            "$ORIGIN local.\n",
+            "$TTL 120\n",
+            "@ 1h IN SOA ns hostmaster.example. ( 1 ; serial\n",
+            "    1h 10m 1w 1d )\n",
+            "tv A 192.168.1.2 ; the living room\n",
+            "   AAAA fe80::1\n",
+            "   4500 TXT \"model=Apple TV\" path=/\\\"a\\\"\n",
+            "   NSEC tv.local. A AAAA TXT TYPE300\n",
+            "_airplay._tcp PTR my\\ tv._airplay._tcp\n",
+            "my\\ tv._airplay._tcp IN 120 SRV 0 0 7000 tv\n",
+            "$ORIGIN example.\n",
+            "mail MX 10 mx.example.\n",
+            "www CNAME @\n",
+        );
+
+        let mut buffer = ArrayVec::<u8, 1024>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section();
+        Zone::new(zone).write_answers(body).unwrap();
+
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let mut body = message.body().unwrap().to_answer_section();
+        let mut answers = body.answers();
+        let mut next = || {
+            let mut output = arrayvec::ArrayString::<256>::new();
+            core::fmt::write(&mut output, format_args!("{}", answers.next().unwrap())).unwrap();
+            output
+        };
+        assert_eq!(next().as_str(), "local.\t3600\tIN\tSOA\tns.local. hostmaster.example. 1 3600 600 604800 86400");
+        assert_eq!(next().as_str(), "tv.local.\t120\tIN\tA\t192.168.1.2");
+        assert_eq!(next().as_str(), "tv.local.\t120\tIN\tAAAA\tfe80::1");
+        assert_eq!(next().as_str(), "tv.local.\t4500\tIN\tTXT\t\"model=Apple TV\" \"path=/\\\"a\\\"\"");
+        assert_eq!(next().as_str(), "tv.local.\t120\tIN\tNSEC\ttv.local. A TXT AAAA TYPE300");
+        assert_eq!(next().as_str(), "_airplay._tcp.local.\t120\tIN\tPTR\tmy\\032tv._airplay._tcp.local.");
+        assert_eq!(next().as_str(), "my\\032tv._airplay._tcp.local.\t120\tIN\tSRV\t0 0 7000 tv.local.");
+        assert_eq!(next().as_str(), "mail.example.\t120\tIN\tMX\t10 mx.example.");
+        assert_eq!(next().as_str(), "www.example.\t120\tIN\tCNAME\texample.");
+        assert_eq!(message.header().unwrap().answer_count(), 9);
+
+        let errors = [
+            ("tv A 10.0.0.1\n", 1, 1),
+            ("$ORIGIN local.\ntv 120 A 10.0.0.256\n", 2, 10),
+            ("$ORIGIN local.\ntv 120 HINFO a b\n", 2, 8),
+            ("$ORIGIN local.\ntv A 10.0.0.1\n", 2, 4),
+            ("$ORIGIN local.\ntv 120 TXT \"open\n", 2, 12),
+            ("$ORIGIN local.\n\ntv 120 SRV 0 0 ( 7000\n", 3, 16),
+        ];
+        for (zone, line, column) in errors {
+            let mut buffer = ArrayVec::<u8, 256>::new();
+            let mut message = Message::new_mut(&mut buffer).unwrap();
+            let body = message.body_mut().unwrap().to_answer_section();
+            assert_eq!(Zone::new(zone).write_answers(body).err(), Some(ZoneError { line, column }), "{}", zone);
+            assert_eq!(buffer.len(), 12, "{}", zone);
+        }
+
+        let mut buffer = ArrayVec::<u8, 256>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section();
+        let zone = "$ORIGIN local.\ntv 120 A 10.0.0.1\ntv 120 A 10.0.0.256\n";
+        assert_eq!(Zone::new(zone).write_answers(body).err(), Some(ZoneError { line: 3, column: 10 }));
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let mut body = message.body().unwrap().to_answer_section();
+        assert_eq!(body.answers().count(), 1);
+        assert_eq!(body.answer_count, 0);
+        assert_eq!(message.header().unwrap().answer_count(), 1);
+    }
+
+    #[cfg(feature = "pcap")]
+    #[test]
+    fn mdns_pcap() {
+        use core::net::SocketAddr;
+        use core::time::Duration;
+
+        let mdns: &[u8] = &[0x00, 0x2a, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        let udp = |port: u16| {
+            let mut udp = ArrayVec::<u8, 64>::new();
+            udp.try_extend_from_slice(&port.to_be_bytes()).unwrap();
+            udp.try_extend_from_slice(&port.to_be_bytes()).unwrap();
+            udp.try_extend_from_slice(&(8 + mdns.len() as u16).to_be_bytes()).unwrap();
+            udp.try_extend_from_slice(&[0, 0]).unwrap();
+            udp.try_extend_from_slice(mdns).unwrap();
+            udp
+        };
+
+        // A little-endian pcap of Linux cooked IPv4 frames, the second one to the DNS port.
+        let mut pcap = ArrayVec::<u8, 512>::new();
+        pcap.try_extend_from_slice(&[0xd4, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0]).unwrap();
+        pcap.try_extend_from_slice(&[0; 8]).unwrap();
+        pcap.try_extend_from_slice(&65535u32.to_le_bytes()).unwrap();
+        pcap.try_extend_from_slice(&113u32.to_le_bytes()).unwrap();
+        for (port, seconds) in [(5353, 10u32), (53, 11)] {
+            let udp = udp(port);
+            let len = 16 + 20 + udp.len() as u32;
+            pcap.try_extend_from_slice(&seconds.to_le_bytes()).unwrap();
+            pcap.try_extend_from_slice(&500u32.to_le_bytes()).unwrap();
+            pcap.try_extend_from_slice(&len.to_le_bytes()).unwrap();
+            pcap.try_extend_from_slice(&len.to_le_bytes()).unwrap();
+            pcap.try_extend_from_slice(&[0; 14]).unwrap();
+            pcap.try_extend_from_slice(&[0x08, 0x00]).unwrap();
+            pcap.try_extend_from_slice(&[0x45, 0, 0, 20 + udp.len() as u8, 0, 0, 0x40, 0, 255, 17, 0, 0]).unwrap();
+            pcap.try_extend_from_slice(&[192, 168, 1, 2, 224, 0, 0, 251]).unwrap();
+            pcap.try_extend_from_slice(&udp).unwrap();
+        }
+
+        let mut capture = Capture::new(&pcap).unwrap();
+        let captured = capture.next().unwrap().unwrap();
+        assert_eq!(captured.source, "192.168.1.2:5353".parse::<SocketAddr>().unwrap());
+        assert_eq!(captured.destination, "224.0.0.251:5353".parse::<SocketAddr>().unwrap());
+        assert_eq!(captured.timestamp, Duration::new(10, 500_000));
+        assert_eq!(captured.message().unwrap().header().unwrap().id(), 42);
+        assert!(capture.next().is_none());
+
+        // A big-endian pcapng with an Ethernet interface in nanoseconds and an IPv6 frame.
+        let udp = udp(5353);
+        let mut frame = ArrayVec::<u8, 128>::new();
+        frame.try_extend_from_slice(&[0; 12]).unwrap();
+        frame.try_extend_from_slice(&[0x86, 0xdd, 0x60, 0, 0, 0, 0, udp.len() as u8, 17, 255]).unwrap();
+        frame.try_extend_from_slice(&[0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]).unwrap();
+        frame.try_extend_from_slice(&[0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xfb]).unwrap();
+        frame.try_extend_from_slice(&udp).unwrap();
+        let padding = (4 - frame.len() % 4) % 4;
+
+        let mut pcapng = ArrayVec::<u8, 512>::new();
+        let mut block = |kind: u32, body: &[u8]| {
+            let len = 12 + body.len() as u32;
+            pcapng.try_extend_from_slice(&kind.to_be_bytes()).unwrap();
+            pcapng.try_extend_from_slice(&len.to_be_bytes()).unwrap();
+            pcapng.try_extend_from_slice(body).unwrap();
+            pcapng.try_extend_from_slice(&len.to_be_bytes()).unwrap();
+        };
+        block(0x0a0d_0d0a, &[0x1a, 0x2b, 0x3c, 0x4d, 0, 1, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        block(1, &[0, 1, 0, 0, 0, 0, 0xff, 0xff, 0, 9, 0, 1, 9, 0, 0, 0, 0, 0, 0, 0]);
+        let mut body = ArrayVec::<u8, 256>::new();
+        body.try_extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 2, 0x54, 0x0b, 0xe4, 0x01]).unwrap();
+        body.try_extend_from_slice(&(frame.len() as u32).to_be_bytes()).unwrap();
+        body.try_extend_from_slice(&(frame.len() as u32).to_be_bytes()).unwrap();
+        body.try_extend_from_slice(&frame).unwrap();
+        body.try_extend_from_slice(&[0; 3][..padding]).unwrap();
+        block(6, &body);
+
+        let mut capture = Capture::new(&pcapng).unwrap();
+        let captured = capture.next().unwrap().unwrap();
+        assert_eq!(captured.source, "[fe80::1]:5353".parse::<SocketAddr>().unwrap());
+        assert_eq!(captured.destination, "[ff02::fb]:5353".parse::<SocketAddr>().unwrap());
+        assert_eq!(captured.timestamp, Duration::new(10, 1));
+        assert_eq!(captured.payload, mdns);
+        assert!(capture.next().is_none());
+
+        assert!(Capture::new(&pcapng[..40]).unwrap().next().unwrap().is_err());
+        assert!(Capture::new(mdns).is_err());
+    }
+
+    #[cfg(feature = "socket")]
+    #[test]
+    fn mdns_socket() {
+        use std::net::{Ipv4Addr, SocketAddrV4};
+
+        let loopback = Ipv4Addr::LOCALHOST;
+        let socket = MdnsSocket::bind_ipv4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0), &[loopback]).unwrap();
+        let port = socket.socket().local_addr().unwrap().port();
+        assert_eq!(socket.group(), SocketAddrV4::new(MDNS_IPV4, 0).into());
+
+        let mut buffer = ArrayVec::<u8, 512>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.header_mut().unwrap().set_id(7);
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"_airplay").unwrap()
+            .label(b"_tcp").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+        let oversized = [0u8; 9001];
+        socket.socket().send_to(&oversized, SocketAddrV4::new(loopback, port)).unwrap();
+        socket.send_to(&message, SocketAddrV4::new(loopback, port).into()).unwrap();
+
+        let mut received = ArrayVec::<u8, 512>::new();
+        let (message, meta) = socket.recv(&mut received).unwrap();
+        assert_eq!(meta.source, SocketAddrV4::new(loopback, port).into());
+        assert!(!meta.is_mdns_port());
+        #[cfg(target_os = "linux")]
+        assert_eq!(meta.ttl, Some(255));
+        assert_eq!(message.header().unwrap().id(), 7);
+        assert_eq!(message.body().unwrap().questions().next().unwrap().qtype(), QType::PTR);
+        assert_eq!(received.as_slice(), buffer.as_slice());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn mdns_tokio() {
+        use bytes::BytesMut;
+        use std::net::{Ipv4Addr, SocketAddrV4};
+        use tokio_util::codec::{Decoder, Encoder};
+
+        let loopback = Ipv4Addr::LOCALHOST;
+        let socket = MdnsSocket::bind_ipv4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0), &[loopback]).unwrap();
+        let socket = AsyncMdnsSocket::from_std(socket).unwrap();
+        let address = SocketAddrV4::new(loopback, socket.socket().local_addr().unwrap().port()).into();
+
+        let mut buffer = ArrayVec::<u8, 512>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.header_mut().unwrap().set_id(9);
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"tv").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::A).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+        let message = MessageBuf::copy_from(&message).unwrap();
+        socket.socket().send_to(&[0u8; 9001], address).await.unwrap();
+        socket.send_to(&message, address).await.unwrap();
+        socket.send_to(&MessageBuf::new(message.bytes().slice(..12)).unwrap(), address).await.unwrap();
+
+        let mut buffer = BytesMut::new();
+        let (received, meta) = socket.recv(&mut buffer).await.unwrap();
+        assert_eq!(received, message);
+        assert_eq!(meta.source, address);
+        assert_eq!(received.message().body().unwrap().questions().next().unwrap().qtype(), QType::A);
+        let (received, _) = socket.recv(&mut buffer).await.unwrap();
+        assert_eq!(received.message().header().unwrap().question_count(), 1);
+        assert_eq!(received.bytes().len(), 12);
+
+        let mut datagram = BytesMut::new();
+        MdnsCodec.encode(&message, &mut datagram).unwrap();
+        assert_eq!(MdnsCodec.decode(&mut datagram).unwrap(), Some(message));
+        assert!(datagram.is_empty());
+        assert_eq!(MdnsCodec.decode(&mut datagram).unwrap(), None);
+        assert!(MdnsCodec.decode(&mut BytesMut::from(&[0u8; 4][..])).is_err());
+    }
+
+    #[cfg(feature = "smoltcp")]
+    #[test]
+    fn mdns_smoltcp() {
+        extern crate alloc;
+        use alloc::vec;
+        use smoltcp::iface::{Config, Interface, SocketSet};
+        use smoltcp::phy::{Loopback, Medium};
+        use smoltcp::socket::udp;
+        use smoltcp::time::Instant;
+        use smoltcp::wire::{EthernetAddress, IpAddress, IpCidr};
+
+        let mut device = Loopback::new(Medium::Ethernet);
+        let config = Config::new(EthernetAddress([0x02, 0, 0, 0, 0, 1]).into());
+        let mut iface = Interface::new(config, &mut device, Instant::ZERO);
+        iface.update_ip_addrs(|addrs| addrs.push(IpCidr::new(IpAddress::v4(192, 168, 1, 2), 24)).unwrap());
+        SmoltcpMdnsSocket::join(&mut iface).unwrap();
+
+        let buffer = || udp::PacketBuffer::new(vec![udp::PacketMetadata::EMPTY; 4], vec![0; 1024]);
+        let mut sockets = SocketSet::new(vec![]);
+        let handle = sockets.add(udp::Socket::new(buffer(), buffer()));
+
+        let mut socket = SmoltcpMdnsSocket::new(sockets.get_mut::<udp::Socket>(handle)).unwrap();
+        let len = socket.send(512, |message| {
+            message.header_mut()?.set_id(3);
+            message.body_mut()?
+                .append_question()
+                .name()
+                .label(b"tv")?
+                .label(b"local")?
+                .finish()?
+                .qtype(QType::AAAA)?
+                .qclass(QClass::IN)?
+                .finish()?;
+            Ok(())
+        });
+        assert_eq!(len, Ok(12 + 10 + 4));
+        // A failed build leaves an empty datagram, which the receiver skips.
+        assert_eq!(socket.send(16, |message| message.body_mut()?.append_question().name().label(b"local").map(|_| ())), Err(()));
+        // Nothing is reserved if the transmit buffer has no room.
+        let mut built = false;
+        assert_eq!(socket.send(1 << 20, |_| { built = true; Ok(()) }), Err(()));
+        assert!(!built);
+        assert_eq!(sockets.get::<udp::Socket>(handle).send_queue(), 12 + 10 + 4);
+
+        for _ in 0..2 {
+            iface.poll(Instant::ZERO, &mut device, &mut sockets);
+        }
+        let mut socket = SmoltcpMdnsSocket::new(sockets.get_mut::<udp::Socket>(handle)).unwrap();
+        let (message, meta) = socket.recv().unwrap();
+        assert_eq!(message.header().unwrap().id(), 3);
+        assert_eq!(message.body().unwrap().questions().next().unwrap().qtype(), QType::AAAA);
+        assert_eq!(meta.endpoint.port, MDNS_PORT);
+        assert!(socket.recv().is_none());
+    }
+
+    #[test]
+    fn mdns_responder() {
+        let host = Host { name: b"printer", ipv4: Some(core::net::Ipv4Addr::new(169, 254, 0, 1)), ipv6: None };
+        let services = [Service { instance: b"Printer A", service: b"_ipp._tcp", port: 631, txt: &[b"rp=ipp/print"] }];
+        let mut responder = Responder::new(host, &services, 1000, 100).unwrap();
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        assert!(responder.poll(1099, &mut buffer).unwrap().is_none());
+
+        for (i, now) in [1100, 1350, 1600].into_iter().enumerate() {
+            let probe = responder.poll(now, &mut buffer).unwrap().unwrap();
+            let header = probe.header().unwrap();
+            assert_eq!(header.kind(), HeaderKind::Query);
+            assert_eq!(header.question_count(), 2);
+            assert_eq!(header.name_server_count(), 3);
+            let mut body = probe.body().unwrap();
+            let question = body.questions().next().unwrap();
+            assert_eq!(question.qtype(), QType::ALL);
+            assert_eq!(question.unicast_response(), i == 0);
+        }
+        assert_eq!(responder.state(), ResponderState::Announcing);
+        assert_eq!(responder.deadline(), Some(1850));
+
+        for now in [1850, 2850] {
+            let announcement = responder.poll(now, &mut buffer).unwrap().unwrap();
+            let header = announcement.header().unwrap();
+            assert_eq!(header.kind(), HeaderKind::Response);
+            assert_eq!(header.answer_count(), 5);
+        }
+        assert_eq!(responder.state(), ResponderState::Running);
+        assert_eq!(responder.deadline(), None);
+
+        let mut query_buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut query_buffer).unwrap();
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(ServiceNamePart::service_type(b"_IPP._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+        let query = Message::new(query_buffer.as_slice()).unwrap();
+        let (response, mode) = responder.handle(&query, "169.254.0.2:5353".parse().unwrap(), &mut buffer).unwrap().unwrap();
+        assert_eq!(mode, ResponseMode::Multicast);
+        let header = response.header().unwrap();
+        assert!(header.authoritative_answer());
+        assert_eq!(header.answer_count(), 1);
+        assert_eq!(header.additional_records_count(), 3);
+        let mut body = response.body().unwrap().to_answer_section();
+        let answer = body.answers().next().unwrap();
+        assert_eq!(answer.ttl(), 4500);
+        assert!(!answer.cache_flush());
+        let mut body = body.to_authority_section().to_additional_section();
+        let mut additionals = body.additionals();
+        for qtype in [QType::A, QType::SRV, QType::TXT] {
+            let additional = additionals.next().unwrap();
+            assert_eq!(additional.atype().type_id(), Ok(u16::from(qtype)));
+            assert!(additional.cache_flush());
+        }
+
+        query_buffer.clear();
+        let mut message = Message::new_mut(&mut query_buffer).unwrap();
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(ServiceNamePart::service_type(b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .to_answer_section()
+            .append_answer()
+            .name()
+            .label(ServiceNamePart::service_type(b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .ptr()
+            .name()
+            .label(ServiceNamePart::new(b"Printer A", b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(4000).unwrap()
+            .finish().unwrap();
+        let query = Message::new(query_buffer.as_slice()).unwrap();
+        assert!(responder.handle(&query, "169.254.0.2:5353".parse().unwrap(), &mut buffer).unwrap().is_none());
+
+        // A legacy unicast query also asking for a type without a QType variant.
+        query_buffer.clear();
+        let mut message = Message::new_mut(&mut query_buffer).unwrap();
+        message.header_mut().unwrap().set_id(0x1234);
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"printer").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::A).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .append_question()
+            .name()
+            .label(b"printer").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype_value(257).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+        let query = Message::new(query_buffer.as_slice()).unwrap();
+        let (response, mode) = responder.handle(&query, "169.254.0.2:49152".parse().unwrap(), &mut buffer).unwrap().unwrap();
+        assert_eq!(mode, ResponseMode::LegacyUnicast);
+        let header = response.header().unwrap();
+        assert_eq!(header.id(), 0x1234);
+        assert_eq!(header.question_count(), 2);
+        assert_eq!(header.answer_count(), 1);
+        let mut body = response.body().unwrap();
+        assert_eq!(body.questions().nth(1).unwrap().qtype_value(), 257);
+
+        query_buffer.clear();
+        let mut message = Message::new_mut(&mut query_buffer).unwrap();
+        message.header_mut().unwrap().set_kind(HeaderKind::Response);
+        message.body_mut().unwrap()
+            .to_answer_section()
+            .append_answer()
+            .name()
+            .label(b"PRINTER").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ip(&[169, 254, 0, 3]).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+        let response = Message::new(query_buffer.as_slice()).unwrap();
+        assert!(responder.handle(&response, "169.254.0.3:5353".parse().unwrap(), &mut buffer).unwrap().is_none());
+        assert_eq!(responder.state(), ResponderState::Conflict);
+    }
+
+    #[cfg(all(feature = "embedded-nal", feature = "std"))]
+    #[test]
+    fn mdns_embedded_nal() {
+        use embedded_nal::{nb, UdpClientStack, UdpFullStack};
+        use std::net::SocketAddr;
+
+        #[derive(Default)]
+        struct Loopback {
+            bound: Option<u16>,
+            datagrams: std::collections::VecDeque<(std::vec::Vec<u8>, SocketAddr)>,
+        }
+
+        impl UdpClientStack for Loopback {
+            type UdpSocket = ();
+            type Error = ();
+
+            fn socket(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+
+            fn connect(&mut self, _: &mut (), _: SocketAddr) -> Result<(), ()> {
+                Err(())
+            }
+
+            fn send(&mut self, _: &mut (), _: &[u8]) -> nb::Result<(), ()> {
+                Err(nb::Error::Other(()))
+            }
+
+            fn receive(&mut self, _: &mut (), buffer: &mut [u8]) -> nb::Result<(usize, SocketAddr), ()> {
+                let (datagram, source) = self.datagrams.pop_front().ok_or(nb::Error::WouldBlock)?;
+                buffer[..datagram.len()].copy_from_slice(&datagram);
+                Ok((datagram.len(), source))
+            }
+
+            fn close(&mut self, _: ()) -> Result<(), ()> {
+                self.bound = None;
+                Ok(())
+            }
+        }
+
+        impl UdpFullStack for Loopback {
+            fn bind(&mut self, _: &mut (), port: u16) -> Result<(), ()> {
+                self.bound = Some(port);
+                Ok(())
+            }
+
+            fn send_to(&mut self, _: &mut (), remote: SocketAddr, buffer: &[u8]) -> nb::Result<(), ()> {
+                self.datagrams.push_back((buffer.to_vec(), remote));
+                Ok(())
+            }
+        }
+
+        let mut stack = Loopback::default();
+        let mut socket = NalMdnsSocket::new(&mut stack).unwrap();
+
+        let mut buffer = ArrayVec::<u8, 64>::new_const();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.header_mut().unwrap().set_id(0x1234);
+        socket.send(&message).unwrap();
+        socket.send_to(&message, "169.254.0.2:49152".parse().unwrap()).unwrap();
+
+        let mut datagram = [0; 64];
+        let (message, source) = socket.recv(&mut datagram).unwrap();
+        assert_eq!(message.header().unwrap().id(), 0x1234);
+        assert_eq!(source, "224.0.0.251:5353".parse().unwrap());
+        let (_, source) = socket.recv(&mut datagram).unwrap();
+        assert_eq!(source, "169.254.0.2:49152".parse().unwrap());
+        assert!(matches!(socket.recv(&mut datagram), Err(nb::Error::WouldBlock)));
+
+        socket.close().unwrap();
+        assert_eq!(stack.bound, None);
+    }
+
+    #[test]
+    fn mdns_message_mut() {
+        let mut buffer = ArrayVec::<u8, 256>::new_const();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.header_mut().unwrap().set_id(0x1234);
+        let body = message.body_mut().unwrap();
+        body.append_question()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::ALL).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .to_answer_section()
+            .append_answer()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ip(&[169, 254, 0, 1]).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap()
+            .to_authority_section()
+            .to_additional_section()
+            .append_additional()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .rdata(&AType::AAAA([0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1])).unwrap().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(10).unwrap()
+            .finish().unwrap();
+
+        let mut message = MessageMut::new(buffer.as_mut_slice()).unwrap();
+        message.header_mut().set_id(0x4321);
+        message.header_mut().set_kind(HeaderKind::Response);
+        let mut sections = ArrayVec::<RecordSection, 2>::new_const();
+        message.for_each_record(|record| {
+            sections.push(record.section());
+            assert!(record.name().unwrap().eq_labels([&b"HOST"[..], b"local"]));
+            record.set_cache_flush(false)?;
+            record.decrement_ttl(30)?;
+            match record.type_id() {
+                1 => record.set_ipv4(core::net::Ipv4Addr::new(169, 254, 0, 2)),
+                _ => {
+                    assert!(record.set_ipv4(core::net::Ipv4Addr::new(169, 254, 0, 2)).is_err());
+                    record.set_ipv6(core::net::Ipv6Addr::LOCALHOST)
+                }
+            }
+        }).unwrap();
+        assert_eq!(sections.as_slice(), [RecordSection::Answer, RecordSection::Additional]);
+
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let header = message.header().unwrap();
+        assert_eq!(header.id(), 0x4321);
+        assert_eq!(header.kind(), HeaderKind::Response);
+        let mut body = message.body().unwrap().to_answer_section();
+        let answer = body.answers().next().unwrap();
+        assert!(!answer.cache_flush());
+        assert_eq!(answer.ttl(), 90);
+        assert_eq!(answer.atype(), &AType::A(u32::from(core::net::Ipv4Addr::new(169, 254, 0, 2))));
+        let mut body = body.to_authority_section().to_additional_section();
+        let additional = body.additionals().next().unwrap();
+        assert_eq!(additional.ttl(), 0);
+        assert_eq!(additional.atype(), &AType::AAAA(core::net::Ipv6Addr::LOCALHOST.octets()));
+    }
+
+    #[test]
+    fn mdns_copy_records() {
+        let mut source_buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut source_buffer).unwrap();
+        let body = message.body_mut().unwrap();
+        let body = body.append_question()
+            .name()
+            .label(ServiceNamePart::service_type(b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .unicast_response(true).unwrap()
+            .qtype(QType::PTR).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .to_answer_section();
+        let body = body.append_answer()
+            .name()
+            .label(ServiceNamePart::service_type(b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .ptr()
+            .name()
+            .label(ServiceNamePart::new(b"Printer", b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(4500).unwrap()
+            .finish().unwrap();
+        body.to_authority_section()
+            .to_additional_section()
+            .append_additional()
+            .name()
+            .label(ServiceNamePart::new(b"Printer", b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .srv()
+            .priority(0).unwrap()
+            .weight(0).unwrap()
+            .port(631).unwrap()
+            .name()
+            .label(b"printer").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+
+        let mut i = 12;
+        let question = Question::parse(&source_buffer, &mut i).unwrap();
+        let ptr = Answer::parse(&source_buffer, &mut i).unwrap();
+        let srv = Answer::parse(&source_buffer, &mut i).unwrap();
+
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.body_mut().unwrap()
+            .append_question_from(&question).unwrap()
+            .to_answer_section()
+            .append_record(&srv).unwrap()
+            .append_record(&ptr).unwrap()
+            .to_authority_section()
+            .to_additional_section()
+            .append_record(&ptr).unwrap();
+        // The second PTR record points to the first one, its data to the SRV owner name.
+        let question_len = 17 + 4;
+        // The target points to the `local` label of the question.
+        let srv_len = 10 + 10 + 6 + 8 + 2;
+        let ptr_len = 2 + 10 + 2;
+        assert_eq!(buffer.len(), 12 + question_len + srv_len + ptr_len + ptr_len);
+
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let header = message.header().unwrap();
+        assert_eq!((header.question_count(), header.answer_count(), header.additional_records_count()), (1, 2, 1));
+        let mut body = message.body().unwrap();
+        let copied = body.questions().next().unwrap();
+        assert!(copied.unicast_response());
+        assert!(copied.name().eq_ignore_ascii_case(question.name()));
+        let mut body = body.to_answer_section();
+        let mut answers = body.answers();
+        let copied = answers.next().unwrap();
+        assert!(copied.cache_flush());
+        assert!(known_answer::rdata_eq(copied.atype(), srv.atype()));
+        let copied = answers.next().unwrap();
+        assert!(copied.name().eq_ignore_ascii_case(ptr.name()));
+        assert!(known_answer::rdata_eq(copied.atype(), ptr.atype()));
+        let mut body = body.to_authority_section().to_additional_section();
+        assert!(known_answer::rdata_eq(body.additionals().next().unwrap().atype(), ptr.atype()));
+
+        // Types without a QType variant are copied as they are.
+        source_buffer.clear();
+        let mut message = Message::new_mut(&mut source_buffer).unwrap();
+        message.body_mut().unwrap()
+            .append_question()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype_value(257).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap();
+        let question = Question::parse(&source_buffer, &mut 12).unwrap();
+        buffer.clear();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.body_mut().unwrap().append_question_from(&question).unwrap();
+        assert_eq!(&buffer[12..], &source_buffer[12..]);
+    }
+
+    #[test]
+    fn mdns_recompress() {
+        let mut source_buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut source_buffer).unwrap();
+        message.header_mut().unwrap().set_id(0x1234);
+        let body = message.body_mut().unwrap();
+        let body = body.append_question()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .qtype(QType::A).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .to_answer_section();
+        let body = body.append_answer()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ip(&[169, 254, 0, 1]).unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+        let body = body.append_answer()
+            .name()
+            .label(ServiceNamePart::service_type(b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .ptr()
+            .name()
+            .label(ServiceNamePart::new(b"Printer", b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(4500).unwrap()
+            .finish().unwrap();
+        body.to_authority_section()
+            .to_additional_section()
+            .append_additional()
+            .name()
+            .label(ServiceNamePart::new(b"Printer", b"_ipp._tcp", b"local")).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .srv()
+            .priority(0).unwrap()
+            .weight(0).unwrap()
+            .port(631).unwrap()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+
+        let source = Message::new(source_buffer.as_slice()).unwrap();
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        // The A owner and the SRV target point to the question, the PTR owner to its `local`,
+        // the PTR data to the PTR owner and the SRV owner to the PTR data.
+        assert_eq!(recompress(&source, &mut buffer), Ok(10 + 5 + 15 + 23 + 10));
+
+        let message = Message::new(buffer.as_slice()).unwrap();
+        assert_eq!(message.header().unwrap(), source.header().unwrap());
+        let mut body = message.body().unwrap().to_answer_section();
+        let mut source_body = source.body().unwrap().to_answer_section();
+        for (copied, original) in body.answers().zip(source_body.answers()) {
+            assert!(copied.name().eq_ignore_ascii_case(original.name()));
+            assert!(known_answer::rdata_eq(copied.atype(), original.atype()));
+            assert_eq!((copied.cache_flush(), copied.ttl()), (original.cache_flush(), original.ttl()));
+        }
+        let mut body = body.to_authority_section().to_additional_section();
+        let srv = body.additionals().next().unwrap();
+        assert!(srv.name().eq_labels([&b"Printer"[..], b"_ipp", b"_tcp", b"local"]));
+        assert_eq!(srv.atype(), &AType::SRV(0, 0, 631, Name::parse(&buffer, &mut 12).unwrap()));
+
+        let mut again = ArrayVec::<u8, 512>::new_const();
+        assert_eq!(recompress(&message, &mut again), Ok(0));
+        assert_eq!(again, buffer);
+
+        for len in 12..source_buffer.len() {
+            let truncated = Message::new(&source_buffer[..len]).unwrap();
+            assert_eq!(recompress(&truncated, &mut again), Err(()));
+            assert!(again.is_empty());
+        }
+    }
+
+    #[test]
+    fn mdns_dnssec() {
+        let fields = RrsigFields {
+            type_covered: 1,
+            algorithm: 13,
+            labels: 2,
+            original_ttl: 3600,
+            expiration: 1048354263,
+            inception: 1045762263,
+            key_tag: 2642,
+        };
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section();
+        let body = body.append_answer()
+            .name()
+            .label(b"example").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .dnskey(257, 3, 13, &[1, 2, 3, 4, 5]).unwrap().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(3600).unwrap()
+            .finish().unwrap();
+        let body = body.append_answer()
+            .name()
+            .label(b"host").unwrap()
+            .label(b"example").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .rrsig(&fields).unwrap()
+            .signer()
+            .label(b"example").unwrap()
+            .finish().unwrap()
+            .signature(&[1, 2, 3, 4, 5]).unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(3600).unwrap()
+            .finish().unwrap();
+        let body = body.to_authority_section()
+            .append_authority()
+            .name()
+            .label(b"28q5cu4qng").unwrap()
+            .label(b"example").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .nsec3(1, 0, 10, &[0xab, 0xcd], &[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc], [1, 46].into_iter()).unwrap().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(3600).unwrap()
+            .finish().unwrap();
+        let body = body.to_additional_section()
+            .append_additional()
+            .name()
+            .label(b"example").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .nsec3param(1, 0, 10, &[]).unwrap().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(0).unwrap()
+            .finish().unwrap();
+        body.append_additional()
+            .name()
+            .label(b"example").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .ds(2642, 13, 2, &[0xde, 0xad]).unwrap().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(3600).unwrap()
+            .finish().unwrap();
+
+        let mut i = 12;
+        let dnskey = Answer::parse(&buffer, &mut i).unwrap();
+        assert_eq!(dnskey.atype(), &AType::DNSKEY(257, 3, 13, &[1, 2, 3, 4, 5]));
+        let rrsig = Answer::parse(&buffer, &mut i).unwrap();
+        let AType::RRSIG(parsed, signer, signature) = rrsig.atype() else { panic!() };
+        assert_eq!(parsed, &fields);
+        assert!(signer.eq_labels([b"example".as_slice()]));
+        assert_eq!(*signature, &[1, 2, 3, 4, 5]);
+        let nsec3 = Answer::parse(&buffer, &mut i).unwrap();
+        assert_eq!(nsec3.atype().nsec_types().collect::<ArrayVec<u16, 4>>().as_slice(), &[1, 46]);
+        assert_eq!(Answer::parse(&buffer, &mut i).unwrap().atype(), &AType::NSEC3PARAM(1, 0, 10, &[]));
+        assert_eq!(Answer::parse(&buffer, &mut i).unwrap().atype(), &AType::DS(2642, 13, 2, &[0xde, 0xad]));
+        assert_eq!(i, buffer.len());
+
+        let mut output = arrayvec::ArrayString::<512>::new();
+        core::fmt::write(&mut output, format_args!("{}\n{}\n{}", dnskey.atype(), rrsig.atype(), nsec3.atype())).unwrap();
+        assert_eq!(output.as_str(), concat!(
+            "257 3 13 AQIDBAU=\n",
+            "A 13 2 3600 20030322173103 20030220173103 2642 example. AQIDBAU=\n",
+            "1 0 10 abcd 28q5cu4qng A RRSIG",
+        ));
+
+        // The signer name must not be compressed.
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section().append_answer().name();
+        let name_ptr = body.ptr();
+        let signer = body
+            .label(b"example").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .rrsig(&fields).unwrap()
+            .signer()
+            .label(&name_ptr).unwrap();
+        assert!(signer.finish().is_err());
+        let mut buffer = ArrayVec::<u8, 64>::new_const();
+        buffer.try_extend_from_slice(b"\x07example\x00\x00\x2e\x00\x01\x00\x00\x0e\x10\x00\x15").unwrap();
+        buffer.try_extend_from_slice(&[0; RrsigFields::LEN]).unwrap();
+        buffer.try_extend_from_slice(&[0xc0, 0x00, 1]).unwrap();
+        assert_eq!(Answer::parse(&buffer, &mut 0), Err(Error));
+        let len = buffer.len();
+        buffer[len - 3..].copy_from_slice(&[0, 1, 2]);
+        assert!(Answer::parse(&buffer, &mut 0).is_ok());
+    }
+
+    #[test]
+    fn mdns_svcb() {
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section();
+        let body = body.append_answer()
+            .name()
+            .label(b"printer").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .https()
+            .priority(1).unwrap()
+            .target()
+            .finish().unwrap()
+            .mandatory(&[SVC_PARAM_ALPN]).unwrap()
+            .alpn(&[b"h2", b"h3"]).unwrap()
+            .port(8443).unwrap()
+            .ipv4hint(&[core::net::Ipv4Addr::new(169, 254, 0, 1)]).unwrap()
+            .ech(&[1, 2, 3]).unwrap()
+            .ipv6hint(&[core::net::Ipv6Addr::LOCALHOST]).unwrap()
+            .param(65000, b"x").unwrap()
+            .finish().unwrap()
+            .cache_flush(true).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+        // SvcParams have to be written in increasing order of their keys.
+        assert!(body.append_answer()
+            .name()
+            .label(b"printer").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .svcb()
+            .priority(1).unwrap()
+            .target()
+            .label(b"printer").unwrap()
+            .label(b"local").unwrap()
+            .finish().unwrap()
+            .port(631).unwrap()
+            .alpn(&[b"ipp"])
+            .is_err());
+        let len = 12 + 15 + 10 + 2 + 1 + 6 + 10 + 6 + 8 + 7 + 20 + 5;
+        buffer.truncate(len);
+
+        let mut i = 12;
+        let record = Answer::parse(&buffer, &mut i).unwrap();
+        assert_eq!(i, len);
+        let AType::HTTPS(priority, target, _) = record.atype() else { panic!() };
+        assert_eq!((*priority, target.labels().count()), (1, 0));
+        let mut params = record.atype().svc_params();
+        let Some(SvcParam::Mandatory(keys)) = params.next() else { panic!() };
+        assert_eq!(keys.collect::<ArrayVec<u16, 2>>().as_slice(), &[SVC_PARAM_ALPN]);
+        let Some(SvcParam::Alpn(ids)) = params.next() else { panic!() };
+        assert_eq!(ids.collect::<ArrayVec<&[u8], 2>>().as_slice(), &[b"h2", b"h3"]);
+        assert_eq!(params.next(), Some(SvcParam::Port(8443)));
+        let Some(SvcParam::Ipv4Hint(mut addrs)) = params.next() else { panic!() };
+        assert_eq!(addrs.next(), Some(core::net::Ipv4Addr::new(169, 254, 0, 1)));
+        assert_eq!(params.next(), Some(SvcParam::Ech(&[1, 2, 3])));
+        let Some(SvcParam::Ipv6Hint(mut addrs)) = params.next() else { panic!() };
+        assert_eq!(addrs.next(), Some(core::net::Ipv6Addr::LOCALHOST));
+        assert_eq!(params.next(), Some(SvcParam::Unknown(65000, b"x")));
+        assert_eq!(params.next(), None);
+
+        let mut output = arrayvec::ArrayString::<512>::new();
+        core::fmt::write(&mut output, format_args!("{}", record.atype())).unwrap();
+        assert_eq!(
+            output.as_str(),
+            "1 . mandatory=alpn alpn=h2,h3 port=8443 ipv4hint=169.254.0.1 ech=AQID ipv6hint=::1 key65000=\"x\"",
+        );
+
+        // A port with a value of the wrong length makes the record malformed.
+        buffer[12 + 15 + 10 + 2 + 1 + 6 + 10 + 3] = 3;
+        assert!(Answer::parse(&buffer, &mut 12).is_err());
+
+        // The target must not be compressed.
+        let mut buffer = ArrayVec::<u8, 64>::new();
+        buffer.try_extend_from_slice(b"\x01a\x00\x00\x40\x00\x01\x00\x00\x00\x78\x00\x04\x00\x01\xc0\x00").unwrap();
+        assert!(Answer::parse(&buffer, &mut 0).is_err());
+        buffer[15..].copy_from_slice(b"\x01a");
+        buffer.push(0);
+        buffer[12] = 5;
+        assert!(Answer::parse(&buffer, &mut 0).is_ok());
+        let mut buffer = ArrayVec::<u8, 64>::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section().append_answer().name();
+        let name_ptr = body.ptr();
+        let target = body
+            .label(b"printer").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .svcb()
+            .priority(1).unwrap()
+            .target()
+            .label(&name_ptr).unwrap();
+        assert!(target.finish().is_err());
+    }
+
+    #[test]
+    fn mdns_framed() {
+        let mut stream = ArrayVec::<u8, 512>::new_const();
+        for id in [1, 2] {
+            let mut framed = Framed::new(&mut stream).unwrap();
+            let mut message = Message::new_mut(&mut framed).unwrap();
+            message.header_mut().unwrap().set_id(id);
+            message.body_mut().unwrap()
+                .append_question()
+                .name()
+                .label(b"printer").unwrap()
+                .label(b"local").unwrap()
+                .finish().unwrap()
+                .qtype(QType::SRV).unwrap()
+                .qclass(QClass::IN).unwrap()
+                .finish().unwrap();
+            assert_eq!(framed.frame()[..2], [0, 12 + 15 + 4]);
+        }
+        assert_eq!(stream.len(), 2 * (2 + 31));
+
+        // The second frame arrives in parts.
+        let mut frames = Frames::new(&stream[..40]);
+        assert_eq!(frames.next().unwrap().unwrap().header().unwrap().id(), 1);
+        assert!(frames.next().is_none());
+        assert_eq!((frames.consumed(), frames.missing()), (33, 26));
+        let mut frames = Frames::new(&stream[33..34]);
+        assert!(frames.next().is_none());
+        assert_eq!(frames.missing(), 1);
+        let mut frames = Frames::new(&stream[33..]);
+        let message = frames.next().unwrap().unwrap();
+        assert_eq!(message.header().unwrap().id(), 2);
+        assert_eq!(message.header().unwrap().question_count(), 1);
+        assert_eq!((frames.consumed(), frames.missing()), (33, 2));
+
+        // Frames too short for a header are skipped.
+        let mut frames = Frames::new(&[0, 1, 0xff]);
+        assert_eq!(frames.next().map(|message| message.is_err()), Some(true));
+        assert!(frames.next().is_none());
+    }
+
+    #[test]
+    fn mdns_update() {
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        message.header_mut().unwrap().set_id(0x4242);
+        let body = message.update_body_mut().unwrap()
+            .append_zone()
+            .name()
+            .label(b"example").unwrap()
+            .finish().unwrap()
+            .qtype(QType::SOA).unwrap()
+            .qclass(QClass::IN).unwrap()
+            .finish().unwrap()
+            .to_prerequisite_section()
+            .name_is_not_in_use(PresentationName("host.example")).unwrap()
+            .to_update_section()
+            .delete_rrset(PresentationName("old.example"), QType::AAAA).unwrap();
+        body.append_update()
+            .name()
+            .label(PresentationName("host.example")).unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .a()
+            .ip(&[192, 0, 2, 1]).unwrap()
+            .finish().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(3600).unwrap()
+            .finish().unwrap()
+            .delete_all_rrsets(PresentationName("gone.example")).unwrap()
+            .to_additional_section();
+
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let header = message.header().unwrap();
+        assert_eq!(header.opcode(), HeaderOpcode::Update);
+        assert_eq!((header.question_count(), header.answer_count(), header.name_server_count()), (1, 1, 3));
+        let mut body = message.update_body().unwrap();
+        let zone = body.zones().next().unwrap();
+        assert_eq!((zone.qtype(), zone.qclass()), (QType::SOA, QClass::IN));
+        let mut body = body.to_prerequisite_section();
+        let prerequisite = body.prerequisites().next().unwrap();
+        assert_eq!(prerequisite.atype(), &AType::Unknown(255, &[]));
+        assert_eq!((prerequisite.aclass(), prerequisite.ttl()), (AClass::NONE, 0));
+        let mut body = body.to_update_section();
+        let mut updates = body.updates();
+        let update = updates.next().unwrap();
+        assert_eq!((update.atype(), update.aclass()), (&AType::Unknown(28, &[]), AClass::ANY));
+        let update = updates.next().unwrap();
+        assert_eq!((update.atype(), update.aclass()), (&AType::A(0xc0000201), AClass::IN));
+        let update = updates.next().unwrap();
+        assert_eq!((update.atype(), update.aclass()), (&AType::Unknown(255, &[]), AClass::ANY));
+        assert!(updates.next().is_none());
+    }
+
+    #[test]
+    fn mdns_srp() {
+        struct Signer(ArrayVec<u8, 1024>);
+
+        impl SrpSigner for Signer {
+            fn algorithm(&self) -> u8 {
+                13
+            }
+
+            fn public_key(&self) -> &[u8] {
+                &[7; 64]
+            }
+
+            fn sign(&mut self, data: &[&[u8]], signature: &mut [u8]) -> Result<usize, ()> {
+                for part in data {
+                    self.0.try_extend_from_slice(part).map_err(|_| ())?;
+                }
+                signature[..64].fill(9);
+                Ok(64)
+            }
+        }
+
+        let host = Host { name: b"printer", ipv4: None, ipv6: Some(core::net::Ipv6Addr::LOCALHOST) };
+        let services = [Service { instance: b"Office", service: b"_ipp._tcp", port: 631, txt: &[] }];
+        let mut buffer = ArrayVec::<u8, 1024>::new_const();
+        let mut signer = Signer(ArrayVec::new_const());
+        let len = SrpRegistration::new(host, &services)
+            .lease(3600, 86400)
+            .write(&mut buffer, 0x5151, &mut signer).unwrap()
+            .bytes().len();
+        assert_eq!(len, buffer.len());
+
+        let message = Message::new(buffer.as_slice()).unwrap();
+        let header = message.header().unwrap();
+        assert_eq!((header.id(), header.opcode()), (0x5151, HeaderOpcode::Update));
+        assert_eq!((header.question_count(), header.answer_count()), (1, 0));
+        assert_eq!((header.name_server_count(), header.additional_records_count()), (7, 2));
+        let mut body = message.update_body().unwrap();
+        assert_eq!(body.zones().next().unwrap().name(), b"default.service.arpa".as_slice());
+        let mut i = 12 + 22 + 4;
+        let mut records = ArrayVec::<Answer, 9>::new();
+        for _ in 0..9 {
+            records.push(Answer::parse(&buffer, &mut i).unwrap());
+        }
+        assert_eq!(i, buffer.len());
+        assert_eq!(records[0].name(), b"_ipp._tcp.default.service.arpa".as_slice());
+        assert!(matches!(records[0].atype(), AType::PTR(_)));
+        assert_eq!((records[1].atype(), records[1].aclass()), (&AType::Unknown(255, &[]), AClass::ANY));
+        assert!(matches!(records[2].atype(), AType::SRV(0, 0, 631, target) if *target == *b"printer.default.service.arpa".as_slice()));
+        assert_eq!(records[3].atype(), &AType::TXT(&[0]));
+        assert_eq!(records[4].name(), b"printer.default.service.arpa".as_slice());
+        assert_eq!(records[5].atype(), &AType::AAAA(core::net::Ipv6Addr::LOCALHOST.octets()));
+        let AType::Unknown(25, key) = records[6].atype() else { panic!() };
+        assert_eq!(&key[..4], &[2, 1, 3, 13]);
+        assert!(matches!(records[7].atype(), AType::OPT(..)));
+        assert_eq!((records[8].aclass(), records[8].ttl()), (AClass::ANY, 0));
+        let AType::Unknown(24, sig) = records[8].atype() else { panic!() };
+        assert_eq!(&sig[..2], &[0, 0]);
+        assert_eq!(&sig[sig.len() - 64..], &[9; 64]);
+        // The signed data are the SIG fields, the signer and the message without the SIG record,
+        // which counted one additional record then.
+        let (fields, message) = signer.0.split_at(18 + 30);
+        assert_eq!(fields, &sig[..18 + 30]);
+        assert_eq!(message.len(), buffer.len() - 11 - sig.len());
+        assert_eq!(&message[12..], &buffer[12..message.len()]);
+        assert_eq!(&message[10..12], &[0, 1]);
+
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let header = message.header_mut().unwrap();
+        header.set_id(0x5151);
+        header.set_kind(HeaderKind::Response);
+        header.set_opcode(HeaderOpcode::Update);
+        header.set_additional_records_count(1);
+        buffer.try_extend_from_slice(&[0, 0, 41, 4, 208, 0, 0, 0, 0, 0, 12, 0, 2, 0, 8, 0, 0, 14, 16, 0, 1, 81, 128]).unwrap();
+        let response = SrpResponse::parse(&Message::new(buffer.as_slice()).unwrap()).unwrap();
+        assert_eq!(response, SrpResponse {
+            id: 0x5151,
+            response_code: HeaderResponseCode::NoError,
+            lease: Some(3600),
+            key_lease: Some(86400),
+        });
+    }
+
+    #[test]
+    fn mdns_truncated_answers() {
+        let records: [(u16, &[u8]); 8] = [
+            (1, &[10, 0, 0, 1]),
+            (6, b"\x02ns\x00\x04mail\x00\x00\x00\x00\x01\x00\x00\x00\x02\x00\x00\x00\x03\x00\x00\x00\x04\x00\x00\x00\x05"),
+            (15, b"\x00\x0a\x02mx\x00"),
+            (16, b"\x03a=b"),
+            (28, &[0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]),
+            (33, b"\x00\x00\x00\x00\x02\x77\x04host\x00"),
+            (41, b"\x00\x02\x00\x04\x00\x00\x0e\x10"),
+            (64, b"\x00\x01\x00\x00\x03\x00\x02\x1f\x90"),
+        ];
+        for (atype, rdata) in records {
+            let mut buffer = ArrayVec::<u8, 64>::new_const();
+            buffer.try_extend_from_slice(b"\x01a\x00").unwrap();
+            buffer.try_extend_from_slice(&atype.to_be_bytes()).unwrap();
+            buffer.try_extend_from_slice(&[0, 1, 0, 0, 0, 120]).unwrap();
+            buffer.try_extend_from_slice(&(rdata.len() as u16).to_be_bytes()).unwrap();
+            buffer.try_extend_from_slice(rdata).unwrap();
+
+            let mut i = 0;
+            Answer::parse(&buffer, &mut i).unwrap();
+            assert_eq!(i, buffer.len());
+            for len in 0..buffer.len() {
+                assert_eq!(Answer::parse(&buffer[..len], &mut 0), Err(Error), "type {} cut at {}", atype, len);
+            }
+        }
+    }
+
+    #[test]
+    fn mdns_responder_truncated() {
+        let host = Host { name: b"dev", ipv4: Some(core::net::Ipv4Addr::new(10, 0, 0, 1)), ipv6: None };
+        let services = [Service { instance: b"Dev", service: b"_http._tcp", port: 80, txt: &[] }];
+        let mut responder = Responder::new(host, &services, 0, 0).unwrap();
+        let mut buffer = ArrayVec::<u8, 512>::new_const();
+        for now in [0, 250, 500, 750, 1750] {
+            responder.poll(now, &mut buffer).unwrap().unwrap();
+        }
+        assert_eq!(responder.state(), ResponderState::Running);
+
+        let source = "10.0.0.2:5353".parse().unwrap();
+        let response = b"\x00\x00\x84\x00\x00\x00\x00\x01\x00\x00\x00\x00\x03dev\x05local\x00\x00\x01\x80\x01\x00\x00\x00\x78\x00\x04\x0a";
+        let query = b"\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x03dev\x05local\x00\x00\x01\x00\x01";
+        for message in [&response[..], &query[..]] {
+            for len in 12..message.len() {
+                let message = Message::new(&message[..len]).unwrap();
+                assert!(responder.handle(&message, source, &mut buffer).is_err());
+            }
+        }
+        assert_eq!(responder.state(), ResponderState::Running);
+        let query = Message::new(&query[..]).unwrap();
+        assert!(responder.handle(&query, source, &mut buffer).unwrap().is_some());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn mdns_copy_records_past_pointer_range() {
+        let txt = alloc::vec![b'x'; 16351];
+        let mut source_buffer = alloc::vec::Vec::new();
+        let mut message = Message::new_mut(&mut source_buffer).unwrap();
+        let body = message.body_mut().unwrap().to_answer_section();
+        let mut body = body.append_answer()
+            .name()
+            .label(b"a").unwrap()
+            .finish().unwrap().unwrap()
+            .atype()
+            .rdata(&AType::TXT(&txt)).unwrap().unwrap()
+            .cache_flush(false).unwrap()
+            .aclass(AClass::IN).unwrap()
+            .ttl(120).unwrap()
+            .finish().unwrap();
+        // The first name starts just below 0x4000, its `local` label just above.
+        for label in [b"aaaaaaaaaaaaaaaa".as_slice(), b"x"] {
+            body = body.append_answer()
+                .name()
+                .label(label).unwrap()
+                .label(b"local").unwrap()
+                .finish().unwrap().unwrap()
+                .atype()
+                .a()
+                .ip(&[169, 254, 0, 1]).unwrap()
+                .finish().unwrap()
+                .cache_flush(true).unwrap()
+                .aclass(AClass::IN).unwrap()
+                .ttl(120).unwrap()
+                .finish().unwrap();
+        }
+
+        let mut i = 12;
+        let records = [(); 3].map(|_| Answer::parse(&source_buffer, &mut i).unwrap());
+        let mut buffer = alloc::vec::Vec::new();
+        let mut message = Message::new_mut(&mut buffer).unwrap();
+        let mut body = message.body_mut().unwrap().to_answer_section();
+        for record in &records {
+            body = body.append_record(record).unwrap();
+        }
+        assert_eq!(buffer.len(), source_buffer.len());
+
+        let message = Message::new(&buffer).unwrap();
+        let mut body = message.body().unwrap().to_answer_section();
+        let mut answers = body.answers().skip(1);
+        assert_eq!(answers.next().unwrap().name(), b"aaaaaaaaaaaaaaaa.local".as_slice());
+        assert_eq!(answers.next().unwrap().name(), b"x.local".as_slice());
+    }
+
+    #[cfg(all(feature = "embassy", feature = "embedded-nal"))]
+    #[tokio::test]
+    async fn mdns_async_nal_transport() {
+        use core::convert::Infallible;
+        use core::net::SocketAddr;
+        use embedded_nal_async::UnconnectedUdp;
+
+        struct Datagrams<'d>(&'d [&'d [u8]]);
+
+        impl UnconnectedUdp for Datagrams<'_> {
+            type Error = Infallible;
+
+            async fn send(&mut self, _: SocketAddr, _: SocketAddr, _: &[u8]) -> Result<(), Infallible> {
+                Ok(())
+            }
+
+            async fn receive_into(&mut self, buffer: &mut [u8]) -> Result<(usize, SocketAddr, SocketAddr), Infallible> {
+                let (datagram, rest) = self.0.split_first().unwrap();
+                self.0 = rest;
+                let len = datagram.len().min(buffer.len());
+                buffer[..len].copy_from_slice(&datagram[..len]);
+                Ok((datagram.len(), "0.0.0.0:5353".parse().unwrap(), "169.254.0.2:5353".parse().unwrap()))
+            }
+        }
+
+        // The first datagram does not fit and is skipped.
+        let mut socket = AsyncNalMdnsSocket::new(Datagrams(&[&[1; 20], &[2; 12]]));
+        let mut buffer = [0; 16];
+        let (len, source) = MdnsTransport::recv_from(&mut socket, &mut buffer).await.unwrap();
+        assert_eq!((len, source), (12, "169.254.0.2:5353".parse().unwrap()));
+        assert_eq!(buffer[..12], [2; 12]);
+    }
 }
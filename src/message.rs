@@ -1,4 +1,4 @@
-use crate::{Header, MessageBody, QuestionsSection};
+use crate::{Error, Header, HeaderOpcode, MessageBody, QuestionsSection, ZoneSection};
 use core::mem::size_of;
 
 pub trait Buffer {
@@ -47,7 +47,12 @@ impl<const CAP: usize> ExtendableBuffer for arrayvec::ArrayVec<u8, CAP> {
             if offset + size > CAP {
                 return Err(());
             }
-            unsafe { self.set_len(offset + size) };
+            // Newly covered bytes are uninitialized, start them out zeroed.
+            let len = self.len();
+            unsafe {
+                self.as_mut_ptr().add(len).write_bytes(0, offset + size - len);
+                self.set_len(offset + size);
+            }
         }
 
         Ok(&mut self.as_mut_slice()[offset..offset + size])
@@ -89,6 +94,168 @@ impl Buffer for [u8] {
     }
 }
 
+impl MutBuffer for [u8] {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Buffer for alloc::vec::Vec<u8> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.as_slice()
+    }
+
+    fn bytes_at(&self, offset: usize, size: usize) -> Result<&[u8], ()> {
+        if offset + size > self.len() {
+            return Err(());
+        }
+
+        Ok(&self.as_slice()[offset..offset + size])
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl MutBuffer for alloc::vec::Vec<u8> {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self.as_mut_slice()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ExtendableBuffer for alloc::vec::Vec<u8> {
+    fn bytes_mut_at(&mut self, offset: usize, size: usize) -> Result<&mut [u8], ()> {
+        if offset + size > self.len() {
+            self.resize(offset + size, 0);
+        }
+
+        Ok(&mut self.as_mut_slice()[offset..offset + size])
+    }
+
+    fn extend_from_slice(&mut self, slice: &[u8]) -> Result<(), ()> {
+        alloc::vec::Vec::extend_from_slice(self, slice);
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        alloc::vec::Vec::truncate(self, len);
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Buffer for bytes::BytesMut {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn bytes_at(&self, offset: usize, size: usize) -> Result<&[u8], ()> {
+        if offset + size > self.len() {
+            return Err(());
+        }
+
+        Ok(&self[offset..offset + size])
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl MutBuffer for bytes::BytesMut {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ExtendableBuffer for bytes::BytesMut {
+    fn bytes_mut_at(&mut self, offset: usize, size: usize) -> Result<&mut [u8], ()> {
+        if offset + size > self.len() {
+            self.resize(offset + size, 0);
+        }
+
+        Ok(&mut self[offset..offset + size])
+    }
+
+    fn extend_from_slice(&mut self, slice: &[u8]) -> Result<(), ()> {
+        bytes::BytesMut::extend_from_slice(self, slice);
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        bytes::BytesMut::truncate(self, len);
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Buffer for bytes::Bytes {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn bytes_at(&self, offset: usize, size: usize) -> Result<&[u8], ()> {
+        if offset + size > self.len() {
+            return Err(());
+        }
+
+        Ok(&self[offset..offset + size])
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> Buffer for heapless::Vec<u8, N> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn bytes_at(&self, offset: usize, size: usize) -> Result<&[u8], ()> {
+        if offset + size > self.len() {
+            return Err(());
+        }
+
+        Ok(&self[offset..offset + size])
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> MutBuffer for heapless::Vec<u8, N> {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> ExtendableBuffer for heapless::Vec<u8, N> {
+    fn bytes_mut_at(&mut self, offset: usize, size: usize) -> Result<&mut [u8], ()> {
+        if offset + size > self.len() {
+            self.resize(offset + size, 0).map_err(|_| ())?;
+        }
+
+        Ok(&mut self[offset..offset + size])
+    }
+
+    fn extend_from_slice(&mut self, slice: &[u8]) -> Result<(), ()> {
+        heapless::Vec::extend_from_slice(self, slice).map_err(|_| ())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        heapless::Vec::truncate(self, len);
+    }
+}
+
 /// A container for a mDNS message.
 pub struct Message<'a, B: Buffer + ?Sized, const WRITE: bool> {
     _marker: core::marker::PhantomData<&'a B>,
@@ -112,10 +279,10 @@ impl<'a, B: Buffer + ?Sized> Message<'a, B, true> {
 
 impl<'a, B: Buffer + ?Sized> Message<'a, B, false> {
     /// Creates a new message.
-    pub fn new(buffer: &'a B) -> Result<Self, ()> {
+    pub fn new(buffer: &'a B) -> Result<Self, Error> {
         if buffer.len() < size_of::<Header>() {
             // Buffer must be at least the size of a header.
-            return Err(());
+            return Err(Error);
         }
 
         Ok(Self {
@@ -127,17 +294,32 @@ impl<'a, B: Buffer + ?Sized> Message<'a, B, false> {
 
 impl<'a, B: Buffer + ?Sized, const WRITE: bool> Message<'a, B, WRITE> {
     /// Returns the message header.
-    pub fn header(&self) -> Result<&Header, ()> {
+    pub fn header(&self) -> Result<&Header, Error> {
         let bytes = unsafe { &*self.buffer }.bytes_at(0, size_of::<Header>())?;
         Ok(unsafe { &*(bytes.as_ptr() as *const Header) })
     }
 
     /// Returns the message body.
-    pub fn body(&self) -> Result<MessageBody<'a, B, QuestionsSection, false>, ()> {
+    pub fn body(&self) -> Result<MessageBody<'a, B, QuestionsSection, false>, Error> {
         let header = self.header()?;
 
         Ok(unsafe { MessageBody::new(header, self.buffer) })
     }
+
+    /// Returns the body of an UPDATE message, whose sections take the place of the question,
+    /// answer and authority sections.
+    ///
+    /// According to [RFC 2136 Section 2](https://tools.ietf.org/rfc/rfc2136#section-2).
+    #[inline(always)]
+    pub fn update_body(&self) -> Result<MessageBody<'a, B, ZoneSection, false>, Error> {
+        Ok(self.body()?.into_section())
+    }
+
+    /// Returns the bytes of the message, e.g. to send it.
+    #[inline(always)]
+    pub fn bytes(&self) -> &[u8] {
+        unsafe { &*self.buffer }.bytes()
+    }
 }
 
 impl<'a, B: ExtendableBuffer + ?Sized> Message<'a, B, true> {
@@ -154,4 +336,12 @@ impl<'a, B: ExtendableBuffer + ?Sized> Message<'a, B, true> {
 
         Ok(unsafe { MessageBody::new(header, self.buffer) })
     }
+
+    /// Sets the opcode to UPDATE and returns the body to write its sections, see
+    /// [`Message::update_body`].
+    #[inline(always)]
+    pub fn update_body_mut(&mut self) -> Result<MessageBody<'a, B, ZoneSection, true>, ()> {
+        self.header_mut()?.set_opcode(HeaderOpcode::Update);
+        Ok(self.body_mut()?.into_section())
+    }
 }
@@ -0,0 +1,207 @@
+use crate::{AClass, Error, Header, MutBuffer, Name, Question};
+use core::mem::size_of;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// The type of `A` records.
+const A: u16 = 1;
+/// The type of `AAAA` records.
+const AAAA: u16 = 28;
+/// The type of `OPT` pseudo records, whose class and TTL fields hold other data.
+const OPT: u16 = 41;
+
+/// The section a record was found in.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RecordSection {
+    Answer,
+    Authority,
+    Additional,
+}
+
+/// A received message whose fixed-size fields are patched in place, e.g. by a proxy or a
+/// cache refreshing its records. Names and record data of other types cannot change size
+/// in place, such messages have to be rebuilt.
+pub struct MessageMut<'a, B: MutBuffer + ?Sized> {
+    buffer: &'a mut B,
+}
+
+impl<'a, B: MutBuffer + ?Sized> MessageMut<'a, B> {
+    /// Wraps the bytes of a message, which have to hold at least a header.
+    pub fn new(buffer: &'a mut B) -> Result<Self, ()> {
+        if buffer.len() < size_of::<Header>() {
+            return Err(());
+        }
+
+        Ok(Self { buffer })
+    }
+
+    /// Returns the message header.
+    #[inline(always)]
+    pub fn header(&self) -> &Header {
+        let bytes = self.buffer.bytes();
+        unsafe { &*(bytes.as_ptr() as *const Header) }
+    }
+
+    /// Returns the message header, e.g. to change the ID or turn a query into a response.
+    #[inline(always)]
+    pub fn header_mut(&mut self) -> &mut Header {
+        Header::from_bytes(self.buffer.bytes_mut())
+    }
+
+    /// Returns the bytes of the message.
+    #[inline(always)]
+    pub fn bytes(&self) -> &[u8] {
+        self.buffer.bytes()
+    }
+
+    /// Calls `f` with each record of the answer, authority and additional sections in
+    /// order, stopping at the first error.
+    ///
+    /// Fails if the message is malformed, in which case the records before the malformed
+    /// one have been visited already.
+    pub fn for_each_record<F>(&mut self, mut f: F) -> Result<(), ()>
+    where
+        F: FnMut(&mut RecordMut<'_>) -> Result<(), ()>,
+    {
+        let header = *self.header();
+        let bytes = self.buffer.bytes_mut();
+        let mut i = size_of::<Header>();
+        for _ in 0..header.question_count() {
+            Question::parse(bytes, &mut i)?;
+        }
+
+        let sections = [
+            (RecordSection::Answer, header.answer_count()),
+            (RecordSection::Authority, header.name_server_count()),
+            (RecordSection::Additional, header.additional_records_count()),
+        ];
+        for (section, count) in sections {
+            for _ in 0..count {
+                let name = i;
+                Name::parse(bytes, &mut i)?;
+                let data_len = u16::from_be_bytes(bytes.get(i + 8..i + 10).ok_or(())?.try_into().map_err(|_| ())?);
+                if bytes.len() < i + 10 + data_len as usize {
+                    return Err(());
+                }
+
+                f(&mut RecordMut { bytes, name, fields: i, section })?;
+                i += 10 + data_len as usize;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A record of a [`MessageMut`], see [`MessageMut::for_each_record`].
+pub struct RecordMut<'b> {
+    bytes: &'b mut [u8],
+    name: usize,
+    /// The offset of the type, followed by the class, TTL, data length and data.
+    fields: usize,
+    section: RecordSection,
+}
+
+impl RecordMut<'_> {
+    /// Returns the section the record was found in.
+    #[inline(always)]
+    pub fn section(&self) -> RecordSection {
+        self.section
+    }
+
+    /// Returns the name of the record.
+    pub fn name(&self) -> Result<Name<'_>, Error> {
+        Name::parse(self.bytes, &mut self.name.clone())
+    }
+
+    /// Returns the numeric type of the record.
+    #[inline(always)]
+    pub fn type_id(&self) -> u16 {
+        self.u16_at(0)
+    }
+
+    #[inline(always)]
+    pub fn aclass(&self) -> AClass {
+        AClass::from(self.u16_at(2) & 0x7fff)
+    }
+
+    #[inline(always)]
+    pub fn cache_flush(&self) -> bool {
+        self.type_id() != OPT && self.u16_at(2) & 0x8000 != 0
+    }
+
+    #[inline(always)]
+    pub fn ttl(&self) -> u32 {
+        let i = self.fields + 4;
+        u32::from_be_bytes([self.bytes[i], self.bytes[i + 1], self.bytes[i + 2], self.bytes[i + 3]])
+    }
+
+    /// Returns the record data.
+    #[inline(always)]
+    pub fn data(&self) -> &[u8] {
+        &self.bytes[self.fields + 10..self.fields + 10 + self.u16_at(8) as usize]
+    }
+
+    /// Sets or clears the cache-flush bit. Fails on `OPT` records, which use the bit as part
+    /// of the payload size.
+    ///
+    /// According to [RFC 6762 Section 10.2](https://tools.ietf.org/rfc/rfc6762#section-10.2).
+    pub fn set_cache_flush(&mut self, cache_flush: bool) -> Result<(), ()> {
+        if self.type_id() == OPT {
+            return Err(());
+        }
+
+        let i = self.fields + 2;
+        if cache_flush {
+            self.bytes[i] |= 0b1000_0000;
+        } else {
+            self.bytes[i] &= 0b0111_1111;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the TTL. Fails on `OPT` records, which hold flags in its place.
+    pub fn set_ttl(&mut self, ttl: u32) -> Result<(), ()> {
+        if self.type_id() == OPT {
+            return Err(());
+        }
+
+        let i = self.fields + 4;
+        self.bytes[i..i + 4].copy_from_slice(&ttl.to_be_bytes());
+        Ok(())
+    }
+
+    /// Decrements the TTL by the given number of seconds, stopping at 0, and returns the
+    /// new TTL.
+    pub fn decrement_ttl(&mut self, seconds: u32) -> Result<u32, ()> {
+        let ttl = self.ttl().saturating_sub(seconds);
+        self.set_ttl(ttl)?;
+        Ok(ttl)
+    }
+
+    /// Replaces the address of an `A` record.
+    pub fn set_ipv4(&mut self, address: Ipv4Addr) -> Result<(), ()> {
+        self.set_data(A, &address.octets())
+    }
+
+    /// Replaces the address of an `AAAA` record.
+    pub fn set_ipv6(&mut self, address: Ipv6Addr) -> Result<(), ()> {
+        self.set_data(AAAA, &address.octets())
+    }
+
+    fn set_data(&mut self, atype: u16, data: &[u8]) -> Result<(), ()> {
+        if self.type_id() != atype || self.u16_at(8) as usize != data.len() {
+            return Err(());
+        }
+
+        let i = self.fields + 10;
+        self.bytes[i..i + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn u16_at(&self, offset: usize) -> u16 {
+        let i = self.fields + offset;
+        u16::from_be_bytes([self.bytes[i], self.bytes[i + 1]])
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{Buffer, Message, MDNS_IPV4, MDNS_PORT};
+use core::net::{Ipv4Addr, SocketAddr};
+use embedded_nal::{nb, UdpFullStack};
+use embedded_nal_async::UnconnectedUdp;
+
+/// A UDP socket of an `embedded-nal` stack bound to the mDNS port.
+///
+/// The traits have no notion of multicast groups, so the stack has to be configured to
+/// receive datagrams sent to `224.0.0.251` by other means. Like the stack, all methods are
+/// non-blocking and return `WouldBlock` when they cannot complete yet, use `nb::block!` to
+/// wait for them.
+pub struct NalMdnsSocket<S: UdpFullStack> {
+    stack: S,
+    socket: S::UdpSocket,
+}
+
+impl<S: UdpFullStack> NalMdnsSocket<S> {
+    /// Opens a socket on the stack and binds it to the mDNS port. A stack which is used for
+    /// other sockets too can be passed as `&mut stack`.
+    pub fn new(mut stack: S) -> Result<Self, S::Error> {
+        let mut socket = stack.socket()?;
+        if let Err(error) = stack.bind(&mut socket, MDNS_PORT) {
+            // The bind error is more telling than a failure to close.
+            let _ = stack.close(socket);
+            return Err(error);
+        }
+
+        Ok(Self { stack, socket })
+    }
+
+    /// Closes the socket, returning the stack.
+    pub fn close(mut self) -> Result<S, S::Error> {
+        self.stack.close(self.socket)?;
+        Ok(self.stack)
+    }
+
+    /// Sends a message to the IPv4 multicast group.
+    pub fn send<B: Buffer + ?Sized, const WRITE: bool>(
+        &mut self,
+        message: &Message<'_, B, WRITE>,
+    ) -> nb::Result<(), S::Error> {
+        self.send_to(message, SocketAddr::new(MDNS_IPV4.into(), MDNS_PORT))
+    }
+
+    /// Sends a message to the given address, e.g. a unicast response to a querier.
+    pub fn send_to<B: Buffer + ?Sized, const WRITE: bool>(
+        &mut self,
+        message: &Message<'_, B, WRITE>,
+        destination: SocketAddr,
+    ) -> nb::Result<(), S::Error> {
+        self.stack.send_to(&mut self.socket, destination, message.bytes())
+    }
+
+    /// Receives the next message into the buffer with the address it came from, skipping
+    /// datagrams shorter than a header.
+    pub fn recv<'b>(&mut self, buffer: &'b mut [u8]) -> nb::Result<(Message<'b, [u8], false>, SocketAddr), S::Error> {
+        let (len, source) = loop {
+            let (len, source) = self.stack.receive(&mut self.socket, buffer)?;
+            if len >= 12 && len <= buffer.len() {
+                break (len, source);
+            }
+        };
+
+        let buffer: &'b [u8] = buffer;
+        let message = Message::new(&buffer[..len]).expect("checked the length");
+        Ok((message, source))
+    }
+}
+
+/// An `embedded-nal-async` UDP socket bound to the mDNS port.
+///
+/// Like [`NalMdnsSocket`], the socket has to be joined to the multicast group by other
+/// means.
+pub struct AsyncNalMdnsSocket<S: UnconnectedUdp> {
+    socket: S,
+    local: SocketAddr,
+}
+
+impl<S: UnconnectedUdp> AsyncNalMdnsSocket<S> {
+    /// Wraps a socket, sending from the unspecified address and the mDNS port.
+    pub fn new(socket: S) -> Self {
+        Self::with_local(socket, SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), MDNS_PORT))
+    }
+
+    /// Wraps a socket, sending from the given local address, which has to be the bound
+    /// address for sockets bound to a single address.
+    pub fn with_local(socket: S, local: SocketAddr) -> Self {
+        Self { socket, local }
+    }
+
+    /// Returns the underlying socket.
+    #[inline(always)]
+    pub fn socket(&mut self) -> &mut S {
+        &mut self.socket
+    }
+
+    /// Returns the underlying socket.
+    #[inline(always)]
+    pub fn into_socket(self) -> S {
+        self.socket
+    }
+
+    /// Sends a message to the IPv4 multicast group.
+    pub async fn send<B: Buffer + ?Sized, const WRITE: bool>(
+        &mut self,
+        message: &Message<'_, B, WRITE>,
+    ) -> Result<(), S::Error> {
+        self.send_to(message, SocketAddr::new(MDNS_IPV4.into(), MDNS_PORT)).await
+    }
+
+    /// Sends a message to the given address, e.g. a unicast response to a querier.
+    pub async fn send_to<B: Buffer + ?Sized, const WRITE: bool>(
+        &mut self,
+        message: &Message<'_, B, WRITE>,
+        destination: SocketAddr,
+    ) -> Result<(), S::Error> {
+        self.socket.send(self.local, destination, message.bytes()).await
+    }
+
+    /// Receives the next message into the buffer with the address it came from, skipping
+    /// datagrams shorter than a header and datagrams truncated by the buffer.
+    pub async fn recv<'b>(&mut self, buffer: &'b mut [u8]) -> Result<(Message<'b, [u8], false>, SocketAddr), S::Error> {
+        let (len, source) = loop {
+            let (len, _, source) = self.socket.receive_into(buffer).await?;
+            if len >= 12 && len <= buffer.len() {
+                break (len, source);
+            }
+        };
+
+        let buffer: &'b [u8] = buffer;
+        let message = Message::new(&buffer[..len]).expect("checked the length");
+        Ok((message, source))
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl<S: UnconnectedUdp> crate::MdnsTransport for AsyncNalMdnsSocket<S> {
+    type Error = S::Error;
+
+    /// Skips datagrams truncated by the buffer, like [`AsyncNalMdnsSocket::recv`].
+    async fn recv_from(&mut self, buffer: &mut [u8]) -> Result<(usize, SocketAddr), Self::Error> {
+        loop {
+            let (len, _, source) = self.socket.receive_into(buffer).await?;
+            if len <= buffer.len() {
+                return Ok((len, source));
+            }
+        }
+    }
+
+    async fn send_to(&mut self, datagram: &[u8], destination: SocketAddr) -> Result<(), Self::Error> {
+        self.socket.send(self.local, destination, datagram).await
+    }
+}
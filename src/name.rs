@@ -1,5 +1,5 @@
-use core::fmt::{Display, Error, Formatter, Write};
-use crate::ExtendableBuffer;
+use core::fmt::{Display, Formatter, Write};
+use crate::{Error, ExtendableBuffer};
 
 /// A DNS name.
 #[derive(Debug, Clone)]
@@ -9,13 +9,13 @@ pub struct Name<'a> {
 }
 
 impl<'a> Name<'a> {
-    pub fn parse(bytes: &'a [u8], i: &mut usize) -> Result<Self, ()> {
+    pub fn parse(bytes: &'a [u8], i: &mut usize) -> Result<Self, Error> {
         const MAX_LENGTH: usize = 255;
         let mut j = *i;
 
         loop {
             if j - *i >= MAX_LENGTH {
-                return Err(());
+                return Err(Error);
             }
 
             match LabelType::from_bytes(bytes, &mut j)? {
@@ -37,6 +37,128 @@ impl<'a> Name<'a> {
 
         Ok(Self { bytes, offset })
     }
+
+    /// Parses a name that must not be compressed, as in the data of `RRSIG` and `SVCB` records.
+    pub(crate) fn parse_uncompressed(bytes: &'a [u8], i: &mut usize) -> Result<Self, ()> {
+        let name = Self::parse(bytes, i)?;
+        let mut j = name.offset;
+        loop {
+            match LabelType::from_bytes(bytes, &mut j)? {
+                LabelType::Pointer(_) => return Err(()),
+                LabelType::Part(0) => return Ok(name),
+                LabelType::Part(len) => j += len as usize,
+            }
+        }
+    }
+
+    /// Returns the name as it appears on the wire, up to the terminating null label or the
+    /// first compression pointer.
+    pub(crate) fn wire(&self) -> &'a [u8] {
+        let mut j = self.offset;
+        while let Ok(LabelType::Part(len @ 1..)) = LabelType::from_bytes(self.bytes, &mut j) {
+            j += len as usize;
+        }
+
+        &self.bytes[self.offset..j.min(self.bytes.len())]
+    }
+
+    /// Returns an iterator over the labels of the name, following compression pointers.
+    #[inline(always)]
+    pub fn labels(&self) -> Labels<'a> {
+        Labels {
+            bytes: self.bytes,
+            i: self.offset,
+            depth: 0,
+        }
+    }
+
+    /// Compares two names ignoring ASCII case, as DNS names are case-insensitive.
+    ///
+    /// According to [RFC 1035 Section 2.3.3](https://tools.ietf.org/rfc/rfc1035#section-2.3.3).
+    #[inline(always)]
+    pub fn eq_ignore_ascii_case(&self, other: &Name<'_>) -> bool {
+        self.eq_by(other, <[u8]>::eq_ignore_ascii_case)
+    }
+
+    /// Compares the name case-insensitively with a sequence of uncompressed labels.
+    pub(crate) fn eq_labels<'l>(&self, labels: impl IntoIterator<Item = &'l [u8]>) -> bool {
+        let mut self_labels = self.labels();
+        for label in labels {
+            match self_labels.next() {
+                Some(part) if part.eq_ignore_ascii_case(label) => {}
+                _ => return false,
+            }
+        }
+
+        self_labels.next().is_none() && self_labels.is_root()
+    }
+
+    /// Compares two names label by label, regardless of how either of them is compressed.
+    fn eq_by(&self, other: &Name<'_>, eq: impl Fn(&[u8], &[u8]) -> bool) -> bool {
+        let mut self_labels = self.labels();
+        let mut other_labels = other.labels();
+
+        loop {
+            match (self_labels.next(), other_labels.next()) {
+                (Some(part), Some(other_part)) if eq(part, other_part) => {}
+                (None, None) => return self_labels.is_root() && other_labels.is_root(),
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// An iterator over the labels of a [`Name`].
+#[derive(Debug, Clone)]
+pub struct Labels<'a> {
+    bytes: &'a [u8],
+    i: usize,
+    depth: usize,
+}
+
+impl<'a> Labels<'a> {
+    /// Whether the iterator stopped at the terminating null label rather than at malformed data.
+    #[inline(always)]
+    fn is_root(&self) -> bool {
+        self.bytes.get(self.i) == Some(&0)
+    }
+
+    /// Returns the name made up of the labels that have not been yielded yet.
+    #[inline(always)]
+    pub fn remainder(&self) -> Name<'a> {
+        Name {
+            bytes: self.bytes,
+            offset: self.i,
+        }
+    }
+}
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.depth > 255 {
+                return None;
+            }
+            self.depth += 1;
+
+            let mut j = self.i;
+            match LabelType::from_bytes(self.bytes, &mut j).ok()? {
+                LabelType::Pointer(ptr) => {
+                    self.i = ptr as usize;
+                }
+                LabelType::Part(len) => {
+                    if len == 0 || self.bytes.len() < j + len as usize {
+                        return None;
+                    }
+
+                    self.i = j + len as usize;
+                    return Some(&self.bytes[j..j + len as usize]);
+                }
+            }
+        }
+    }
 }
 
 impl PartialEq<[u8]> for Name<'_> {
@@ -48,7 +170,10 @@ impl PartialEq<[u8]> for Name<'_> {
             if depth > 255 {
                 return false;
             }
-            match LabelType::from_bytes(self.bytes, &mut i).unwrap() {
+            let Ok(label) = LabelType::from_bytes(self.bytes, &mut i) else {
+                return false;
+            };
+            match label {
                 LabelType::Pointer(ptr) => {
                     if ptr < self.offset as u16 {
                         i = ptr as usize;
@@ -66,7 +191,7 @@ impl PartialEq<[u8]> for Name<'_> {
 
                     let part = &self.bytes[i..i + len as usize];
                     if j > 0 {
-                        if other[j] == b'.' {
+                        if other.get(j) == Some(&b'.') {
                             j += 1;
                         } else {
                             return false;
@@ -91,102 +216,43 @@ impl PartialEq<[u8]> for Name<'_> {
 
 impl PartialEq<Name<'_>> for Name<'_> {
     fn eq(&self, other: &Name<'_>) -> bool {
-        let mut self_i = self.offset;
-        let mut self_depth = 0;
-        let mut other_i = other.offset;
-        let mut other_depth = 0;
-
-        loop {
-            if self_depth > 255 || other_depth > 255 {
-                return false;
-            }
-
-            match LabelType::from_bytes(self.bytes, &mut self_i).unwrap() {
-                LabelType::Pointer(ptr) => {
-                    if ptr < self.offset as u16 {
-                        self_i = ptr as usize;
-                    } else {
-                        return false;
-                    }
-                }
-                LabelType::Part(len) => {
-                    if self.bytes.len() < self_i + len as usize {
-                        return false;
-                    }
-                    let part = &self.bytes[self_i..self_i + len as usize];
-                    match LabelType::from_bytes(other.bytes, &mut other_i).unwrap() {
-                        LabelType::Pointer(ptr) => {
-                            if ptr < other.offset as u16 {
-                                other_i = ptr as usize;
-                            } else {
-                                return false;
-                            }
-                        }
-                        LabelType::Part(other_len) => {
-                            if len != other_len {
-                                return false;
-                            }
-                            if len == 0 {
-                                return true;
-                            }
-                            if other.bytes.len() < other_i + other_len as usize {
-                                return false;
-                            }
-
-                            let other_part = &other.bytes[other_i..other_i + other_len as usize];
-                            if part != other_part {
-                                return false;
-                            }
-                            other_i += other_len as usize;
-                        }
-                    }
-
-                    self_i += len as usize;
-                }
-            }
-
-            self_depth += 1;
-            other_depth += 1;
-        }
+        self.eq_by(other, |a, b| a == b)
     }
 }
 
 impl Display for Name<'_> {
+    /// Writes the name in presentation format, escaping dots and special characters in labels.
+    ///
+    /// According to [RFC 1035 Section 5.1](https://tools.ietf.org/rfc/rfc1035#section-5.1).
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        let mut i = self.offset;
-        let mut depth = 0;
-        loop {
-            if depth > 255 {
-                return Err(Error::default());
+        let mut labels = self.labels();
+        for (depth, label) in labels.by_ref().enumerate() {
+            if depth > 0 {
+                f.write_char('.')?;
             }
-            match LabelType::from_bytes(self.bytes, &mut i).unwrap() {
-                LabelType::Pointer(ptr) => {
-                    if ptr < self.offset as u16 {
-                        i = ptr as usize;
-                    } else {
-                        // Cannot point outside of the message or to itself.
-                        return Err(Error::default());
+            for chunk in label.utf8_chunks() {
+                for c in chunk.valid().chars() {
+                    match c {
+                        '.' | '\\' | '"' | '(' | ')' | ';' | '@' | '$' => {
+                            f.write_char('\\')?;
+                            f.write_char(c)?;
+                        }
+                        c if c <= ' ' || c == '\x7f' => write!(f, "\\{:03}", c as u32)?,
+                        c => f.write_char(c)?,
                     }
                 }
-                LabelType::Part(len) => {
-                    if len == 0 {
-                        return Ok(());
-                    }
-                    if self.bytes.len() < i + len as usize {
-                        return Err(Error::default());
-                    }
-
-                    let part = &self.bytes[i..i + len as usize];
-                    if depth > 0 {
-                        f.write_char('.')?;
-                    }
-                    f.write_str(core::str::from_utf8(part).unwrap())?;
-                    i += len as usize;
+                for byte in chunk.invalid() {
+                    write!(f, "\\{:03}", byte)?;
                 }
             }
+        }
 
-            depth += 1;
+        if !labels.is_root() {
+            // Cannot point outside of the message or to itself.
+            return Err(core::fmt::Error);
         }
+
+        Ok(())
     }
 }
 
@@ -201,11 +267,11 @@ impl LabelType {
         const PTR_MASK: u8 = 0b11000000;
         const LEN_MASK: u8 = !PTR_MASK;
 
-        let c = bytes[*i];
+        let c = *bytes.get(*i).ok_or(())?;
 
         if c & PTR_MASK == PTR_MASK {
             let c = c & LEN_MASK;
-            let pointer = u16::from_be_bytes([c, bytes[*i + 1]]);
+            let pointer = u16::from_be_bytes([c, *bytes.get(*i + 1).ok_or(())?]);
             if pointer >= *i as u16 {
                 // Cannot point to the future.
                 return Err(());
@@ -294,7 +360,7 @@ impl<'a> NamePart for &Name<'a> {
             if depth > 255 {
                 return Err(());
             }
-            match LabelType::from_bytes(self.bytes, &mut i).unwrap() {
+            match LabelType::from_bytes(self.bytes, &mut i)? {
                 LabelType::Pointer(ptr) => {
                     i = ptr as usize;
                 }
@@ -319,11 +385,74 @@ impl<'a> NamePart for &Name<'a> {
     }
 }
 
+/// A name in presentation format, e.g. `Bob's\032Printer._ipp._tcp.local.`, written label by label.
+///
+/// Labels are separated by dots, `\c` escapes a character and `\DDD` a byte in decimal.
+pub struct PresentationName<'s>(pub &'s str);
+
+impl NamePart for PresentationName<'_> {
+    #[inline(always)]
+    fn to_bytes<B: ExtendableBuffer + ?Sized>(self, buf: &mut B) -> Result<(), ()> {
+        (&self).to_bytes(buf)
+    }
+}
+
+impl NamePart for &PresentationName<'_> {
+    fn to_bytes<B: ExtendableBuffer + ?Sized>(self, buf: &mut B) -> Result<(), ()> {
+        let text = self.0.as_bytes();
+        if text == b"." {
+            return Ok(());
+        }
+
+        let mut label = arrayvec::ArrayVec::<u8, 63>::new();
+        let mut i = 0;
+        while i < text.len() {
+            match text[i] {
+                b'.' => {
+                    if label.is_empty() {
+                        return Err(());
+                    }
+                    label.as_slice().to_bytes(buf)?;
+                    label.clear();
+                    i += 1;
+                }
+                b'\\' => label.try_push(unescape(text, &mut i)?).map_err(|_| ())?,
+                c => {
+                    label.try_push(c).map_err(|_| ())?;
+                    i += 1;
+                }
+            }
+        }
+        if !label.is_empty() {
+            label.as_slice().to_bytes(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes the `\c` or `\DDD` escape sequence starting at the backslash at `i`.
+pub(crate) fn unescape(text: &[u8], i: &mut usize) -> Result<u8, ()> {
+    match text.get(*i + 1..*i + 4) {
+        Some(digits) if digits.iter().all(u8::is_ascii_digit) => {
+            *i += 4;
+            let value = digits.iter().fold(0u16, |value, digit| value * 10 + (digit - b'0') as u16);
+            u8::try_from(value).map_err(|_| ())
+        }
+        _ => {
+            *i += 2;
+            text.get(*i - 1).copied().ok_or(())
+        }
+    }
+}
+
 pub struct NameBuilder<'a, B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O> {
     parent: P,
     finalizer: F,
     buffer: &'a mut B,
+    start: usize,
     last_offset: usize,
+    compressed: bool,
 }
 
 impl<'a, B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O> NameBuilder<'a, B, P, O, F> {
@@ -338,7 +467,23 @@ impl<'a, B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O> NameBuilder<'a, B, P
             parent,
             finalizer,
             buffer,
+            start: offset,
             last_offset: offset,
+            compressed: true,
+        }
+    }
+
+    /// A builder for a name that must not be compressed, failing in `finish` if a part
+    /// wrote a pointer.
+    #[inline(always)]
+    pub(crate) fn new_uncompressed(
+        buffer: &'a mut B,
+        parent: P,
+        finalizer: F,
+    ) -> Self {
+        Self {
+            compressed: false,
+            ..Self::new(buffer, parent, finalizer)
         }
     }
 
@@ -359,10 +504,19 @@ impl<'a, B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O> NameBuilder<'a, B, P
 
     #[inline(always)]
     pub fn finish(self) -> Result<O, ()> {
-        // If the last label is not a pointer, add a null label.
-        let end_of_name = self.buffer.len();
-        let last_bytes = &self.buffer.bytes()[end_of_name - 2..end_of_name];
-        if last_bytes[0] & 0b11000000 != 0b11000000 {
+        // If the last label is not a pointer, add a null label. The labels are walked from the
+        // start of the name, as label contents (e.g. UTF-8 instance names) may look like a pointer.
+        let bytes = self.buffer.bytes();
+        let mut i = self.start;
+        let mut ends_with_pointer = false;
+        while i < bytes.len() {
+            ends_with_pointer = bytes[i] & 0b11000000 == 0b11000000;
+            if ends_with_pointer && !self.compressed {
+                return Err(());
+            }
+            i += if ends_with_pointer { 2 } else { 1 + bytes[i] as usize };
+        }
+        if !ends_with_pointer {
             self.buffer.extend_from_slice(&[0])?;
         }
 
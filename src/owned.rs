@@ -0,0 +1,522 @@
+use crate::{
+    answer::{write_nsec3_params, write_nsec_types}, AClass, AType, Answer, AnswerBuilder, Buffer, Error, ExtendableBuffer, Header, Message, Name, NamePart,
+    PresentationName, QClass, QType, Question, RrsigFields,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// An owned copy of a message, which can be written again through the builders.
+///
+/// Names are kept in presentation format, see [`PresentationName`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename = "Message"))]
+pub struct OwnedMessage {
+    pub header: Header,
+    pub questions: Vec<OwnedQuestion>,
+    pub answers: Vec<OwnedAnswer>,
+    pub authorities: Vec<OwnedAnswer>,
+    pub additionals: Vec<OwnedAnswer>,
+}
+
+/// An owned copy of a [`Question`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename = "Question"))]
+pub struct OwnedQuestion {
+    pub name: String,
+    pub qtype: QType,
+    pub unicast_response: bool,
+    pub qclass: QClass,
+}
+
+/// An owned copy of an [`Answer`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename = "Answer"))]
+pub struct OwnedAnswer {
+    pub name: String,
+    pub atype: OwnedAType,
+    pub cache_flush: bool,
+    pub aclass: AClass,
+    pub ttl: u32,
+}
+
+/// An owned copy of an [`AType`].
+///
+/// The character strings of `TXT` records are kept as text, so records with malformed or
+/// non-UTF-8 strings cannot be copied.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename = "AType"))]
+pub enum OwnedAType {
+    A(Ipv4Addr),
+    NS(String),
+    CNAME(String),
+    SOA {
+        mname: String,
+        rname: String,
+        serial: u32,
+        refresh: u32,
+        retry: u32,
+        expire: u32,
+        minimum: u32,
+    },
+    PTR(String),
+    MX {
+        preference: u16,
+        exchange: String,
+    },
+    TXT(Vec<String>),
+    AAAA(Ipv6Addr),
+    SRV {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: String,
+    },
+    OPT {
+        udp_payload_size: u16,
+        extended_rcode: u8,
+        version: u8,
+        data: Vec<u8>,
+    },
+    NSEC {
+        next: String,
+        types: Vec<u16>,
+    },
+    DS {
+        key_tag: u16,
+        algorithm: u8,
+        digest_type: u8,
+        digest: Vec<u8>,
+    },
+    RRSIG {
+        type_covered: u16,
+        algorithm: u8,
+        labels: u8,
+        original_ttl: u32,
+        expiration: u32,
+        inception: u32,
+        key_tag: u16,
+        signer: String,
+        signature: Vec<u8>,
+    },
+    DNSKEY {
+        flags: u16,
+        protocol: u8,
+        algorithm: u8,
+        public_key: Vec<u8>,
+    },
+    NSEC3 {
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: Vec<u8>,
+        next_hashed: Vec<u8>,
+        types: Vec<u16>,
+    },
+    NSEC3PARAM {
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: Vec<u8>,
+    },
+    /// The SvcParams are kept as keys and raw values.
+    SVCB {
+        priority: u16,
+        target: String,
+        params: Vec<(u16, Vec<u8>)>,
+    },
+    HTTPS {
+        priority: u16,
+        target: String,
+        params: Vec<(u16, Vec<u8>)>,
+    },
+    Unknown {
+        atype: u16,
+        data: Vec<u8>,
+    },
+}
+
+impl OwnedMessage {
+    /// Copies a parsed message.
+    pub fn parse<B: Buffer + ?Sized>(message: &Message<'_, B, false>) -> Result<Self, Error> {
+        let header = *message.header()?;
+        let mut body = message.body()?;
+        let questions = body.questions().map(|question| OwnedQuestion::try_from(&question)).collect::<Result<Vec<_>, _>>()?;
+        let mut body = body.to_answer_section();
+        let answers = body.answers().map(|answer| OwnedAnswer::try_from(&answer)).collect::<Result<Vec<_>, _>>()?;
+        let mut body = body.to_authority_section();
+        let authorities = body.authorities().map(|answer| OwnedAnswer::try_from(&answer)).collect::<Result<Vec<_>, _>>()?;
+        let mut body = body.to_additional_section();
+        let additionals = body.additionals().map(|answer| OwnedAnswer::try_from(&answer)).collect::<Result<Vec<_>, _>>()?;
+
+        if questions.len() != header.question_count() as usize
+            || answers.len() != header.answer_count() as usize
+            || authorities.len() != header.name_server_count() as usize
+            || additionals.len() != header.additional_records_count() as usize
+        {
+            return Err(Error);
+        }
+
+        Ok(Self { header, questions, answers, authorities, additionals })
+    }
+
+    /// Writes the message into an empty buffer. The section counts of the header are
+    /// taken from the number of records, names are written uncompressed.
+    pub fn write<'b, B: ExtendableBuffer + ?Sized>(&self, buffer: &'b mut B) -> Result<Message<'b, B, true>, ()> {
+        let mut message = Message::new_mut(buffer)?;
+        let header = message.header_mut()?;
+        header.set_id(self.header.id());
+        header.set_kind(self.header.kind());
+        header.set_opcode(self.header.opcode());
+        header.set_authoritative_answer(self.header.authoritative_answer());
+        header.set_truncated(self.header.truncated());
+        header.set_recursion_desired(self.header.recursion_desired());
+        header.set_recursion_available(self.header.recursion_available());
+        header.set_response_code(self.header.response_code());
+
+        let mut body = message.body_mut()?;
+        for question in &self.questions {
+            if question.qtype == QType::Reserved || question.qclass == QClass::Reserved {
+                return Err(());
+            }
+
+            body = body.append_question()
+                .name()
+                .label(PresentationName(&question.name))?
+                .finish()?
+                .unicast_response(question.unicast_response)?
+                .qtype(question.qtype)?
+                .qclass(question.qclass)?
+                .finish()?;
+        }
+        let mut body = body.to_answer_section();
+        for answer in &self.answers {
+            body = answer.write(body.append_answer())??;
+        }
+        let mut body = body.to_authority_section();
+        for answer in &self.authorities {
+            body = answer.write(body.append_authority())??;
+        }
+        let mut body = body.to_additional_section();
+        for answer in &self.additionals {
+            body = answer.write(body.append_additional())??;
+        }
+
+        Ok(message)
+    }
+}
+
+impl OwnedAnswer {
+    /// Writes the record through an answer builder of any section.
+    pub fn write<B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O>(
+        &self,
+        builder: AnswerBuilder<'_, B, P, O, F, false, false, false, false, false>,
+    ) -> Result<O, ()> {
+        if self.aclass == AClass::Unknown && !matches!(self.atype, OwnedAType::OPT { .. }) {
+            return Err(());
+        }
+        let mut rdata = Vec::new();
+        let atype = self.atype.to_rdata(&mut rdata)?;
+        let atype = AType::parse(atype, rdata.len() as u16, &rdata, &mut 0)?;
+
+        let builder = builder
+            .name()
+            .label(PresentationName(&self.name))?
+            .finish()??
+            .atype()
+            .rdata(&atype)??;
+        let builder = match self.atype {
+            // The class of an OPT record holds the payload size, its TTL the extended RCODE and version.
+            OwnedAType::OPT { udp_payload_size, extended_rcode, version, .. } => {
+                let [_, _, flags @ ..] = self.ttl.to_be_bytes();
+                builder
+                    .udp_payload_size(udp_payload_size)?
+                    .ttl(u32::from_be_bytes([extended_rcode, version, flags[0], flags[1]]))?
+            }
+            _ => builder
+                .cache_flush(self.cache_flush)?
+                .aclass(self.aclass)?
+                .ttl(self.ttl)?,
+        };
+
+        Ok(builder.finish())
+    }
+}
+
+impl OwnedAType {
+    /// Appends the record data in wire format, returning the record type.
+    pub fn to_rdata(&self, rdata: &mut Vec<u8>) -> Result<u16, ()> {
+        fn name(rdata: &mut Vec<u8>, name: &str) -> Result<(), ()> {
+            PresentationName(name).to_bytes(rdata)?;
+            rdata.push(0);
+            Ok(())
+        }
+
+        match self {
+            OwnedAType::A(addr) => {
+                rdata.extend_from_slice(&addr.octets());
+                Ok(1)
+            }
+            OwnedAType::NS(target) => name(rdata, target).map(|_| 2),
+            OwnedAType::CNAME(target) => name(rdata, target).map(|_| 5),
+            OwnedAType::SOA { mname, rname, serial, refresh, retry, expire, minimum } => {
+                name(rdata, mname)?;
+                name(rdata, rname)?;
+                for value in [serial, refresh, retry, expire, minimum] {
+                    rdata.extend_from_slice(&value.to_be_bytes());
+                }
+                Ok(6)
+            }
+            OwnedAType::PTR(target) => name(rdata, target).map(|_| 12),
+            OwnedAType::MX { preference, exchange } => {
+                rdata.extend_from_slice(&preference.to_be_bytes());
+                name(rdata, exchange).map(|_| 15)
+            }
+            OwnedAType::TXT(strings) => {
+                for string in strings {
+                    let len = u8::try_from(string.len()).map_err(|_| ())?;
+                    rdata.push(len);
+                    rdata.extend_from_slice(string.as_bytes());
+                }
+                Ok(16)
+            }
+            OwnedAType::AAAA(addr) => {
+                rdata.extend_from_slice(&addr.octets());
+                Ok(28)
+            }
+            OwnedAType::SRV { priority, weight, port, target } => {
+                rdata.extend_from_slice(&priority.to_be_bytes());
+                rdata.extend_from_slice(&weight.to_be_bytes());
+                rdata.extend_from_slice(&port.to_be_bytes());
+                name(rdata, target).map(|_| 33)
+            }
+            OwnedAType::OPT { data, .. } => {
+                rdata.extend_from_slice(data);
+                Ok(41)
+            }
+            OwnedAType::NSEC { next, types } => {
+                name(rdata, next)?;
+                write_nsec_types(types.iter().copied(), rdata).map(|_| 47)
+            }
+            OwnedAType::DS { key_tag, algorithm, digest_type, digest } => {
+                rdata.extend_from_slice(&key_tag.to_be_bytes());
+                rdata.extend_from_slice(&[*algorithm, *digest_type]);
+                rdata.extend_from_slice(digest);
+                Ok(43)
+            }
+            OwnedAType::RRSIG {
+                type_covered,
+                algorithm,
+                labels,
+                original_ttl,
+                expiration,
+                inception,
+                key_tag,
+                signer,
+                signature,
+            } => {
+                let fields = RrsigFields {
+                    type_covered: *type_covered,
+                    algorithm: *algorithm,
+                    labels: *labels,
+                    original_ttl: *original_ttl,
+                    expiration: *expiration,
+                    inception: *inception,
+                    key_tag: *key_tag,
+                };
+                rdata.extend_from_slice(&fields.to_bytes());
+                name(rdata, signer)?;
+                rdata.extend_from_slice(signature);
+                Ok(46)
+            }
+            OwnedAType::DNSKEY { flags, protocol, algorithm, public_key } => {
+                rdata.extend_from_slice(&flags.to_be_bytes());
+                rdata.extend_from_slice(&[*protocol, *algorithm]);
+                rdata.extend_from_slice(public_key);
+                Ok(48)
+            }
+            OwnedAType::NSEC3 { hash_algorithm, flags, iterations, salt, next_hashed, types } => {
+                write_nsec3_params(*hash_algorithm, *flags, *iterations, salt, rdata)?;
+                rdata.push(u8::try_from(next_hashed.len()).map_err(|_| ())?);
+                rdata.extend_from_slice(next_hashed);
+                write_nsec_types(types.iter().copied(), rdata).map(|_| 50)
+            }
+            OwnedAType::NSEC3PARAM { hash_algorithm, flags, iterations, salt } => {
+                write_nsec3_params(*hash_algorithm, *flags, *iterations, salt, rdata).map(|_| 51)
+            }
+            OwnedAType::SVCB { priority, target, params } | OwnedAType::HTTPS { priority, target, params } => {
+                rdata.extend_from_slice(&priority.to_be_bytes());
+                name(rdata, target)?;
+                for (key, value) in params {
+                    rdata.extend_from_slice(&key.to_be_bytes());
+                    rdata.extend_from_slice(&u16::try_from(value.len()).map_err(|_| ())?.to_be_bytes());
+                    rdata.extend_from_slice(value);
+                }
+                match self {
+                    OwnedAType::SVCB { .. } => Ok(64),
+                    _ => Ok(65),
+                }
+            }
+            OwnedAType::Unknown { atype, data } => {
+                rdata.extend_from_slice(data);
+                Ok(*atype)
+            }
+        }
+    }
+}
+
+/// Fails on names which cannot be written in presentation format.
+impl TryFrom<&Question<'_>> for OwnedQuestion {
+    type Error = ();
+
+    fn try_from(question: &Question<'_>) -> Result<Self, ()> {
+        Ok(Self {
+            name: name_string(question.name())?,
+            qtype: question.qtype(),
+            unicast_response: question.unicast_response(),
+            qclass: question.qclass(),
+        })
+    }
+}
+
+/// Fails on names which cannot be written in presentation format and on malformed `TXT`
+/// records.
+impl TryFrom<&Answer<'_>> for OwnedAnswer {
+    type Error = ();
+
+    fn try_from(answer: &Answer<'_>) -> Result<Self, ()> {
+        Ok(Self {
+            name: name_string(answer.name())?,
+            atype: OwnedAType::try_from(answer.atype())?,
+            cache_flush: answer.cache_flush(),
+            aclass: answer.aclass(),
+            ttl: answer.ttl(),
+        })
+    }
+}
+
+/// Fails on names which cannot be written in presentation format and on malformed `TXT`
+/// records.
+impl TryFrom<&AType<'_>> for OwnedAType {
+    type Error = ();
+
+    fn try_from(atype: &AType<'_>) -> Result<Self, ()> {
+        Ok(match atype {
+            AType::A(addr) => OwnedAType::A(Ipv4Addr::from(*addr)),
+            AType::NS(name) => OwnedAType::NS(name_string(name)?),
+            AType::CNAME(name) => OwnedAType::CNAME(name_string(name)?),
+            AType::SOA(mname, rname, serial, refresh, retry, expire, minimum) => OwnedAType::SOA {
+                mname: name_string(mname)?,
+                rname: name_string(rname)?,
+                serial: *serial,
+                refresh: *refresh,
+                retry: *retry,
+                expire: *expire,
+                minimum: *minimum,
+            },
+            AType::PTR(name) => OwnedAType::PTR(name_string(name)?),
+            AType::MX(preference, exchange) => OwnedAType::MX {
+                preference: *preference,
+                exchange: name_string(exchange)?,
+            },
+            AType::TXT(txt) => OwnedAType::TXT(txt_strings(txt).map(|string| string.map(String::from)).collect::<Result<_, _>>()?),
+            AType::AAAA(addr) => OwnedAType::AAAA(Ipv6Addr::from(*addr)),
+            AType::SRV(priority, weight, port, target) => OwnedAType::SRV {
+                priority: *priority,
+                weight: *weight,
+                port: *port,
+                target: name_string(target)?,
+            },
+            AType::OPT(udp_payload_size, extended_rcode, version, data) => OwnedAType::OPT {
+                udp_payload_size: *udp_payload_size,
+                extended_rcode: *extended_rcode,
+                version: *version,
+                data: data.to_vec(),
+            },
+            AType::NSEC(next, _) => OwnedAType::NSEC {
+                next: name_string(next)?,
+                types: atype.nsec_types().collect(),
+            },
+            AType::DS(key_tag, algorithm, digest_type, digest) => OwnedAType::DS {
+                key_tag: *key_tag,
+                algorithm: *algorithm,
+                digest_type: *digest_type,
+                digest: digest.to_vec(),
+            },
+            AType::RRSIG(fields, signer, signature) => OwnedAType::RRSIG {
+                type_covered: fields.type_covered,
+                algorithm: fields.algorithm,
+                labels: fields.labels,
+                original_ttl: fields.original_ttl,
+                expiration: fields.expiration,
+                inception: fields.inception,
+                key_tag: fields.key_tag,
+                signer: name_string(signer)?,
+                signature: signature.to_vec(),
+            },
+            AType::DNSKEY(flags, protocol, algorithm, public_key) => OwnedAType::DNSKEY {
+                flags: *flags,
+                protocol: *protocol,
+                algorithm: *algorithm,
+                public_key: public_key.to_vec(),
+            },
+            AType::NSEC3(hash_algorithm, flags, iterations, salt, next_hashed, _) => OwnedAType::NSEC3 {
+                hash_algorithm: *hash_algorithm,
+                flags: *flags,
+                iterations: *iterations,
+                salt: salt.to_vec(),
+                next_hashed: next_hashed.to_vec(),
+                types: atype.nsec_types().collect(),
+            },
+            AType::NSEC3PARAM(hash_algorithm, flags, iterations, salt) => OwnedAType::NSEC3PARAM {
+                hash_algorithm: *hash_algorithm,
+                flags: *flags,
+                iterations: *iterations,
+                salt: salt.to_vec(),
+            },
+            AType::SVCB(priority, target, _) => OwnedAType::SVCB {
+                priority: *priority,
+                target: name_string(target)?,
+                params: svc_params(atype),
+            },
+            AType::HTTPS(priority, target, _) => OwnedAType::HTTPS {
+                priority: *priority,
+                target: name_string(target)?,
+                params: svc_params(atype),
+            },
+            AType::Unknown(atype, data) => OwnedAType::Unknown {
+                atype: *atype,
+                data: data.to_vec(),
+            },
+        })
+    }
+}
+
+/// Copies the SvcParams of an `SVCB` or `HTTPS` record as keys and raw values.
+fn svc_params(atype: &AType<'_>) -> Vec<(u16, Vec<u8>)> {
+    atype.svc_params().raw().map(|(key, value)| (key, value.to_vec())).collect()
+}
+
+/// Writes a name in presentation format, failing where it cannot be written, e.g. on pointer loops.
+pub(crate) fn name_string(name: &Name<'_>) -> Result<String, ()> {
+    let mut string = String::new();
+    write!(string, "{}", name).map_err(|_| ())?;
+    Ok(string)
+}
+
+/// Splits `TXT` record data into its length-prefixed character strings, failing on strings
+/// which overrun the data or are not UTF-8.
+pub(crate) fn txt_strings(txt: &[u8]) -> impl Iterator<Item = Result<&str, ()>> {
+    let mut i = 0;
+    core::iter::from_fn(move || {
+        let len = *txt.get(i)? as usize;
+        let string = txt.get(i + 1..i + 1 + len).ok_or(());
+        i += 1 + len;
+        Some(string.and_then(|string| core::str::from_utf8(string).map_err(|_| ())))
+    })
+}
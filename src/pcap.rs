@@ -0,0 +1,306 @@
+use crate::{Error, Message, MDNS_PORT};
+use arrayvec::ArrayVec;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use core::time::Duration;
+
+/// The most interfaces a pcapng section may describe.
+const MAX_INTERFACES: usize = 16;
+
+/// A UDP payload sent from or to the mDNS port, found in a capture.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CapturedMessage<'a> {
+    /// The source address and port of the datagram.
+    pub source: SocketAddr,
+    /// The destination address and port of the datagram.
+    pub destination: SocketAddr,
+    /// The capture time since the Unix epoch.
+    pub timestamp: Duration,
+    /// The UDP payload.
+    pub payload: &'a [u8],
+}
+
+impl<'a> CapturedMessage<'a> {
+    /// Returns the payload as a message.
+    #[inline(always)]
+    pub fn message(&self) -> Result<Message<'a, [u8], false>, Error> {
+        Message::new(self.payload)
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Format {
+    /// A classic pcap file with the link type and the number of timestamp units per second.
+    Pcap { link_type: u16, units: u64 },
+    /// A pcapng file with the interfaces of the current section.
+    PcapNg,
+}
+
+/// A captured frame with its link type and timestamp.
+type Frame<'a> = (u16, Duration, &'a [u8]);
+
+#[derive(Copy, Clone)]
+struct Interface {
+    link_type: u16,
+    units: u64,
+}
+
+/// An iterator over the mDNS messages in a pcap or pcapng capture.
+///
+/// Frames of Ethernet, Linux cooked (v1 and v2), BSD loopback and raw IP links carrying
+/// IPv4 or IPv6 are decoded, everything else is skipped, as are IP fragments. Iteration
+/// stops after the first malformed block.
+pub struct Capture<'a> {
+    data: &'a [u8],
+    i: usize,
+    little_endian: bool,
+    format: Format,
+    interfaces: ArrayVec<Interface, MAX_INTERFACES>,
+}
+
+impl<'a> Capture<'a> {
+    /// Reads the file header of a capture, telling pcap and pcapng apart by their magic.
+    pub fn new(data: &'a [u8]) -> Result<Self, ()> {
+        let magic: [u8; 4] = data.get(..4).ok_or(())?.try_into().map_err(|_| ())?;
+        let mut capture = Self {
+            data,
+            i: 0,
+            little_endian: false,
+            format: Format::PcapNg,
+            interfaces: ArrayVec::new(),
+        };
+
+        let units = match magic {
+            [0xd4, 0xc3, 0xb2, 0xa1] | [0xa1, 0xb2, 0xc3, 0xd4] => 1_000_000,
+            [0x4d, 0x3c, 0xb2, 0xa1] | [0xa1, 0xb2, 0x3c, 0x4d] => 1_000_000_000,
+            [0x0a, 0x0d, 0x0d, 0x0a] => {
+                capture.section()?;
+                return Ok(capture);
+            }
+            _ => return Err(()),
+        };
+        capture.little_endian = magic[0] != 0xa1;
+        capture.format = Format::Pcap {
+            link_type: capture.u32_at(20)? as u16,
+            units,
+        };
+        capture.i = 24;
+
+        Ok(capture)
+    }
+
+    fn u16_at(&self, i: usize) -> Result<u16, ()> {
+        let bytes = self.data.get(i..i + 2).ok_or(())?.try_into().map_err(|_| ())?;
+        Ok(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    }
+
+    fn u32_at(&self, i: usize) -> Result<u32, ()> {
+        let bytes = self.data.get(i..i + 4).ok_or(())?.try_into().map_err(|_| ())?;
+        Ok(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    /// Starts a pcapng section at the current block, which sets the byte order and forgets
+    /// the interfaces of the previous section.
+    fn section(&mut self) -> Result<(), ()> {
+        self.little_endian = match self.data.get(self.i + 8..self.i + 12).ok_or(())? {
+            [0x4d, 0x3c, 0x2b, 0x1a] => true,
+            [0x1a, 0x2b, 0x3c, 0x4d] => false,
+            _ => return Err(()),
+        };
+        let len = self.u32_at(self.i + 4)? as usize;
+        if len < 28 || !len.is_multiple_of(4) {
+            return Err(());
+        }
+        self.interfaces.clear();
+        self.i += len;
+
+        Ok(())
+    }
+
+    /// Reads an interface description block, looking for the timestamp resolution option.
+    fn interface(&mut self, block: usize, len: usize) -> Result<(), ()> {
+        let mut interface = Interface {
+            link_type: self.u16_at(block + 8)?,
+            units: 1_000_000,
+        };
+
+        let mut i = block + 16;
+        while i + 4 <= block + len - 4 {
+            let code = self.u16_at(i)?;
+            let option_len = self.u16_at(i + 2)? as usize;
+            match code {
+                0 => break,
+                // if_tsresol, a negative power of 10 or of 2 if the high bit is set.
+                9 => {
+                    let resolution = *self.data.get(i + 4).ok_or(())?;
+                    interface.units = match resolution & 0x80 {
+                        0 => 10u64.checked_pow(resolution as u32),
+                        _ => 1u64.checked_shl((resolution & 0x7f) as u32),
+                    }
+                    .ok_or(())?;
+                }
+                _ => {}
+            }
+            i += 4 + option_len.next_multiple_of(4);
+        }
+
+        self.interfaces.try_push(interface).map_err(|_| ())
+    }
+
+    /// Returns the next frame with its link type and timestamp.
+    fn frame(&mut self) -> Option<Result<Frame<'a>, ()>> {
+        while self.i < self.data.len() {
+            let frame = match self.format {
+                Format::Pcap { link_type, units } => self.pcap_record(link_type, units).map(Some),
+                Format::PcapNg => self.pcapng_block(),
+            };
+            match frame {
+                Ok(Some(frame)) => return Some(Ok(frame)),
+                Ok(None) => {}
+                Err(()) => {
+                    self.i = self.data.len();
+                    return Some(Err(()));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn pcap_record(&mut self, link_type: u16, units: u64) -> Result<Frame<'a>, ()> {
+        let seconds = self.u32_at(self.i)? as u64;
+        let fraction = self.u32_at(self.i + 4)? as u64;
+        let len = self.u32_at(self.i + 8)? as usize;
+        let frame = self.data.get(self.i + 16..self.i + 16 + len).ok_or(())?;
+        self.i += 16 + len;
+
+        Ok((link_type, timestamp(seconds * units + fraction, units), frame))
+    }
+
+    fn pcapng_block(&mut self) -> Result<Option<Frame<'a>>, ()> {
+        let block = self.i;
+        let kind = self.u32_at(block)?;
+        if kind == 0x0a0d_0d0a {
+            return self.section().map(|_| None);
+        }
+        let len = self.u32_at(block + 4)? as usize;
+        if len < 12 || !len.is_multiple_of(4) || block + len > self.data.len() {
+            return Err(());
+        }
+        self.i += len;
+
+        match kind {
+            // Interface description block.
+            1 => self.interface(block, len).map(|_| None),
+            // Enhanced packet block.
+            6 => {
+                let interface = *self.interfaces.get(self.u32_at(block + 8)? as usize).ok_or(())?;
+                let time = (self.u32_at(block + 12)? as u64) << 32 | self.u32_at(block + 16)? as u64;
+                let captured = self.u32_at(block + 20)? as usize;
+                let frame = self.data.get(block + 28..block + 28 + captured).ok_or(())?;
+                Ok(Some((interface.link_type, timestamp(time, interface.units), frame)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl<'a> Iterator for Capture<'a> {
+    type Item = Result<CapturedMessage<'a>, ()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (link_type, timestamp, frame) = match self.frame()? {
+                Ok(frame) => frame,
+                Err(()) => return Some(Err(())),
+            };
+            let message = link_payload(link_type, frame, self.little_endian)
+                .and_then(ip_payload)
+                .and_then(|(source, destination, udp)| udp_payload(source, destination, udp));
+            if let Some((source, destination, payload)) = message {
+                return Some(Ok(CapturedMessage { source, destination, timestamp, payload }));
+            }
+        }
+    }
+}
+
+fn timestamp(time: u64, units: u64) -> Duration {
+    let nanos = (time % units) as u128 * 1_000_000_000 / units as u128;
+    Duration::new(time / units, nanos as u32)
+}
+
+fn u16_at(bytes: &[u8], i: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(i..i + 2)?.try_into().ok()?))
+}
+
+/// Returns the IP packet carried by a frame of the given link type.
+fn link_payload(link_type: u16, frame: &[u8], little_endian: bool) -> Option<&[u8]> {
+    match link_type {
+        // BSD loopback, the address family in the byte order of the capturing host.
+        0 => {
+            let family: [u8; 4] = frame.get(..4)?.try_into().ok()?;
+            let family = if little_endian { u32::from_le_bytes(family) } else { u32::from_be_bytes(family) };
+            matches!(family, 2 | 24 | 28 | 30).then(|| &frame[4..])
+        }
+        // Ethernet, skipping VLAN tags.
+        1 => {
+            let mut i = 12;
+            while matches!(u16_at(frame, i)?, 0x8100 | 0x88a8) {
+                i += 4;
+            }
+            matches!(u16_at(frame, i)?, 0x0800 | 0x86dd).then(|| &frame[i + 2..])
+        }
+        // Raw IP, raw IPv4 and raw IPv6.
+        101 | 228 | 229 => Some(frame),
+        // Linux cooked capture.
+        113 => matches!(u16_at(frame, 14)?, 0x0800 | 0x86dd).then(|| frame.get(16..)).flatten(),
+        // Linux cooked capture v2.
+        276 => matches!(u16_at(frame, 0)?, 0x0800 | 0x86dd).then(|| frame.get(20..)).flatten(),
+        _ => None,
+    }
+}
+
+/// Returns the addresses and the UDP datagram of an unfragmented IP packet.
+fn ip_payload(packet: &[u8]) -> Option<(IpAddr, IpAddr, &[u8])> {
+    match packet.first()? >> 4 {
+        4 => {
+            let packet = packet.get(..u16_at(packet, 2)? as usize)?;
+            // More fragments or a fragment offset.
+            if *packet.get(9)? != 17 || u16_at(packet, 6)? & 0x3fff != 0 {
+                return None;
+            }
+            let source: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+            let udp = packet.get((packet[0] & 0x0f) as usize * 4..)?;
+            Some((Ipv4Addr::from(source).into(), Ipv4Addr::from(destination).into(), udp))
+        }
+        6 => {
+            let packet = packet.get(..40 + u16_at(packet, 4)? as usize)?;
+            let source: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+            let mut next = packet[6];
+            let mut i = 40;
+            // Skip the hop-by-hop, routing and destination options extension headers.
+            while matches!(next, 0 | 43 | 60) {
+                next = *packet.get(i)?;
+                i += (*packet.get(i + 1)? as usize + 1) * 8;
+            }
+            (next == 17).then_some((Ipv6Addr::from(source).into(), Ipv6Addr::from(destination).into(), packet.get(i..)?))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the payload of a UDP datagram sent from or to the mDNS port.
+fn udp_payload(source: IpAddr, destination: IpAddr, udp: &[u8]) -> Option<(SocketAddr, SocketAddr, &[u8])> {
+    let source_port = u16_at(udp, 0)?;
+    let destination_port = u16_at(udp, 2)?;
+    if source_port != MDNS_PORT && destination_port != MDNS_PORT {
+        return None;
+    }
+
+    Some((
+        SocketAddr::new(source, source_port),
+        SocketAddr::new(destination, destination_port),
+        udp.get(8..u16_at(udp, 4)? as usize)?,
+    ))
+}
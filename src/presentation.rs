@@ -0,0 +1,573 @@
+use crate::{
+    AType, Answer, Buffer, Header, HeaderKind, HeaderOpcode, Message, QType, Question, RrsigFields, SvcParam,
+    SVC_PARAM_ALPN, SVC_PARAM_ECH, SVC_PARAM_IPV4HINT, SVC_PARAM_IPV6HINT, SVC_PARAM_MANDATORY,
+    SVC_PARAM_NO_DEFAULT_ALPN, SVC_PARAM_PORT,
+};
+use core::fmt::{Display, Formatter, Write};
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+impl<'a, B: Buffer + ?Sized, const WRITE: bool> Message<'a, B, WRITE> {
+    /// Returns an adapter printing the message the way `dig` does, with the records in
+    /// zone-file syntax.
+    #[inline(always)]
+    pub fn display(&self) -> MessageDisplay<'_, 'a, B, WRITE> {
+        MessageDisplay { message: self }
+    }
+}
+
+/// Prints a message the way `dig` does, see [`Message::display`].
+///
+/// The mDNS QU and cache-flush bits are written as comments after the record, an `OPT`
+/// record in the additional section is shown as the EDNS pseudo-section.
+pub struct MessageDisplay<'m, 'a, B: Buffer + ?Sized, const WRITE: bool> {
+    message: &'m Message<'a, B, WRITE>,
+}
+
+impl<B: Buffer + ?Sized, const WRITE: bool> Display for MessageDisplay<'_, '_, B, WRITE> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let header = self.message.header().map_err(|_| core::fmt::Error)?;
+        let body = || self.message.body().map_err(|_| core::fmt::Error);
+
+        let mut opt = None;
+        let mut additionals = body()?.to_answer_section().to_authority_section().to_additional_section();
+        for answer in additionals.additionals() {
+            if let AType::OPT(..) = answer.atype() {
+                opt = Some(answer);
+                break;
+            }
+        }
+
+        write_header(f, header, opt.as_ref())?;
+        if let Some(AType::OPT(udp_payload_size, _, version, options)) = opt.as_ref().map(Answer::atype) {
+            writeln!(f, "\n;; OPT PSEUDOSECTION:")?;
+            write!(f, "; EDNS: version: {}, flags:", version)?;
+            if opt.as_ref().is_some_and(|opt| opt.ttl() & 0x8000 != 0) {
+                write!(f, " do")?;
+            }
+            writeln!(f, "; udp: {}", udp_payload_size)?;
+            let mut i = 0;
+            while let Some(option) = options.get(i..i + 4) {
+                let code = u16::from_be_bytes([option[0], option[1]]);
+                let len = u16::from_be_bytes([option[2], option[3]]) as usize;
+                let Some(data) = options.get(i + 4..i + 4 + len) else {
+                    writeln!(f, "; malformed option")?;
+                    break;
+                };
+                write!(f, "; OPT={}:", code)?;
+                for byte in data {
+                    write!(f, " {:02x}", byte)?;
+                }
+                writeln!(f)?;
+                i += 4 + len;
+            }
+        }
+
+        let mut body = body()?;
+        if header.question_count() > 0 {
+            writeln!(f, "\n;; QUESTION SECTION:")?;
+            let mut count = 0;
+            for question in body.questions() {
+                if !displayable(&question) {
+                    return writeln!(f, ";; malformed name");
+                }
+                writeln!(f, ";{}", question)?;
+                count += 1;
+            }
+            if count != header.question_count() {
+                return writeln!(f, ";; malformed question");
+            }
+        }
+
+        let mut body = body.to_answer_section();
+        if !write_section(f, "ANSWER", header.answer_count(), body.answers())? {
+            return Ok(());
+        }
+        let mut body = body.to_authority_section();
+        if !write_section(f, "AUTHORITY", header.name_server_count(), body.authorities())? {
+            return Ok(());
+        }
+        let mut body = body.to_additional_section();
+        write_section(f, "ADDITIONAL", header.additional_records_count(), body.additionals())?;
+
+        Ok(())
+    }
+}
+
+fn write_header(f: &mut Formatter<'_>, header: &Header, opt: Option<&Answer>) -> core::fmt::Result {
+    let opcode = match header.opcode() {
+        HeaderOpcode::Query => "QUERY",
+        HeaderOpcode::InverseQuery => "IQUERY",
+        HeaderOpcode::Status => "STATUS",
+        HeaderOpcode::Notify => "NOTIFY",
+        HeaderOpcode::Update => "UPDATE",
+        HeaderOpcode::Reserved(_) => "RESERVED",
+    };
+    // The extended RCODE holds the upper 8 bits of the 12 bit response code.
+    let extended_rcode = match opt.map(Answer::atype) {
+        Some(AType::OPT(_, extended_rcode, _, _)) => *extended_rcode as u16,
+        _ => 0,
+    };
+    let status = match extended_rcode << 4 | u8::from(header.response_code()) as u16 {
+        0 => "NOERROR",
+        1 => "FORMERR",
+        2 => "SERVFAIL",
+        3 => "NXDOMAIN",
+        4 => "NOTIMP",
+        5 => "REFUSED",
+        6 => "YXDOMAIN",
+        7 => "YXRRSET",
+        8 => "NXRRSET",
+        9 => "NOTAUTH",
+        10 => "NOTZONE",
+        16 => "BADVERS",
+        17 => "BADKEY",
+        18 => "BADTIME",
+        19 => "BADMODE",
+        20 => "BADNAME",
+        21 => "BADALG",
+        _ => "RESERVED",
+    };
+    writeln!(f, ";; ->>HEADER<<- opcode: {}, status: {}, id: {}", opcode, status, header.id())?;
+
+    write!(f, ";; flags:")?;
+    for (set, flag) in [
+        (header.kind() == HeaderKind::Response, "qr"),
+        (header.authoritative_answer(), "aa"),
+        (header.truncated(), "tc"),
+        (header.recursion_desired(), "rd"),
+        (header.recursion_available(), "ra"),
+    ] {
+        if set {
+            write!(f, " {}", flag)?;
+        }
+    }
+    writeln!(
+        f,
+        "; QUERY: {}, ANSWER: {}, AUTHORITY: {}, ADDITIONAL: {}",
+        header.question_count(),
+        header.answer_count(),
+        header.name_server_count(),
+        header.additional_records_count(),
+    )
+}
+
+/// Writes the records of a section, returning whether all of them could be parsed.
+fn write_section<'a>(
+    f: &mut Formatter<'_>,
+    section: &str,
+    expected: u16,
+    answers: impl Iterator<Item = Answer<'a>>,
+) -> Result<bool, core::fmt::Error> {
+    if expected == 0 {
+        return Ok(true);
+    }
+
+    let mut header = false;
+    let mut count = 0;
+    for answer in answers {
+        count += 1;
+        if let AType::OPT(..) = answer.atype() {
+            continue;
+        }
+        if !header {
+            writeln!(f, "\n;; {} SECTION:", section)?;
+            header = true;
+        }
+        if !displayable(&answer) {
+            writeln!(f, ";; malformed name")?;
+            return Ok(false);
+        }
+        writeln!(f, "{}", answer)?;
+    }
+    if count != expected {
+        writeln!(f, ";; malformed record")?;
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// The mnemonic of a record type, or `TYPEn` for types without one.
+///
+/// According to [RFC 3597 Section 5](https://tools.ietf.org/rfc/rfc3597#section-5).
+struct RecordType(u16);
+
+impl Display for RecordType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match QType::from(self.0) {
+            QType::Reserved if self.0 == 41 => f.write_str("OPT"),
+            QType::Reserved => write!(f, "TYPE{}", self.0),
+            QType::ALL => f.write_str("ANY"),
+            qtype => write!(f, "{}", qtype),
+        }
+    }
+}
+
+impl Display for Question<'_> {
+    /// Writes the question as `name class type`, followed by a `QU` comment if a unicast
+    /// response was requested.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.\t\t{}\t", self.name(), self.qclass())?;
+        match self.qtype() {
+            QType::Reserved => f.write_str("Reserved")?,
+            qtype => write!(f, "{}", RecordType(u16::from(qtype)))?,
+        }
+        if self.unicast_response() {
+            f.write_str("\t; QU")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Answer<'_> {
+    /// Writes the record as `name ttl class type rdata`, followed by a `cache-flush` comment if
+    /// the bit is set.
+    ///
+    /// According to [RFC 1035 Section 5.1](https://tools.ietf.org/rfc/rfc1035#section-5.1).
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.\t{}\t", self.name(), self.ttl())?;
+        match self.atype() {
+            AType::OPT(udp_payload_size, ..) => write!(f, "CLASS{}", udp_payload_size)?,
+            _ => write!(f, "{}", self.aclass())?,
+        }
+        let atype = self.atype().type_id().map_err(|_| core::fmt::Error)?;
+        write!(f, "\t{}\t{}", RecordType(atype), self.atype())?;
+        if self.cache_flush() {
+            f.write_str("\t; cache-flush")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for AType<'_> {
+    /// Writes the record data in presentation format, data of unknown types in the generic
+    /// `\# length hex` form. Malformed data, e.g. `TXT` strings overrunning the data or names
+    /// pointing outside of the message, is written in the generic form as well.
+    ///
+    /// According to [RFC 3597 Section 5](https://tools.ietf.org/rfc/rfc3597#section-5).
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if displayable(&Rdata(self)) {
+            return write!(f, "{}", Rdata(self));
+        }
+
+        let mut fields = [0; 20];
+        let parts: [&[u8]; 3] = match self {
+            AType::NS(name) | AType::CNAME(name) | AType::PTR(name) => [name.wire(), &[], &[]],
+            AType::SOA(mname, rname, serial, refresh, retry, expire, minimum) => {
+                for (i, value) in [serial, refresh, retry, expire, minimum].into_iter().enumerate() {
+                    fields[4 * i..4 * i + 4].copy_from_slice(&value.to_be_bytes());
+                }
+                [mname.wire(), rname.wire(), &fields]
+            }
+            AType::MX(preference, exchange) => {
+                fields[..2].copy_from_slice(&preference.to_be_bytes());
+                [&fields[..2], exchange.wire(), &[]]
+            }
+            AType::TXT(txt) => [txt, &[], &[]],
+            AType::SRV(priority, weight, port, target) => {
+                fields[..2].copy_from_slice(&priority.to_be_bytes());
+                fields[2..4].copy_from_slice(&weight.to_be_bytes());
+                fields[4..6].copy_from_slice(&port.to_be_bytes());
+                [&fields[..6], target.wire(), &[]]
+            }
+            AType::NSEC(next, bitmaps) => [next.wire(), bitmaps, &[]],
+            AType::RRSIG(rrsig, signer, signature) => {
+                fields[..RrsigFields::LEN].copy_from_slice(&rrsig.to_bytes());
+                [&fields[..RrsigFields::LEN], signer.wire(), signature]
+            }
+            AType::SVCB(priority, target, params) | AType::HTTPS(priority, target, params) => {
+                fields[..2].copy_from_slice(&priority.to_be_bytes());
+                [&fields[..2], target.wire(), params]
+            }
+            // The data of other types can always be written.
+            _ => return Err(core::fmt::Error),
+        };
+
+        write_generic(f, &parts)
+    }
+}
+
+/// The record data in presentation format, failing on malformed data.
+struct Rdata<'r, 'a>(&'r AType<'a>);
+
+impl Display for Rdata<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            AType::A(addr) => write!(f, "{}", Ipv4Addr::from(*addr)),
+            AType::NS(name) | AType::CNAME(name) | AType::PTR(name) => write!(f, "{}.", name),
+            AType::SOA(mname, rname, serial, refresh, retry, expire, minimum) => write!(
+                f,
+                "{}. {}. {} {} {} {} {}",
+                mname, rname, serial, refresh, retry, expire, minimum
+            ),
+            AType::MX(preference, exchange) => write!(f, "{} {}.", preference, exchange),
+            AType::TXT(txt) => {
+                let mut i = 0;
+                while let Some(len) = txt.get(i) {
+                    let string = txt.get(i + 1..i + 1 + *len as usize).ok_or(core::fmt::Error)?;
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write_character_string(f, string)?;
+                    i += 1 + *len as usize;
+                }
+
+                Ok(())
+            }
+            AType::AAAA(addr) => write!(f, "{}", Ipv6Addr::from(*addr)),
+            AType::SRV(priority, weight, port, target) => {
+                write!(f, "{} {} {} {}.", priority, weight, port, target)
+            }
+            AType::NSEC(next, _) => {
+                write!(f, "{}.", next)?;
+                for atype in self.0.nsec_types() {
+                    write!(f, " {}", RecordType(atype))?;
+                }
+
+                Ok(())
+            }
+            AType::DS(key_tag, algorithm, digest_type, digest) => {
+                write!(f, "{} {} {} ", key_tag, algorithm, digest_type)?;
+                for byte in *digest {
+                    write!(f, "{:02X}", byte)?;
+                }
+
+                Ok(())
+            }
+            AType::RRSIG(fields, signer, signature) => {
+                write!(f, "{} {} {} {} ", RecordType(fields.type_covered), fields.algorithm, fields.labels, fields.original_ttl)?;
+                write_timestamp(f, fields.expiration)?;
+                f.write_char(' ')?;
+                write_timestamp(f, fields.inception)?;
+                write!(f, " {} {}. ", fields.key_tag, signer)?;
+                write_base64(f, signature)
+            }
+            AType::DNSKEY(flags, protocol, algorithm, public_key) => {
+                write!(f, "{} {} {} ", flags, protocol, algorithm)?;
+                write_base64(f, public_key)
+            }
+            AType::NSEC3(hash_algorithm, flags, iterations, salt, next_hashed, _) => {
+                write!(f, "{} {} {} ", hash_algorithm, flags, iterations)?;
+                write_salt(f, salt)?;
+                f.write_char(' ')?;
+                write_base32hex(f, next_hashed)?;
+                for atype in self.0.nsec_types() {
+                    write!(f, " {}", RecordType(atype))?;
+                }
+
+                Ok(())
+            }
+            AType::NSEC3PARAM(hash_algorithm, flags, iterations, salt) => {
+                write!(f, "{} {} {} ", hash_algorithm, flags, iterations)?;
+                write_salt(f, salt)
+            }
+            AType::SVCB(priority, target, _) | AType::HTTPS(priority, target, _) => {
+                write!(f, "{} {}.", priority, target)?;
+                for param in self.0.svc_params() {
+                    f.write_char(' ')?;
+                    write_svc_param(f, &param)?;
+                }
+
+                Ok(())
+            }
+            AType::OPT(_, _, _, data) | AType::Unknown(_, data) => write_generic(f, &[data]),
+        }
+    }
+}
+
+/// Writes record data in the generic `\# length hex` form.
+///
+/// According to [RFC 3597 Section 5](https://tools.ietf.org/rfc/rfc3597#section-5).
+fn write_generic(f: &mut Formatter<'_>, parts: &[&[u8]]) -> core::fmt::Result {
+    write!(f, "\\# {}", parts.iter().map(|part| part.len()).sum::<usize>())?;
+    for (i, byte) in parts.iter().copied().flatten().enumerate() {
+        if i == 0 {
+            f.write_char(' ')?;
+        }
+        write!(f, "{:02x}", byte)?;
+    }
+
+    Ok(())
+}
+
+/// Whether a value can be written, so malformed data is detected before any of it is written.
+fn displayable(value: &impl Display) -> bool {
+    struct Sink;
+
+    impl Write for Sink {
+        fn write_str(&mut self, _: &str) -> core::fmt::Result {
+            Ok(())
+        }
+    }
+
+    write!(Sink, "{}", value).is_ok()
+}
+
+/// Writes a SvcParam as `key=value`, with the values of unknown keys as quoted strings.
+///
+/// According to [RFC 9460 Section 7](https://tools.ietf.org/rfc/rfc9460#section-7).
+fn write_svc_param(f: &mut Formatter<'_>, param: &SvcParam<'_>) -> core::fmt::Result {
+    write_svc_param_key(f, param.key())?;
+    match param {
+        SvcParam::Mandatory(keys) => {
+            for (i, key) in keys.clone().enumerate() {
+                f.write_char(if i == 0 { '=' } else { ',' })?;
+                write_svc_param_key(f, key)?;
+            }
+        }
+        SvcParam::Alpn(ids) => {
+            for (i, id) in ids.clone().enumerate() {
+                f.write_char(if i == 0 { '=' } else { ',' })?;
+                // Commas within an identifier are escaped twice, once for the list and once for the value.
+                for byte in id {
+                    match byte {
+                        b',' => f.write_str("\\\\,")?,
+                        b'\\' => f.write_str("\\\\\\\\")?,
+                        0x21..=0x7e if *byte != b'"' && *byte != b';' => f.write_char(*byte as char)?,
+                        _ => write!(f, "\\{:03}", byte)?,
+                    }
+                }
+            }
+        }
+        SvcParam::NoDefaultAlpn => {}
+        SvcParam::Port(port) => write!(f, "={}", port)?,
+        SvcParam::Ipv4Hint(addrs) => {
+            for (i, addr) in addrs.clone().enumerate() {
+                write!(f, "{}{}", if i == 0 { '=' } else { ',' }, addr)?;
+            }
+        }
+        SvcParam::Ech(config_list) => {
+            f.write_char('=')?;
+            write_base64(f, config_list)?;
+        }
+        SvcParam::Ipv6Hint(addrs) => {
+            for (i, addr) in addrs.clone().enumerate() {
+                write!(f, "{}{}", if i == 0 { '=' } else { ',' }, addr)?;
+            }
+        }
+        SvcParam::Unknown(_, []) => {}
+        SvcParam::Unknown(_, value) => {
+            f.write_char('=')?;
+            write_character_string(f, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_svc_param_key(f: &mut Formatter<'_>, key: u16) -> core::fmt::Result {
+    match key {
+        SVC_PARAM_MANDATORY => f.write_str("mandatory"),
+        SVC_PARAM_ALPN => f.write_str("alpn"),
+        SVC_PARAM_NO_DEFAULT_ALPN => f.write_str("no-default-alpn"),
+        SVC_PARAM_PORT => f.write_str("port"),
+        SVC_PARAM_IPV4HINT => f.write_str("ipv4hint"),
+        SVC_PARAM_ECH => f.write_str("ech"),
+        SVC_PARAM_IPV6HINT => f.write_str("ipv6hint"),
+        key => write!(f, "key{}", key),
+    }
+}
+
+/// Writes a DNSSEC timestamp as `YYYYMMDDHHmmSS` in UTC.
+///
+/// According to [RFC 4034 Section 3.2](https://tools.ietf.org/rfc/rfc4034#section-3.2).
+fn write_timestamp(f: &mut Formatter<'_>, timestamp: u32) -> core::fmt::Result {
+    let days = timestamp / 86400;
+    let seconds = timestamp % 86400;
+
+    // Converts the days since 1970-01-01 to a date, counting in eras of 400 years from 0000-03-01.
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + (month <= 2) as u32;
+
+    write!(
+        f,
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Writes bytes in base64 with padding.
+///
+/// According to [RFC 4648 Section 4](https://tools.ietf.org/rfc/rfc4648#section-4).
+fn write_base64(f: &mut Formatter<'_>, bytes: &[u8]) -> core::fmt::Result {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    for chunk in bytes.chunks(3) {
+        let mut group = [0; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                f.write_char(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char)?;
+            } else {
+                f.write_char('=')?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes bytes in base32 with the extended hex alphabet and without padding, as used for
+/// hashed owner names.
+///
+/// According to [RFC 5155 Section 3.3](https://tools.ietf.org/rfc/rfc5155#section-3.3).
+fn write_base32hex(f: &mut Formatter<'_>, bytes: &[u8]) -> core::fmt::Result {
+    const ALPHABET: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+    for chunk in bytes.chunks(5) {
+        let mut group = [0; 8];
+        group[3..3 + chunk.len()].copy_from_slice(chunk);
+        let bits = u64::from_be_bytes(group);
+        for i in 0..(chunk.len() * 8).div_ceil(5) {
+            f.write_char(ALPHABET[(bits >> (35 - 5 * i) & 0x1f) as usize] as char)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes an `NSEC3` salt in hex, or `-` if it is empty.
+fn write_salt(f: &mut Formatter<'_>, salt: &[u8]) -> core::fmt::Result {
+    if salt.is_empty() {
+        return f.write_char('-');
+    }
+    for byte in salt {
+        write!(f, "{:02x}", byte)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a quoted character string, escaping quotes, backslashes and unprintable bytes.
+fn write_character_string(f: &mut Formatter<'_>, string: &[u8]) -> core::fmt::Result {
+    f.write_char('"')?;
+    for chunk in string.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '"' | '\\' => {
+                    f.write_char('\\')?;
+                    f.write_char(c)?;
+                }
+                c if c < ' ' || c == '\x7f' => write!(f, "\\{:03}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        for byte in chunk.invalid() {
+            write!(f, "\\{:03}", byte)?;
+        }
+    }
+    f.write_char('"')
+}
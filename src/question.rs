@@ -1,23 +1,29 @@
-use crate::Name;
+use crate::{Error, Name};
 use derive_more::Display;
 
 #[derive(Debug, PartialEq)]
 pub struct Question<'a> {
     name: Name<'a>,
-    qtype: QType,
-    qclass: QClass,
+    qtype: u16,
+    unicast_response: bool,
+    qclass: u16,
 }
 
 impl<'a> Question<'a> {
-    pub fn parse(bytes: &'a [u8], i: &mut usize) -> Result<Self, ()> {
+    pub fn parse(bytes: &'a [u8], i: &mut usize) -> Result<Self, Error> {
         let name = Name::parse(bytes, i)?;
-        let qtype = u16::from_be_bytes([bytes[*i], bytes[*i + 1]]).into();
-        let qclass = u16::from_be_bytes([bytes[*i + 2], bytes[*i + 3]]).into();
+        if bytes.len() < *i + 4 {
+            return Err(Error);
+        }
+        let qtype = u16::from_be_bytes([bytes[*i], bytes[*i + 1]]);
+        let unicast_response = (bytes[*i + 2] & 0b1000_0000) != 0;
+        let qclass = u16::from_be_bytes([bytes[*i + 2] & 0b0111_1111, bytes[*i + 3]]);
         *i += 4;
 
         Ok(Self {
             name,
             qtype,
+            unicast_response,
             qclass,
         })
     }
@@ -29,11 +35,32 @@ impl<'a> Question<'a> {
 
     #[inline(always)]
     pub fn qtype(&self) -> QType {
+        self.qtype.into()
+    }
+
+    /// The raw type, which is kept for types mapped to [`QType::Reserved`].
+    #[inline(always)]
+    pub(crate) fn qtype_value(&self) -> u16 {
         self.qtype
     }
 
+    /// Whether the querier asked for a unicast response (the mDNS QU bit).
+    ///
+    /// According to [RFC 6762 Section 5.4](https://tools.ietf.org/rfc/rfc6762#section-5.4).
+    #[inline(always)]
+    pub fn unicast_response(&self) -> bool {
+        self.unicast_response
+    }
+
     #[inline(always)]
     pub fn qclass(&self) -> QClass {
+        self.qclass.into()
+    }
+
+    /// The raw class without the unicast response bit, which is kept for classes mapped to
+    /// [`QClass::Reserved`].
+    #[inline(always)]
+    pub(crate) fn qclass_value(&self) -> u16 {
         self.qclass
     }
 }
@@ -43,6 +70,7 @@ impl<'a> Question<'a> {
 /// According to [RFC 1035 Section 3.2.2](https://tools.ietf.org/rfc/rfc1035#section-3.2.2)
 /// and [RFC 1035 Section 3.2.3](https://tools.ietf.org/rfc/rfc1035#section-3.2.3).
 #[derive(Copy, Clone, Debug, Display, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u16)]
 pub enum QType {
     A = 1,
@@ -61,6 +89,16 @@ pub enum QType {
     MINFO = 14,
     MX = 15,
     TXT = 16,
+    AAAA = 28,
+    SRV = 33,
+    DS = 43,
+    RRSIG = 46,
+    NSEC = 47,
+    DNSKEY = 48,
+    NSEC3 = 50,
+    NSEC3PARAM = 51,
+    SVCB = 64,
+    HTTPS = 65,
     AXFR = 252,
     MAILB = 253,
     MAILA = 254,
@@ -82,7 +120,7 @@ impl From<u16> for QType {
     #[inline(always)]
     fn from(n: u16) -> Self {
         match n {
-            1..=16 | 252..=255 => unsafe { core::mem::transmute(n) },
+            1..=16 | 28 | 33 | 43 | 46..=48 | 50 | 51 | 64 | 65 | 252..=255 => unsafe { core::mem::transmute(n) },
             _ => QType::Reserved,
         }
     }
@@ -92,6 +130,7 @@ impl From<u16> for QType {
 ///
 /// According to [RFC 1035 Section 3.2.4](https://tools.ietf.org/rfc/rfc1035#section-3.2.4).
 #[derive(Copy, Clone, Debug, Display, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u16)]
 pub enum QClass {
     /// Internet
@@ -0,0 +1,547 @@
+use crate::{
+    known_answer::rdata_eq, AClass, Answer, AnswerBuilder, Buffer, ExtendableBuffer, HeaderKind, HeaderOpcode,
+    KnownAnswers, Message, NamePart, QClass, QType, Question, ResponseMode, LEGACY_UNICAST_TTL,
+};
+use arrayvec::ArrayVec;
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// The most services a [`Responder`] can announce.
+pub const MAX_SERVICES: usize = 15;
+
+/// The most labels of a name the responder owns, including the `local` label.
+const MAX_LABELS: usize = 8;
+
+/// The size of the scratch buffer a record is written to when comparing it with a received
+/// record. Larger records are never considered equal.
+const SCRATCH: usize = 512;
+
+/// The TTL of records containing a host name.
+///
+/// According to [RFC 6762 Section 10](https://tools.ietf.org/rfc/rfc6762#section-10).
+const HOST_TTL: u32 = 120;
+
+/// The TTL of all other records.
+const OTHER_TTL: u32 = 4500;
+
+/// The time between two probes and between the last probe and the first announcement.
+///
+/// According to [RFC 6762 Section 8.1](https://tools.ietf.org/rfc/rfc6762#section-8.1).
+const PROBE_INTERVAL: u64 = 250;
+
+/// The number of probes sent before announcing.
+const PROBES: u8 = 3;
+
+/// The time between the two announcements.
+///
+/// According to [RFC 6762 Section 8.3](https://tools.ietf.org/rfc/rfc6762#section-8.3).
+const ANNOUNCE_INTERVAL: u64 = 1000;
+
+/// The number of unsolicited responses sent after probing.
+const ANNOUNCEMENTS: u8 = 2;
+
+type NameLabels<'s> = ArrayVec<&'s [u8], MAX_LABELS>;
+
+/// The host a [`Responder`] answers address questions for, as `<name>.local`.
+#[derive(Copy, Clone, Debug)]
+pub struct Host<'s> {
+    /// The single label host name.
+    pub name: &'s [u8],
+    pub ipv4: Option<Ipv4Addr>,
+    pub ipv6: Option<Ipv6Addr>,
+}
+
+/// A DNS-SD service a [`Responder`] announces, as `<instance>.<service>.local` on the port of
+/// the host.
+#[derive(Copy, Clone, Debug)]
+pub struct Service<'s> {
+    /// The single label instance name, e.g. `Living Room`.
+    pub instance: &'s [u8],
+    /// The service type, e.g. `_airplay._tcp`.
+    pub service: &'s [u8],
+    pub port: u16,
+    /// The character strings of the `TXT` record, e.g. `model=tv`.
+    pub txt: &'s [&'s [u8]],
+}
+
+/// The state of a [`Responder`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResponderState {
+    /// Checking that no other host uses the names.
+    ///
+    /// According to [RFC 6762 Section 8.1](https://tools.ietf.org/rfc/rfc6762#section-8.1).
+    Probing,
+    /// Announcing the records with unsolicited responses, answering questions already.
+    Announcing,
+    /// Answering questions.
+    Running,
+    /// Another host uses one of the names. The responder stays silent, a new one has to be
+    /// started with different names.
+    ///
+    /// According to [RFC 6762 Section 9](https://tools.ietf.org/rfc/rfc6762#section-9).
+    Conflict,
+}
+
+/// A record the responder owns.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Record {
+    A,
+    Aaaa,
+    /// `_services._dns-sd._udp.local PTR <service>.local`, once per service type.
+    ServiceType(usize),
+    Ptr(usize),
+    Srv(usize),
+    Txt(usize),
+}
+
+impl Record {
+    fn bit(self) -> u64 {
+        match self {
+            Record::A => 1 << 0,
+            Record::Aaaa => 1 << 1,
+            Record::ServiceType(i) => 1 << (2 + 4 * i),
+            Record::Ptr(i) => 1 << (3 + 4 * i),
+            Record::Srv(i) => 1 << (4 + 4 * i),
+            Record::Txt(i) => 1 << (5 + 4 * i),
+        }
+    }
+
+    fn qtype(self) -> QType {
+        match self {
+            Record::A => QType::A,
+            Record::Aaaa => QType::AAAA,
+            Record::ServiceType(_) | Record::Ptr(_) => QType::PTR,
+            Record::Srv(_) => QType::SRV,
+            Record::Txt(_) => QType::TXT,
+        }
+    }
+
+    /// Whether the record is unique to this host, rather than shared with other hosts.
+    fn is_unique(self) -> bool {
+        !matches!(self, Record::ServiceType(_) | Record::Ptr(_))
+    }
+
+    fn ttl(self) -> u32 {
+        match self {
+            Record::A | Record::Aaaa | Record::Srv(_) => HOST_TTL,
+            _ => OTHER_TTL,
+        }
+    }
+}
+
+/// A sans-IO mDNS responder for a host and a static set of services.
+///
+/// The responder answers `A`, `AAAA`, `PTR`, `SRV` and `TXT` questions, after probing for
+/// its names and announcing its records. It does not perform any IO or keep time itself:
+/// received messages are passed to [`Responder::handle`], [`Responder::poll`] is called at
+/// the [`Responder::deadline`] and the messages both return are sent by the caller. Times
+/// are in milliseconds since an arbitrary epoch.
+///
+/// Simultaneous probes of another host for the same names are not tie-broken, and a unicast
+/// response is sent for questions with the QU bit even if the records were not multicast
+/// recently.
+pub struct Responder<'s> {
+    host: Host<'s>,
+    services: &'s [Service<'s>],
+    state: ResponderState,
+    sent: u8,
+    deadline: u64,
+}
+
+impl<'s> Responder<'s> {
+    /// Creates a responder which starts probing after a delay of `random % 250`
+    /// milliseconds, which should be random to avoid colliding with other hosts.
+    pub fn new(host: Host<'s>, services: &'s [Service<'s>], now: u64, random: u32) -> Result<Self, ()> {
+        if services.len() > MAX_SERVICES {
+            return Err(());
+        }
+
+        let responder = Self {
+            host,
+            services,
+            state: ResponderState::Probing,
+            sent: 0,
+            deadline: now + random as u64 % PROBE_INTERVAL,
+        };
+        // Fail early on names which cannot be written.
+        for record in responder.records() {
+            responder.name(record)?;
+        }
+
+        Ok(responder)
+    }
+
+    /// The current state.
+    #[inline(always)]
+    pub fn state(&self) -> ResponderState {
+        self.state
+    }
+
+    /// When [`Responder::poll`] has to be called next, if at all.
+    #[inline(always)]
+    pub fn deadline(&self) -> Option<u64> {
+        match self.state {
+            ResponderState::Probing | ResponderState::Announcing => Some(self.deadline),
+            _ => None,
+        }
+    }
+
+    /// Writes the next probe or announcement if it is due, to be sent to the multicast
+    /// group. The buffer is cleared first.
+    ///
+    /// Fails if the message does not fit the buffer, leaving the deadline as it is.
+    pub fn poll<'b, B: ExtendableBuffer + ?Sized>(
+        &mut self,
+        now: u64,
+        buffer: &'b mut B,
+    ) -> Result<Option<Message<'b, B, true>>, ()> {
+        if self.deadline().is_none_or(|deadline| now < deadline) {
+            return Ok(None);
+        }
+
+        buffer.truncate(0);
+        let message = match self.state {
+            ResponderState::Probing => {
+                let message = self.write_probe(buffer)?;
+                self.sent += 1;
+                self.deadline = now + PROBE_INTERVAL;
+                if self.sent == PROBES {
+                    self.state = ResponderState::Announcing;
+                    self.sent = 0;
+                }
+                message
+            }
+            _ => {
+                let message = self.write_response::<_, [u8]>(buffer, u64::MAX, 0, None)?;
+                self.sent += 1;
+                self.deadline = now + ANNOUNCE_INTERVAL;
+                if self.sent == ANNOUNCEMENTS {
+                    self.state = ResponderState::Running;
+                }
+                message
+            }
+        };
+
+        Ok(Some(message))
+    }
+
+    /// Handles a received message, writing a response to a query if it asks for any of the
+    /// records, or checking the records of a response for conflicts. The buffer is cleared
+    /// first.
+    ///
+    /// The returned mode tells where to send the response: to the multicast group, or back
+    /// to the source for unicast and legacy unicast responses.
+    pub fn handle<'b, B: ExtendableBuffer + ?Sized, Q: Buffer + ?Sized>(
+        &mut self,
+        message: &Message<'_, Q, false>,
+        source: SocketAddr,
+        buffer: &'b mut B,
+    ) -> Result<Option<(Message<'b, B, true>, ResponseMode)>, ()> {
+        let header = message.header()?;
+        if header.opcode() != HeaderOpcode::Query || self.state == ResponderState::Conflict {
+            return Ok(None);
+        }
+        if header.kind() == HeaderKind::Response {
+            self.check_conflicts(message)?;
+            return Ok(None);
+        }
+        if self.state == ResponderState::Probing {
+            return Ok(None);
+        }
+
+        let mode = ResponseMode::classify(message, source)?;
+        let mut answers = 0;
+        let mut body = message.body()?;
+        for question in body.questions() {
+            for record in self.records() {
+                if self.answers(record, &question) {
+                    answers |= record.bit();
+                }
+            }
+        }
+        if body.question_count != 0 {
+            // A malformed question.
+            return Err(());
+        }
+
+        let known_answers = KnownAnswers::new(message.body()?);
+        for record in self.records() {
+            if answers & record.bit() != 0 && self.with_record(record, |record| known_answers.suppresses(record)) {
+                answers &= !record.bit();
+            }
+        }
+        if answers == 0 {
+            return Ok(None);
+        }
+
+        // Send the records a querier will need next along.
+        //
+        // According to [RFC 6763 Section 12](https://tools.ietf.org/rfc/rfc6763#section-12).
+        let mut additionals = 0;
+        for record in self.records().filter(|record| answers & record.bit() != 0) {
+            additionals |= match record {
+                Record::Ptr(i) => Record::Srv(i).bit() | Record::Txt(i).bit() | Record::A.bit() | Record::Aaaa.bit(),
+                Record::Srv(_) => Record::A.bit() | Record::Aaaa.bit(),
+                _ => 0,
+            };
+        }
+
+        buffer.truncate(0);
+        let legacy = (mode == ResponseMode::LegacyUnicast).then_some(message);
+        let response = self.write_response(buffer, answers, additionals & !answers, legacy)?;
+
+        Ok(Some((response, mode)))
+    }
+
+    /// Iterates over the records of the host and services.
+    fn records(&self) -> impl Iterator<Item = Record> + '_ {
+        let host = [self.host.ipv4.map(|_| Record::A), self.host.ipv6.map(|_| Record::Aaaa)];
+        let services = (0..self.services.len()).flat_map(move |i| {
+            let service = self.services[i].service;
+            let first = !self.services[..i].iter().any(|other| other.service.eq_ignore_ascii_case(service));
+            [first.then_some(Record::ServiceType(i)), Some(Record::Ptr(i)), Some(Record::Srv(i)), Some(Record::Txt(i))]
+        });
+
+        host.into_iter().chain(services).flatten()
+    }
+
+    fn answers(&self, record: Record, question: &Question<'_>) -> bool {
+        question.qclass() == QClass::IN
+            && (question.qtype() == record.qtype() || question.qtype() == QType::ALL)
+            && self.name(record).is_ok_and(|name| question.name().eq_labels(name))
+    }
+
+    /// The labels of the name of a record.
+    fn name(&self, record: Record) -> Result<NameLabels<'s>, ()> {
+        match record {
+            Record::A | Record::Aaaa => self.host_name(),
+            Record::ServiceType(_) => labels([&b"_services"[..], b"_dns-sd", b"_udp"]),
+            Record::Ptr(i) => self.service_name(i, false),
+            Record::Srv(i) | Record::Txt(i) => self.service_name(i, true),
+        }
+    }
+
+    fn host_name(&self) -> Result<NameLabels<'s>, ()> {
+        labels([self.host.name])
+    }
+
+    /// The service type name, or the service instance name.
+    fn service_name(&self, i: usize, instance: bool) -> Result<NameLabels<'s>, ()> {
+        let service = &self.services[i];
+        let instance = instance.then_some(service.instance);
+        labels(instance.into_iter().chain(service.service.split(|c| *c == b'.').filter(|label| !label.is_empty())))
+    }
+
+    fn write_record<B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O>(
+        &self,
+        record: Record,
+        builder: AnswerBuilder<'_, B, P, O, F, false, false, false, false, false>,
+        cache_flush: bool,
+        ttl: u32,
+    ) -> Result<O, ()> {
+        let mut name = builder.name();
+        for label in self.name(record)? {
+            name = name.label(label)?;
+        }
+
+        let builder = name.finish()??
+            .atype()
+            .write_with(u16::from(record.qtype()), |buffer| self.write_rdata(record, buffer))??
+            .cache_flush(cache_flush && record.is_unique())?
+            .aclass(AClass::IN)?
+            .ttl(ttl)?;
+
+        Ok(builder.finish())
+    }
+
+    fn write_rdata<B: ExtendableBuffer + ?Sized>(&self, record: Record, buffer: &mut B) -> Result<(), ()> {
+        let write_name = |buffer: &mut B, name: NameLabels<'_>| {
+            for label in name {
+                label.to_bytes(buffer)?;
+            }
+            buffer.extend_from_slice(&[0])
+        };
+
+        match record {
+            Record::A => buffer.extend_from_slice(&self.host.ipv4.ok_or(())?.octets()),
+            Record::Aaaa => buffer.extend_from_slice(&self.host.ipv6.ok_or(())?.octets()),
+            Record::ServiceType(i) => write_name(buffer, self.service_name(i, false)?),
+            Record::Ptr(i) => write_name(buffer, self.service_name(i, true)?),
+            Record::Srv(i) => {
+                // Priority and weight.
+                buffer.extend_from_slice(&[0, 0, 0, 0])?;
+                buffer.extend_from_slice(&self.services[i].port.to_be_bytes())?;
+                write_name(buffer, self.host_name()?)
+            }
+            Record::Txt(i) => {
+                // A TXT record holds at least one, possibly empty, string.
+                //
+                // According to [RFC 6763 Section 6.1](https://tools.ietf.org/rfc/rfc6763#section-6.1).
+                if self.services[i].txt.is_empty() {
+                    return buffer.extend_from_slice(&[0]);
+                }
+                for string in self.services[i].txt {
+                    buffer.extend_from_slice(&[u8::try_from(string.len()).map_err(|_| ())?])?;
+                    buffer.extend_from_slice(string)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes a record on its own into a scratch buffer and passes it parsed to `f`, which
+    /// is not called if the record does not fit.
+    fn with_record(&self, record: Record, f: impl FnOnce(&Answer<'_>) -> bool) -> bool {
+        let mut scratch = ArrayVec::<u8, SCRATCH>::new();
+        let builder = AnswerBuilder::new(&mut scratch, (), |_| ());
+        if self.write_record(record, builder, false, record.ttl()).is_err() {
+            return false;
+        }
+
+        Answer::parse(&scratch, &mut 0).is_ok_and(|answer| f(&answer))
+    }
+
+    /// Writes a probe, asking for any record of the unique names with the proposed records
+    /// in the authority section. The first probe asks for unicast responses.
+    ///
+    /// According to [RFC 6762 Section 8.2](https://tools.ietf.org/rfc/rfc6762#section-8.2).
+    fn write_probe<'b, B: ExtendableBuffer + ?Sized>(&self, buffer: &'b mut B) -> Result<Message<'b, B, true>, ()> {
+        let mut message = Message::new_mut(buffer)?;
+        let mut body = message.body_mut()?;
+        let mut previous: Option<NameLabels<'s>> = None;
+        for record in self.records().filter(|record| record.is_unique()) {
+            let name = self.name(record)?;
+            if previous.as_ref() == Some(&name) {
+                continue;
+            }
+
+            let mut builder = body.append_question().name();
+            for label in &name {
+                builder = builder.label(*label)?;
+            }
+            body = builder.finish()?
+                .unicast_response(self.sent == 0)?
+                .qtype(QType::ALL)?
+                .qclass(QClass::IN)?
+                .finish()?;
+            previous = Some(name);
+        }
+
+        let mut body = body.to_answer_section().to_authority_section();
+        for record in self.records().filter(|record| record.is_unique()) {
+            body = self.write_record(record, body.append_authority(), false, record.ttl())??;
+        }
+
+        Ok(message)
+    }
+
+    /// Writes a response with the given answer and additional records, an announcement if
+    /// all records are answers. A legacy unicast response echoes the query and caps the
+    /// TTLs.
+    ///
+    /// According to [RFC 6762 Section 6](https://tools.ietf.org/rfc/rfc6762#section-6).
+    fn write_response<'b, B: ExtendableBuffer + ?Sized, Q: Buffer + ?Sized>(
+        &self,
+        buffer: &'b mut B,
+        answers: u64,
+        additionals: u64,
+        legacy: Option<&Message<'_, Q, false>>,
+    ) -> Result<Message<'b, B, true>, ()> {
+        let mut message = Message::new_mut(buffer)?;
+        let header = message.header_mut()?;
+        header.set_kind(HeaderKind::Response);
+        header.set_authoritative_answer(true);
+        if let Some(query) = legacy {
+            header.set_id(query.header()?.id());
+        }
+
+        let mut body = message.body_mut()?;
+        if let Some(query) = legacy {
+            for question in query.body()?.questions() {
+                // Types and classes without a variant are echoed as they are.
+                body = body.append_question()
+                    .name()
+                    .label(question.name())?
+                    .finish()?
+                    .qtype_value(question.qtype_value())?
+                    .qclass_value(question.qclass_value())?
+                    .finish()?;
+            }
+        }
+
+        let ttl = |record: Record| match legacy {
+            Some(_) => record.ttl().min(LEGACY_UNICAST_TTL),
+            None => record.ttl(),
+        };
+        let mut body = body.to_answer_section();
+        for record in self.records().filter(|record| answers & record.bit() != 0) {
+            body = self.write_record(record, body.append_answer(), legacy.is_none(), ttl(record))??;
+        }
+        let mut body = body.to_authority_section().to_additional_section();
+        for record in self.records().filter(|record| additionals & record.bit() != 0) {
+            body = self.write_record(record, body.append_additional(), legacy.is_none(), ttl(record))??;
+        }
+
+        Ok(message)
+    }
+
+    /// Looks for records of other hosts with the names of the unique records. While probing
+    /// any such record is a conflict, afterwards only one with the same type and different
+    /// data, as the responder receives its own multicasts. Fails on malformed records, before
+    /// changing the state.
+    fn check_conflicts<Q: Buffer + ?Sized>(&mut self, response: &Message<'_, Q, false>) -> Result<(), ()> {
+        let mut conflict = false;
+        let mut body = response.body()?.to_answer_section();
+        body.answers().for_each(|record| conflict |= self.conflicts(&record));
+        let mut body = body.to_authority_section();
+        body.authorities().for_each(|record| conflict |= self.conflicts(&record));
+        let mut body = body.to_additional_section();
+        body.additionals().for_each(|record| conflict |= self.conflicts(&record));
+        if body.question_count != 0 || body.answer_count != 0 || body.authority_count != 0 || body.additional_count != 0 {
+            return Err(());
+        }
+
+        if conflict {
+            self.state = ResponderState::Conflict;
+        }
+
+        Ok(())
+    }
+
+    fn conflicts(&self, other: &Answer<'_>) -> bool {
+        if other.aclass() != AClass::IN {
+            return false;
+        }
+
+        let mut same_name = false;
+        let mut same_type = false;
+        for record in self.records().filter(|record| record.is_unique()) {
+            if !self.name(record).is_ok_and(|name| other.name().eq_labels(name)) {
+                continue;
+            }
+            same_name = true;
+            if other.atype().type_id() == Ok(u16::from(record.qtype())) {
+                same_type = true;
+                if self.with_record(record, |record| rdata_eq(record.atype(), other.atype())) {
+                    return false;
+                }
+            }
+        }
+
+        match self.state {
+            ResponderState::Probing => same_name,
+            _ => same_type,
+        }
+    }
+}
+
+fn labels<'s>(name: impl IntoIterator<Item = &'s [u8]>) -> Result<NameLabels<'s>, ()> {
+    let mut labels = NameLabels::new();
+    for label in name.into_iter().chain([&b"local"[..]]) {
+        if label.is_empty() {
+            return Err(());
+        }
+        labels.try_push(label).map_err(|_| ())?;
+    }
+
+    Ok(labels)
+}
@@ -0,0 +1,116 @@
+use crate::{AClass, Answer, Buffer, ExtendableBuffer, HeaderKind, HeaderOpcode, Message};
+use core::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// The UDP port mDNS queriers and responders communicate on.
+pub const MDNS_PORT: u16 = 5353;
+
+/// The IPv4 mDNS multicast group.
+pub const MDNS_IPV4: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+
+/// The IPv6 mDNS multicast group.
+pub const MDNS_IPV6: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb);
+
+/// The maximum TTL of records in a legacy unicast response.
+///
+/// According to [RFC 6762 Section 6.7](https://tools.ietf.org/rfc/rfc6762#section-6.7).
+pub const LEGACY_UNICAST_TTL: u32 = 10;
+
+/// How a responder has to answer a query.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ResponseMode {
+    /// Send the response to the multicast group.
+    Multicast,
+    /// All questions have the QU bit set, the response may be sent directly to the querier.
+    ///
+    /// According to [RFC 6762 Section 5.4](https://tools.ietf.org/rfc/rfc6762#section-5.4),
+    /// a responder should still multicast records it has not multicast recently.
+    Unicast,
+    /// The query was sent by a simple resolver from a port other than 5353, answer it
+    /// with [`legacy_unicast_response`].
+    ///
+    /// According to [RFC 6762 Section 6.7](https://tools.ietf.org/rfc/rfc6762#section-6.7).
+    LegacyUnicast,
+}
+
+impl ResponseMode {
+    /// Decides how to answer a query received from the given source address.
+    pub fn classify<B: Buffer + ?Sized>(query: &Message<'_, B, false>, source: SocketAddr) -> Result<Self, ()> {
+        if source.port() != MDNS_PORT {
+            return Ok(ResponseMode::LegacyUnicast);
+        }
+
+        let header = query.header()?;
+        let mut body = query.body()?;
+        let mut questions = 0;
+        for question in body.questions() {
+            if !question.unicast_response() {
+                return Ok(ResponseMode::Multicast);
+            }
+            questions += 1;
+        }
+        if questions != header.question_count() {
+            // A malformed question, the QU bits cannot be trusted.
+            return Err(());
+        }
+
+        Ok(if questions > 0 { ResponseMode::Unicast } else { ResponseMode::Multicast })
+    }
+}
+
+/// Builds a legacy unicast response to a query from the given records.
+///
+/// The response echoes the query ID and questions, caps the TTLs at [`LEGACY_UNICAST_TTL`]
+/// and never sets the cache-flush bit.
+///
+/// According to [RFC 6762 Section 6.7](https://tools.ietf.org/rfc/rfc6762#section-6.7).
+pub fn legacy_unicast_response<'b, 'r, B, Q, I>(
+    query: &Message<'_, Q, false>,
+    records: I,
+    buffer: &'b mut B,
+) -> Result<Message<'b, B, true>, ()>
+where
+    B: ExtendableBuffer + ?Sized,
+    Q: Buffer + ?Sized,
+    I: IntoIterator<Item = Answer<'r>>,
+{
+    let query_header = query.header()?;
+    let mut message = Message::new_mut(buffer)?;
+    let header = message.header_mut()?;
+    header.set_id(query_header.id());
+    header.set_kind(HeaderKind::Response);
+    header.set_opcode(HeaderOpcode::Query);
+    header.set_authoritative_answer(true);
+
+    let mut body = message.body_mut()?;
+    let mut query_body = query.body()?;
+    for question in query_body.questions() {
+        // Types and classes without a variant are echoed as they are.
+        body = body.append_question()
+            .name()
+            .label(question.name())?
+            .finish()?
+            .qtype_value(question.qtype_value())?
+            .qclass_value(question.qclass_value())?
+            .finish()?;
+    }
+
+    let mut body = body.to_answer_section();
+    for record in records {
+        if record.aclass() == AClass::Unknown {
+            return Err(());
+        }
+
+        body = body.append_answer()
+            .name()
+            .label(record.name())?
+            .finish()??
+            .atype()
+            .rdata(record.atype())??
+            .cache_flush(false)?
+            .aclass(record.aclass())?
+            .ttl(record.ttl().min(LEGACY_UNICAST_TTL))?
+            .finish()?;
+    }
+
+    Ok(message)
+}
@@ -0,0 +1,124 @@
+use crate::{ExtendableBuffer, Name, NameBuilder};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+impl<'a, B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O> NameBuilder<'a, B, P, O, F> {
+    /// Writes the reverse-mapping name of an address, `w.z.y.x.in-addr.arpa` for IPv4
+    /// and the 32 nibble form below `ip6.arpa` for IPv6.
+    ///
+    /// According to [RFC 1035 Section 3.5](https://tools.ietf.org/rfc/rfc1035#section-3.5)
+    /// and [RFC 3596 Section 2.5](https://tools.ietf.org/rfc/rfc3596#section-2.5).
+    pub fn reverse(mut self, addr: IpAddr) -> Result<Self, ()> {
+        match addr {
+            IpAddr::V4(addr) => {
+                for octet in addr.octets().iter().rev() {
+                    let mut digits = [0; 3];
+                    let mut start = digits.len();
+                    let mut octet = *octet;
+                    loop {
+                        start -= 1;
+                        digits[start] = b'0' + octet % 10;
+                        octet /= 10;
+                        if octet == 0 {
+                            break;
+                        }
+                    }
+                    self = self.label(&digits[start..])?;
+                }
+                self.label(b"in-addr")?.label(b"arpa")
+            }
+            IpAddr::V6(addr) => {
+                for octet in addr.octets().iter().rev() {
+                    self = self
+                        .label([HEX_DIGITS[(octet & 0x0f) as usize]])?
+                        .label([HEX_DIGITS[(octet >> 4) as usize]])?;
+                }
+                self.label(b"ip6")?.label(b"arpa")
+            }
+        }
+    }
+}
+
+impl Name<'_> {
+    /// Returns the address of a reverse-mapping name below `in-addr.arpa` or `ip6.arpa`.
+    pub fn reverse_address(&self) -> Result<IpAddr, ()> {
+        let mut labels = [&[][..]; 34];
+        let count = collect_labels(self, &mut labels)?;
+
+        match &labels[..count] {
+            [d, c, b, a, in_addr, arpa]
+                if in_addr.eq_ignore_ascii_case(b"in-addr") && arpa.eq_ignore_ascii_case(b"arpa") =>
+            {
+                Ok(IpAddr::V4(Ipv4Addr::new(
+                    decimal(a)?,
+                    decimal(b)?,
+                    decimal(c)?,
+                    decimal(d)?,
+                )))
+            }
+            [nibbles @ .., ip6, arpa]
+                if nibbles.len() == 32 && ip6.eq_ignore_ascii_case(b"ip6") && arpa.eq_ignore_ascii_case(b"arpa") =>
+            {
+                let mut octets = [0; 16];
+                for (i, pair) in nibbles.chunks_exact(2).enumerate() {
+                    octets[15 - i] = hex(pair[1])? << 4 | hex(pair[0])?;
+                }
+                Ok(IpAddr::V6(Ipv6Addr::from(octets)))
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// Whether the name is below one of the link-local reverse-mapping zones, which are
+    /// resolved through mDNS: `254.169.in-addr.arpa` and `8.e.f.ip6.arpa` to `b.e.f.ip6.arpa`.
+    ///
+    /// According to [RFC 6762 Section 4](https://tools.ietf.org/rfc/rfc6762#section-4).
+    pub fn is_link_local_reverse(&self) -> bool {
+        let mut labels = [&[][..]; 34];
+        let count = match collect_labels(self, &mut labels) {
+            Ok(count) => count,
+            Err(()) => return false,
+        };
+
+        match &labels[..count] {
+            [.., b"254", b"169", in_addr, arpa] => {
+                in_addr.eq_ignore_ascii_case(b"in-addr") && arpa.eq_ignore_ascii_case(b"arpa")
+            }
+            [.., nibble, e, f, ip6, arpa] => {
+                matches!(hex(nibble), Ok(0x8..=0xb))
+                    && e.eq_ignore_ascii_case(b"e")
+                    && f.eq_ignore_ascii_case(b"f")
+                    && ip6.eq_ignore_ascii_case(b"ip6")
+                    && arpa.eq_ignore_ascii_case(b"arpa")
+            }
+            _ => false,
+        }
+    }
+}
+
+fn collect_labels<'a>(name: &Name<'a>, labels: &mut [&'a [u8]]) -> Result<usize, ()> {
+    let mut count = 0;
+    for label in name.labels() {
+        *labels.get_mut(count).ok_or(())? = label;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+fn decimal(label: &[u8]) -> Result<u8, ()> {
+    if label.is_empty() || label.len() > 3 || !label.iter().all(u8::is_ascii_digit) {
+        return Err(());
+    }
+
+    let value = label.iter().fold(0u16, |value, digit| value * 10 + (digit - b'0') as u16);
+    u8::try_from(value).map_err(|_| ())
+}
+
+fn hex(label: &[u8]) -> Result<u8, ()> {
+    match label {
+        [digit] => (*digit as char).to_digit(16).map(|digit| digit as u8).ok_or(()),
+        _ => Err(()),
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{
+    owned::{name_string, txt_strings}, AClass, AType, Answer, Buffer, Header, HeaderKind, HeaderOpcode, HeaderResponseCode,
+    Message, Name, QClass, QType, Question,
+};
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::net::{Ipv4Addr, Ipv6Addr};
+use serde::ser::{Error, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// The borrowed types serialize to the same shape as the owned types, so a serialized
+// message can be deserialized into an `OwnedMessage`.
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Header")]
+struct HeaderFields {
+    id: u16,
+    kind: HeaderKind,
+    opcode: HeaderOpcode,
+    authoritative_answer: bool,
+    truncated: bool,
+    recursion_desired: bool,
+    recursion_available: bool,
+    response_code: HeaderResponseCode,
+    question_count: u16,
+    answer_count: u16,
+    name_server_count: u16,
+    additional_records_count: u16,
+}
+
+impl Serialize for Header {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HeaderFields {
+            id: self.id(),
+            kind: self.kind(),
+            opcode: self.opcode(),
+            authoritative_answer: self.authoritative_answer(),
+            truncated: self.truncated(),
+            recursion_desired: self.recursion_desired(),
+            recursion_available: self.recursion_available(),
+            response_code: self.response_code(),
+            question_count: self.question_count(),
+            answer_count: self.answer_count(),
+            name_server_count: self.name_server_count(),
+            additional_records_count: self.additional_records_count(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Header {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = HeaderFields::deserialize(deserializer)?;
+        let mut header = Header::new(
+            fields.id,
+            fields.kind,
+            fields.opcode,
+            fields.authoritative_answer,
+            fields.truncated,
+            fields.recursion_desired,
+            fields.recursion_available,
+            fields.response_code,
+        );
+        header.set_question_count(fields.question_count);
+        header.set_answer_count(fields.answer_count);
+        header.set_name_server_count(fields.name_server_count);
+        header.set_additional_records_count(fields.additional_records_count);
+
+        Ok(header)
+    }
+}
+
+/// Names are serialized in presentation format.
+impl Serialize for Name<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&name_string(self).map_err(|_| S::Error::custom("malformed name"))?)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Question")]
+struct QuestionFields<'a> {
+    name: &'a Name<'a>,
+    qtype: QType,
+    unicast_response: bool,
+    qclass: QClass,
+}
+
+impl Serialize for Question<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        QuestionFields {
+            name: self.name(),
+            qtype: self.qtype(),
+            unicast_response: self.unicast_response(),
+            qclass: self.qclass(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Answer")]
+struct AnswerFields<'a> {
+    name: &'a Name<'a>,
+    atype: &'a AType<'a>,
+    cache_flush: bool,
+    aclass: AClass,
+    ttl: u32,
+}
+
+impl Serialize for Answer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AnswerFields {
+            name: self.name(),
+            atype: self.atype(),
+            cache_flush: self.cache_flush(),
+            aclass: self.aclass(),
+            ttl: self.ttl(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "AType")]
+enum ATypeFields<'a> {
+    A(Ipv4Addr),
+    NS(&'a Name<'a>),
+    CNAME(&'a Name<'a>),
+    SOA {
+        mname: &'a Name<'a>,
+        rname: &'a Name<'a>,
+        serial: u32,
+        refresh: u32,
+        retry: u32,
+        expire: u32,
+        minimum: u32,
+    },
+    PTR(&'a Name<'a>),
+    MX {
+        preference: u16,
+        exchange: &'a Name<'a>,
+    },
+    TXT(Vec<&'a str>),
+    AAAA(Ipv6Addr),
+    SRV {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: &'a Name<'a>,
+    },
+    OPT {
+        udp_payload_size: u16,
+        extended_rcode: u8,
+        version: u8,
+        data: &'a [u8],
+    },
+    NSEC {
+        next: &'a Name<'a>,
+        types: Vec<u16>,
+    },
+    DS {
+        key_tag: u16,
+        algorithm: u8,
+        digest_type: u8,
+        digest: &'a [u8],
+    },
+    RRSIG {
+        type_covered: u16,
+        algorithm: u8,
+        labels: u8,
+        original_ttl: u32,
+        expiration: u32,
+        inception: u32,
+        key_tag: u16,
+        signer: &'a Name<'a>,
+        signature: &'a [u8],
+    },
+    DNSKEY {
+        flags: u16,
+        protocol: u8,
+        algorithm: u8,
+        public_key: &'a [u8],
+    },
+    NSEC3 {
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: &'a [u8],
+        next_hashed: &'a [u8],
+        types: Vec<u16>,
+    },
+    NSEC3PARAM {
+        hash_algorithm: u8,
+        flags: u8,
+        iterations: u16,
+        salt: &'a [u8],
+    },
+    SVCB {
+        priority: u16,
+        target: &'a Name<'a>,
+        params: Vec<(u16, &'a [u8])>,
+    },
+    HTTPS {
+        priority: u16,
+        target: &'a Name<'a>,
+        params: Vec<(u16, &'a [u8])>,
+    },
+    Unknown {
+        atype: u16,
+        data: &'a [u8],
+    },
+}
+
+/// Addresses are serialized as strings by human-readable formats, the character strings of
+/// `TXT` records as text, failing on malformed or non-UTF-8 strings.
+impl Serialize for AType<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            AType::A(addr) => ATypeFields::A(Ipv4Addr::from(*addr)),
+            AType::NS(name) => ATypeFields::NS(name),
+            AType::CNAME(name) => ATypeFields::CNAME(name),
+            AType::SOA(mname, rname, serial, refresh, retry, expire, minimum) => ATypeFields::SOA {
+                mname,
+                rname,
+                serial: *serial,
+                refresh: *refresh,
+                retry: *retry,
+                expire: *expire,
+                minimum: *minimum,
+            },
+            AType::PTR(name) => ATypeFields::PTR(name),
+            AType::MX(preference, exchange) => ATypeFields::MX {
+                preference: *preference,
+                exchange,
+            },
+            AType::TXT(txt) => ATypeFields::TXT(
+                txt_strings(txt).collect::<Result<_, _>>().map_err(|_| S::Error::custom("malformed TXT record"))?,
+            ),
+            AType::AAAA(addr) => ATypeFields::AAAA(Ipv6Addr::from(*addr)),
+            AType::SRV(priority, weight, port, target) => ATypeFields::SRV {
+                priority: *priority,
+                weight: *weight,
+                port: *port,
+                target,
+            },
+            AType::OPT(udp_payload_size, extended_rcode, version, data) => ATypeFields::OPT {
+                udp_payload_size: *udp_payload_size,
+                extended_rcode: *extended_rcode,
+                version: *version,
+                data,
+            },
+            AType::NSEC(next, _) => ATypeFields::NSEC {
+                next,
+                types: self.nsec_types().collect(),
+            },
+            AType::DS(key_tag, algorithm, digest_type, digest) => ATypeFields::DS {
+                key_tag: *key_tag,
+                algorithm: *algorithm,
+                digest_type: *digest_type,
+                digest,
+            },
+            AType::RRSIG(fields, signer, signature) => ATypeFields::RRSIG {
+                type_covered: fields.type_covered,
+                algorithm: fields.algorithm,
+                labels: fields.labels,
+                original_ttl: fields.original_ttl,
+                expiration: fields.expiration,
+                inception: fields.inception,
+                key_tag: fields.key_tag,
+                signer,
+                signature,
+            },
+            AType::DNSKEY(flags, protocol, algorithm, public_key) => ATypeFields::DNSKEY {
+                flags: *flags,
+                protocol: *protocol,
+                algorithm: *algorithm,
+                public_key,
+            },
+            AType::NSEC3(hash_algorithm, flags, iterations, salt, next_hashed, _) => ATypeFields::NSEC3 {
+                hash_algorithm: *hash_algorithm,
+                flags: *flags,
+                iterations: *iterations,
+                salt,
+                next_hashed,
+                types: self.nsec_types().collect(),
+            },
+            AType::NSEC3PARAM(hash_algorithm, flags, iterations, salt) => ATypeFields::NSEC3PARAM {
+                hash_algorithm: *hash_algorithm,
+                flags: *flags,
+                iterations: *iterations,
+                salt,
+            },
+            AType::SVCB(priority, target, _) => ATypeFields::SVCB {
+                priority: *priority,
+                target,
+                params: self.svc_params().raw().collect(),
+            },
+            AType::HTTPS(priority, target, _) => ATypeFields::HTTPS {
+                priority: *priority,
+                target,
+                params: self.svc_params().raw().collect(),
+            },
+            AType::Unknown(atype, data) => ATypeFields::Unknown { atype: *atype, data },
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Serializes the records of a section while walking over it.
+struct Records<I>(Cell<Option<I>>);
+
+impl<I: Iterator<Item = T>, T: Serialize> Serialize for Records<I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.take().into_iter().flatten())
+    }
+}
+
+/// The whole message, serialized in the shape of an `OwnedMessage`.
+impl<B: Buffer + ?Sized, const WRITE: bool> Serialize for Message<'_, B, WRITE> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut message = serializer.serialize_struct("Message", 5)?;
+        message.serialize_field("header", self.header().map_err(|_| S::Error::custom("malformed header"))?)?;
+        let mut body = self.body().map_err(|_| S::Error::custom("malformed header"))?;
+        message.serialize_field("questions", &Records(Cell::new(Some(body.questions()))))?;
+        if body.question_count != 0 {
+            return Err(S::Error::custom("malformed question"));
+        }
+        let mut body = body.to_answer_section();
+        message.serialize_field("answers", &Records(Cell::new(Some(body.answers()))))?;
+        let mut body = body.to_authority_section();
+        message.serialize_field("authorities", &Records(Cell::new(Some(body.authorities()))))?;
+        let mut body = body.to_additional_section();
+        message.serialize_field("additionals", &Records(Cell::new(Some(body.additionals()))))?;
+        if body.answer_count != 0 || body.authority_count != 0 || body.additional_count != 0 {
+            return Err(S::Error::custom("malformed record"));
+        }
+        message.end()
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{Error, ExtendableBuffer, Name, NamePart};
+
+/// A DNS-SD service name, split into `<Instance>.<Service>.<Domain>`.
+///
+/// According to [RFC 6763 Section 4.1](https://tools.ietf.org/rfc/rfc6763#section-4.1)
+/// and [RFC 6763 Section 7.1](https://tools.ietf.org/rfc/rfc6763#section-7.1).
+/// The instance is a single label which may contain any UTF-8 text, including dots.
+#[derive(Debug, Clone)]
+pub struct ServiceName<'a> {
+    instance: Option<&'a [u8]>,
+    subtype: Option<&'a [u8]>,
+    service: &'a [u8],
+    protocol: &'a [u8],
+    service_type: Name<'a>,
+    domain: Name<'a>,
+}
+
+impl<'a> ServiceName<'a> {
+    /// Splits a name like `Bob's Printer._ipp._tcp.local`, `_ipp._tcp.local` or
+    /// `_universal._sub._ipp._tcp.local` into its parts.
+    pub fn parse(name: &Name<'a>) -> Result<Self, Error> {
+        // At most instance or subtype + `_sub` may come before the service label.
+        let mut prefix: [&'a [u8]; 3] = [&[]; 3];
+        let mut starts: [Option<Name<'a>>; 3] = [None, None, None];
+        let mut count = 0;
+        let mut labels = name.labels();
+
+        loop {
+            let start = labels.remainder();
+            let Some(label) = labels.next() else {
+                break;
+            };
+            let is_protocol =
+                label.eq_ignore_ascii_case(b"_tcp") || label.eq_ignore_ascii_case(b"_udp");
+            if is_protocol && count > 0 && prefix[count - 1].first() == Some(&b'_') {
+                let service = prefix[count - 1];
+                let (instance, subtype, first) = match &prefix[..count - 1] {
+                    [] => (None, None, count - 1),
+                    [instance] => (Some(*instance), None, count - 1),
+                    [subtype, sub] if sub.eq_ignore_ascii_case(b"_sub") => (None, Some(*subtype), 0),
+                    _ => return Err(Error),
+                };
+
+                return Ok(Self {
+                    instance,
+                    subtype,
+                    service,
+                    protocol: label,
+                    service_type: starts[first].take().ok_or(Error)?,
+                    domain: labels.remainder(),
+                });
+            }
+
+            if count == prefix.len() {
+                return Err(Error);
+            }
+            prefix[count] = label;
+            starts[count] = Some(start);
+            count += 1;
+        }
+
+        Err(Error)
+    }
+
+    /// The instance label, e.g. `Bob's Printer`.
+    #[inline(always)]
+    pub fn instance(&self) -> Option<&'a [u8]> {
+        self.instance
+    }
+
+    /// The subtype label of a `<Subtype>._sub.<Service>` name, e.g. `_universal`.
+    #[inline(always)]
+    pub fn subtype(&self) -> Option<&'a [u8]> {
+        self.subtype
+    }
+
+    /// The service label, e.g. `_ipp`. Together with the protocol it forms the service, see
+    /// [`ServiceName::service_type`] for the whole name.
+    #[inline(always)]
+    pub fn service(&self) -> &'a [u8] {
+        self.service
+    }
+
+    /// The protocol label, either `_tcp` or `_udp`.
+    #[inline(always)]
+    pub fn protocol(&self) -> &'a [u8] {
+        self.protocol
+    }
+
+    /// The service type name, i.e. the service with its subtype and the domain, e.g.
+    /// `_ipp._tcp.local` or `_universal._sub._ipp._tcp.local`. This is the name browsed for
+    /// with `PTR` queries.
+    #[inline(always)]
+    pub fn service_type(&self) -> &Name<'a> {
+        &self.service_type
+    }
+
+    /// The domain the service is registered in, e.g. `local`.
+    #[inline(always)]
+    pub fn domain(&self) -> &Name<'a> {
+        &self.domain
+    }
+}
+
+/// Writes a DNS-SD service name as a sequence of labels.
+///
+/// The instance is written as a single label, so it must not be escaped. The service
+/// (e.g. `_ipp._tcp` or `_universal._sub._ipp._tcp`) and the domain are split at dots.
+pub struct ServiceNamePart<'s> {
+    instance: Option<&'s [u8]>,
+    service: &'s [u8],
+    domain: &'s [u8],
+}
+
+impl<'s> ServiceNamePart<'s> {
+    /// A service instance name, e.g. `Bob's Printer._ipp._tcp.local`.
+    #[inline(always)]
+    pub fn new(instance: &'s [u8], service: &'s [u8], domain: &'s [u8]) -> Self {
+        Self {
+            instance: Some(instance),
+            service,
+            domain,
+        }
+    }
+
+    /// A service type name without an instance, e.g. `_ipp._tcp.local`.
+    #[inline(always)]
+    pub fn service_type(service: &'s [u8], domain: &'s [u8]) -> Self {
+        Self {
+            instance: None,
+            service,
+            domain,
+        }
+    }
+}
+
+impl NamePart for ServiceNamePart<'_> {
+    #[inline(always)]
+    fn to_bytes<B: ExtendableBuffer + ?Sized>(self, buf: &mut B) -> Result<(), ()> {
+        (&self).to_bytes(buf)
+    }
+}
+
+impl NamePart for &ServiceNamePart<'_> {
+    fn to_bytes<B: ExtendableBuffer + ?Sized>(self, buf: &mut B) -> Result<(), ()> {
+        if let Some(instance) = self.instance {
+            if instance.is_empty() {
+                return Err(());
+            }
+            instance.to_bytes(buf)?;
+        }
+        for label in self.service.split(|c| *c == b'.').chain(self.domain.split(|c| *c == b'.')) {
+            // Skip the empty labels of a leading or trailing dot.
+            if !label.is_empty() {
+                label.to_bytes(buf)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{Buffer, Error, ExtendableBuffer, MutBuffer};
+use core::fmt::{Display, Formatter};
+
+/// The error returned when a [`SliceWriter`] has no space left.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BufferFull;
+
+impl Display for BufferFull {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("buffer full")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferFull {}
+
+impl From<BufferFull> for Error {
+    #[inline(always)]
+    fn from(_: BufferFull) -> Self {
+        Error
+    }
+}
+
+/// A cursor writing a message into a caller-provided slice, e.g. a DMA buffer handed out by
+/// a network driver.
+pub struct SliceWriter<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Creates an empty writer over the slice.
+    #[inline(always)]
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self { buffer, len: 0 }
+    }
+
+    /// The size of the underlying slice.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The number of bytes that can still be written.
+    #[inline(always)]
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.len
+    }
+
+    /// Appends the bytes, or fails without writing anything if they do not fit.
+    pub fn write(&mut self, slice: &[u8]) -> Result<(), BufferFull> {
+        let end = self.len + slice.len();
+        if end > self.buffer.len() {
+            return Err(BufferFull);
+        }
+
+        self.buffer[self.len..end].copy_from_slice(slice);
+        self.len = end;
+        Ok(())
+    }
+
+    /// The bytes written so far.
+    #[inline(always)]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Consumes the writer, returning the finished packet.
+    #[inline(always)]
+    pub fn into_bytes(self) -> &'a [u8] {
+        &self.buffer[..self.len]
+    }
+}
+
+impl Buffer for SliceWriter<'_> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+
+    fn bytes_at(&self, offset: usize, size: usize) -> Result<&[u8], ()> {
+        if offset + size > self.len {
+            return Err(());
+        }
+
+        Ok(&self.buffer[offset..offset + size])
+    }
+}
+
+impl MutBuffer for SliceWriter<'_> {
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer[..self.len]
+    }
+}
+
+impl ExtendableBuffer for SliceWriter<'_> {
+    fn bytes_mut_at(&mut self, offset: usize, size: usize) -> Result<&mut [u8], ()> {
+        if offset + size > self.len {
+            if offset + size > self.buffer.len() {
+                return Err(());
+            }
+            // The slice may hold stale data, newly covered bytes start out zeroed.
+            self.buffer[self.len..offset + size].fill(0);
+            self.len = offset + size;
+        }
+
+        Ok(&mut self.buffer[offset..offset + size])
+    }
+
+    fn extend_from_slice(&mut self, slice: &[u8]) -> Result<(), ()> {
+        self.write(slice).map_err(|_| ())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            self.len = len;
+        }
+    }
+}
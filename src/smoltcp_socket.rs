@@ -0,0 +1,84 @@
+use crate::{Message, SliceWriter, MDNS_IPV4, MDNS_IPV6, MDNS_PORT};
+use smoltcp::iface::Interface;
+use smoltcp::socket::udp::{Socket, UdpMetadata};
+use smoltcp::wire::IpEndpoint;
+
+/// Drives a smoltcp UDP socket bound to the mDNS port.
+///
+/// Received messages are parsed in place in the receive buffer of the socket and messages are
+/// written straight into its transmit buffer, so no datagram is copied.
+pub struct SmoltcpMdnsSocket<'s, 'a> {
+    socket: &'s mut Socket<'a>,
+}
+
+impl<'s, 'a> SmoltcpMdnsSocket<'s, 'a> {
+    /// Wraps a socket, binding it to the mDNS port if it is not bound yet. Multicasts are
+    /// sent with a hop limit of 255.
+    pub fn new(socket: &'s mut Socket<'a>) -> Result<Self, ()> {
+        if !socket.is_open() {
+            socket.bind(MDNS_PORT).map_err(|_| ())?;
+        }
+        socket.set_hop_limit(Some(255));
+
+        Ok(Self { socket })
+    }
+
+    /// Joins the IPv4 and IPv6 mDNS multicast groups on the interface.
+    pub fn join(iface: &mut Interface) -> Result<(), ()> {
+        iface.join_multicast_group(MDNS_IPV4).map_err(|_| ())?;
+        iface.join_multicast_group(MDNS_IPV6).map_err(|_| ())
+    }
+
+    /// Returns the next received message with the endpoint it came from, skipping datagrams
+    /// shorter than a header, or `None` if no datagram is waiting.
+    pub fn recv(&mut self) -> Option<(Message<'_, [u8], false>, UdpMetadata)> {
+        loop {
+            // Peeking first keeps the borrow of the payload out of the loop.
+            let (payload, _) = self.socket.peek().ok()?;
+            if payload.len() >= 12 {
+                break;
+            }
+            self.socket.recv().ok()?;
+        }
+
+        let (payload, meta) = self.socket.recv().ok()?;
+        Some((Message::new(payload).ok()?, meta))
+    }
+
+    /// Writes a message into the transmit buffer, to be sent to the IPv4 multicast group.
+    pub fn send<F>(&mut self, max_size: usize, build: F) -> Result<usize, ()>
+    where
+        F: FnOnce(&mut Message<'_, SliceWriter<'_>, true>) -> Result<(), ()>,
+    {
+        self.send_to(IpEndpoint::new(MDNS_IPV4.into(), MDNS_PORT), max_size, build)
+    }
+
+    /// Writes a message into the transmit buffer, to be sent to the given endpoint.
+    ///
+    /// The message is built by `build` in a slice of `max_size` bytes reserved in the
+    /// transmit buffer, of which only the bytes written are sent. Nothing is reserved if the
+    /// transmit buffer has no room for `max_size` bytes. smoltcp cannot take back a reserved
+    /// datagram though, so if `build` fails an empty datagram is sent, which receivers drop
+    /// as it is shorter than a header.
+    pub fn send_to<F>(&mut self, destination: IpEndpoint, max_size: usize, build: F) -> Result<usize, ()>
+    where
+        F: FnOnce(&mut Message<'_, SliceWriter<'_>, true>) -> Result<(), ()>,
+    {
+        let free = self.socket.payload_send_capacity() - self.socket.send_queue();
+        if !self.socket.can_send() || max_size > free {
+            return Err(());
+        }
+
+        let mut result = Err(());
+        let len = self
+            .socket
+            .send_with(max_size, destination, |data| {
+                let mut writer = SliceWriter::new(data);
+                result = Message::new_mut(&mut writer).and_then(|mut message| build(&mut message));
+                if result.is_ok() { writer.as_bytes().len() } else { 0 }
+            })
+            .map_err(|_| ())?;
+
+        result.map(|_| len)
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{Buffer, ExtendableBuffer, Message, MDNS_IPV4, MDNS_IPV6, MDNS_PORT};
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, UdpSocket};
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
+
+/// The largest datagram [`MdnsSocket::recv`] accepts.
+///
+/// According to [RFC 6762 Section 17](https://tools.ietf.org/rfc/rfc6762#section-17).
+pub(crate) const MAX_DATAGRAM: usize = 9000;
+
+/// Where and how a message was received.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PacketMeta {
+    /// The address and port of the sender.
+    pub source: SocketAddr,
+    /// The IPv4 TTL or IPv6 hop limit the packet arrived with, if the platform reports it.
+    pub ttl: Option<u8>,
+}
+
+impl PacketMeta {
+    /// Returns whether the packet was sent from the mDNS port, rather than by a legacy
+    /// unicast querier.
+    #[inline(always)]
+    pub fn is_mdns_port(&self) -> bool {
+        self.source.port() == MDNS_PORT
+    }
+}
+
+/// A blocking UDP socket bound to the mDNS port and joined to the multicast group of one
+/// address family.
+///
+/// The socket is bound with `SO_REUSEADDR` and `SO_REUSEPORT`, so it can share the port with
+/// other responders on the host, and sends with a multicast TTL or hop limit of 255 and
+/// multicast loopback enabled.
+pub struct MdnsSocket {
+    socket: UdpSocket,
+    group: SocketAddr,
+}
+
+impl MdnsSocket {
+    /// Binds `0.0.0.0:5353` and joins `224.0.0.251` on the interfaces with the given
+    /// addresses, the default interface if none are given. Multicasts leave through the
+    /// first interface.
+    pub fn ipv4(interfaces: &[Ipv4Addr]) -> io::Result<Self> {
+        Self::bind_ipv4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, MDNS_PORT), interfaces)
+    }
+
+    /// Binds `0.0.0.0` on the given port and joins `224.0.0.251` like [`MdnsSocket::ipv4`],
+    /// multicasts are sent to the same port.
+    pub fn bind_ipv4(address: SocketAddrV4, interfaces: &[Ipv4Addr]) -> io::Result<Self> {
+        let socket = Self::bind(Domain::IPV4, address.into())?;
+        let default = [Ipv4Addr::UNSPECIFIED];
+        let interfaces = if interfaces.is_empty() { &default[..] } else { interfaces };
+        for interface in interfaces {
+            socket.join_multicast_v4(&MDNS_IPV4, interface)?;
+        }
+        socket.set_multicast_if_v4(&interfaces[0])?;
+        socket.set_multicast_ttl_v4(255)?;
+        socket.set_ttl_v4(255)?;
+        socket.set_multicast_loop_v4(true)?;
+        #[cfg(unix)]
+        set_option(&socket, libc::IPPROTO_IP, libc::IP_RECVTTL)?;
+
+        Ok(Self {
+            socket: socket.into(),
+            group: SocketAddrV4::new(MDNS_IPV4, address.port()).into(),
+        })
+    }
+
+    /// Binds `[::]:5353` and joins `ff02::fb` on the interfaces with the given indices, the
+    /// default interface if none are given. Multicasts leave through the first interface.
+    pub fn ipv6(interfaces: &[u32]) -> io::Result<Self> {
+        Self::bind_ipv6(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, MDNS_PORT, 0, 0), interfaces)
+    }
+
+    /// Binds `[::]` on the given port and joins `ff02::fb` like [`MdnsSocket::ipv6`],
+    /// multicasts are sent to the same port.
+    pub fn bind_ipv6(address: SocketAddrV6, interfaces: &[u32]) -> io::Result<Self> {
+        let socket = Self::bind(Domain::IPV6, address.into())?;
+        let default = [0];
+        let interfaces = if interfaces.is_empty() { &default[..] } else { interfaces };
+        for interface in interfaces {
+            socket.join_multicast_v6(&MDNS_IPV6, *interface)?;
+        }
+        socket.set_multicast_if_v6(interfaces[0])?;
+        socket.set_multicast_hops_v6(255)?;
+        socket.set_unicast_hops_v6(255)?;
+        socket.set_multicast_loop_v6(true)?;
+        #[cfg(unix)]
+        set_option(&socket, libc::IPPROTO_IPV6, libc::IPV6_RECVHOPLIMIT)?;
+
+        Ok(Self {
+            socket: socket.into(),
+            group: SocketAddrV6::new(MDNS_IPV6, address.port(), 0, interfaces[0]).into(),
+        })
+    }
+
+    fn bind(domain: Domain, address: SocketAddr) -> io::Result<Socket> {
+        let socket = Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?;
+        if domain == Domain::IPV6 {
+            socket.set_only_v6(true)?;
+        }
+        socket.set_reuse_address(true)?;
+        #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))))]
+        socket.set_reuse_port(true)?;
+        socket.bind(&SockAddr::from(address))?;
+
+        Ok(socket)
+    }
+
+    /// Returns the underlying socket, e.g. to set a read timeout.
+    #[inline(always)]
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the underlying socket.
+    #[inline(always)]
+    pub fn into_socket(self) -> UdpSocket {
+        self.socket
+    }
+
+    /// Returns the multicast group and port messages are sent to.
+    #[inline(always)]
+    pub fn group(&self) -> SocketAddr {
+        self.group
+    }
+
+    /// Sends a message to the multicast group.
+    pub fn send<B: Buffer + ?Sized, const WRITE: bool>(&self, message: &Message<'_, B, WRITE>) -> io::Result<()> {
+        self.send_to(message, self.group)
+    }
+
+    /// Sends a message to the given address, e.g. a unicast response to a querier.
+    pub fn send_to<B: Buffer + ?Sized, const WRITE: bool>(
+        &self,
+        message: &Message<'_, B, WRITE>,
+        destination: SocketAddr,
+    ) -> io::Result<()> {
+        self.socket.send_to(message.bytes(), destination).map(|_| ())
+    }
+
+    /// Receives the next message into the buffer, replacing its contents.
+    ///
+    /// Packets shorter than a header are skipped, as are packets from the mDNS port which
+    /// arrived with a TTL other than 255 and thus were not sent on the local link. Packets
+    /// larger than 9000 bytes are skipped as well rather than received cut short.
+    ///
+    /// According to [RFC 6762 Section 11](https://tools.ietf.org/rfc/rfc6762#section-11).
+    pub fn recv<'b, B: ExtendableBuffer + ?Sized>(
+        &self,
+        buffer: &'b mut B,
+    ) -> io::Result<(Message<'b, B, false>, PacketMeta)> {
+        let mut datagram = [0; MAX_DATAGRAM];
+        let (len, meta) = loop {
+            let (len, meta) = self.recv_datagram(&mut datagram)?;
+            if accept(len, &meta) {
+                break (len, meta);
+            }
+        };
+
+        buffer.truncate(0);
+        buffer
+            .extend_from_slice(&datagram[..len])
+            .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "buffer too small"))?;
+        let buffer: &'b B = buffer;
+        let message = Message::new(buffer).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+
+        Ok((message, meta))
+    }
+
+    #[cfg(not(unix))]
+    fn recv_datagram(&self, datagram: &mut [u8]) -> io::Result<(usize, PacketMeta)> {
+        let (len, source) = self.socket.recv_from(datagram)?;
+        Ok((len, PacketMeta { source, ttl: None }))
+    }
+
+    #[cfg(unix)]
+    #[inline(always)]
+    fn recv_datagram(&self, datagram: &mut [u8]) -> io::Result<(usize, PacketMeta)> {
+        recv_datagram(&self.socket, datagram)
+    }
+}
+
+/// Returns whether a received datagram should be handed out, dropping datagrams shorter than
+/// a header and datagrams from the mDNS port which were not sent on the local link.
+pub(crate) fn accept(len: usize, meta: &PacketMeta) -> bool {
+    let off_link = meta.is_mdns_port() && meta.ttl.is_some_and(|ttl| ttl != 255);
+    len >= 12 && !off_link
+}
+
+/// Receives a datagram with `recvmsg`, reading the TTL from the control messages.
+///
+/// Datagrams which did not fit into the given slice are dropped instead of being handed out
+/// cut short.
+#[cfg(unix)]
+pub(crate) fn recv_datagram(socket: &impl AsRawFd, datagram: &mut [u8]) -> io::Result<(usize, PacketMeta)> {
+    let mut iov = libc::iovec {
+        iov_base: datagram.as_mut_ptr().cast(),
+        iov_len: datagram.len(),
+    };
+    // u64 keeps the control messages aligned.
+    let mut control = [0u64; 8];
+    let mut name: libc::sockaddr_storage = unsafe { core::mem::zeroed() };
+    let mut header: libc::msghdr = unsafe { core::mem::zeroed() };
+    let len = loop {
+        header.msg_name = (&mut name as *mut libc::sockaddr_storage).cast();
+        header.msg_namelen = size_of::<libc::sockaddr_storage>() as _;
+        header.msg_iov = &mut iov;
+        header.msg_iovlen = 1;
+        header.msg_control = control.as_mut_ptr().cast();
+        header.msg_controllen = size_of_val(&control) as _;
+        header.msg_flags = 0;
+
+        let len = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut header, 0) };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if header.msg_flags & libc::MSG_TRUNC == 0 {
+            break len;
+        }
+    };
+
+    let mut ttl = None;
+    let mut message = unsafe { libc::CMSG_FIRSTHDR(&header) };
+    while !message.is_null() {
+        let (level, kind, len) = unsafe { ((*message).cmsg_level, (*message).cmsg_type, (*message).cmsg_len) };
+        let data = unsafe { libc::CMSG_DATA(message) };
+        let is_ttl = matches!(
+            (level, kind),
+            (libc::IPPROTO_IP, libc::IP_TTL | libc::IP_RECVTTL) | (libc::IPPROTO_IPV6, libc::IPV6_HOPLIMIT)
+        );
+        if is_ttl {
+            // Linux and IPv6 report an int, the BSDs a single byte for IPv4.
+            let value = if len >= unsafe { libc::CMSG_LEN(4) } as _ {
+                unsafe { data.cast::<libc::c_int>().read_unaligned() }
+            } else {
+                (unsafe { *data }) as libc::c_int
+            };
+            ttl = u8::try_from(value).ok();
+        }
+        message = unsafe { libc::CMSG_NXTHDR(&header, message) };
+    }
+
+    let source = match name.ss_family as libc::c_int {
+        libc::AF_INET => {
+            let address = unsafe { &*(&name as *const libc::sockaddr_storage).cast::<libc::sockaddr_in>() };
+            SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::from(address.sin_addr.s_addr.to_ne_bytes())),
+                u16::from_be(address.sin_port),
+            )
+        }
+        libc::AF_INET6 => {
+            let address = unsafe { &*(&name as *const libc::sockaddr_storage).cast::<libc::sockaddr_in6>() };
+            SocketAddrV6::new(
+                Ipv6Addr::from(address.sin6_addr.s6_addr),
+                u16::from_be(address.sin6_port),
+                address.sin6_flowinfo,
+                address.sin6_scope_id,
+            )
+            .into()
+        }
+        _ => return Err(io::Error::from(io::ErrorKind::InvalidData)),
+    };
+
+    Ok((len as usize, PacketMeta { source, ttl }))
+}
+
+/// Enables a boolean socket option.
+#[cfg(unix)]
+fn set_option(socket: &Socket, level: libc::c_int, option: libc::c_int) -> io::Result<()> {
+    let enable: libc::c_int = 1;
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            level,
+            option,
+            (&enable as *const libc::c_int).cast(),
+            size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,365 @@
+use crate::{
+    AClass, AType, AnswerBuilder, Buffer, Error, ExtendableBuffer, HeaderKind, HeaderOpcode, HeaderResponseCode, Host,
+    Message, NamePart, QClass, QType, RrsigFields, Service, ServiceNamePart,
+};
+use arrayvec::ArrayVec;
+
+/// The domain SRP registrations are made in, unless the network provides another one.
+///
+/// According to [RFC 9665 Section 6.1](https://tools.ietf.org/rfc/rfc9665#section-6.1).
+pub const SRP_DOMAIN: &[u8] = b"default.service.arpa";
+
+/// The size of the largest signature an [`SrpSigner`] may write, e.g. of an RSA-4096 key.
+pub const MAX_SIGNATURE_LEN: usize = 512;
+
+/// The type of `SIG` records.
+const SIG: u16 = 24;
+/// The type of `KEY` records.
+const KEY: u16 = 25;
+/// The type of `OPT` pseudo records.
+const OPT: u16 = 41;
+
+/// The flags of the host key: a key of a host, which may sign updates.
+///
+/// According to [RFC 2535 Section 3.1.2](https://tools.ietf.org/rfc/rfc2535#section-3.1.2).
+const KEY_FLAGS: u16 = 0x0201;
+/// The protocol of keys used with DNSSEC.
+const KEY_PROTOCOL: u8 = 3;
+
+/// The code of the EDNS Update Lease option.
+///
+/// According to [RFC 9664 Section 4](https://tools.ietf.org/rfc/rfc9664#section-4).
+const UPDATE_LEASE: u16 = 2;
+
+/// The UDP payload size announced in the `OPT` record of a registration.
+const UDP_PAYLOAD_SIZE: u16 = 1232;
+
+/// Signs SRP registrations with the key of the host, which the server remembers as the owner
+/// of the registered names.
+///
+/// According to [RFC 9665 Section 3.2.4](https://tools.ietf.org/rfc/rfc9665#section-3.2.4).
+pub trait SrpSigner {
+    /// The DNSSEC algorithm number of the key, e.g. 13 for ECDSA P-256 with SHA-256.
+    fn algorithm(&self) -> u8;
+
+    /// The public key in the format of `KEY` records, e.g. the 64 bytes of the point of an
+    /// ECDSA P-256 key.
+    fn public_key(&self) -> &[u8];
+
+    /// Signs the concatenation of the parts, writing the signature into the buffer and
+    /// returning its length.
+    fn sign(&mut self, data: &[&[u8]], signature: &mut [u8]) -> Result<usize, ()>;
+}
+
+/// The registration of a host and its services with an SRP server, written as a DNS UPDATE
+/// signed with SIG(0).
+///
+/// The update holds, for each service, its `PTR` record, followed by the deletion of all
+/// records of the instance name and its `SRV` and `TXT` records. Then come the deletion of
+/// all records of the host name, its addresses and its `KEY` record. The additional section
+/// holds the requested leases and the signature.
+///
+/// According to [RFC 9665 Section 2.3](https://tools.ietf.org/rfc/rfc9665#section-2.3).
+#[derive(Copy, Clone, Debug)]
+pub struct SrpRegistration<'s> {
+    host: Host<'s>,
+    services: &'s [Service<'s>],
+    domain: &'s [u8],
+    ttl: u32,
+    lease: u32,
+    key_lease: u32,
+    inception: u32,
+    expiration: u32,
+}
+
+impl<'s> SrpRegistration<'s> {
+    /// Registers the host and services in [`SRP_DOMAIN`], with a lease of two hours and a
+    /// key lease of two weeks.
+    pub fn new(host: Host<'s>, services: &'s [Service<'s>]) -> Self {
+        Self {
+            host,
+            services,
+            domain: SRP_DOMAIN,
+            ttl: 7200,
+            lease: 7200,
+            key_lease: 14 * 24 * 3600,
+            inception: 0,
+            expiration: 0,
+        }
+    }
+
+    /// Sets the domain, e.g. `default.service.arpa`.
+    #[inline(always)]
+    pub fn domain(mut self, domain: &'s [u8]) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Sets the TTL of the registered records.
+    #[inline(always)]
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the requested lease of the records and of the key, which keeps the names
+    /// reserved for the host after the records expired, in seconds.
+    ///
+    /// According to [RFC 9664 Section 4](https://tools.ietf.org/rfc/rfc9664#section-4).
+    #[inline(always)]
+    pub fn lease(mut self, lease: u32, key_lease: u32) -> Self {
+        self.lease = lease;
+        self.key_lease = key_lease;
+        self
+    }
+
+    /// Sets the validity period of the signature in seconds since the epoch, modulo 2^32.
+    /// Both are 0 by default, for hosts without a clock.
+    #[inline(always)]
+    pub fn validity(mut self, inception: u32, expiration: u32) -> Self {
+        self.inception = inception;
+        self.expiration = expiration;
+        self
+    }
+
+    /// Writes the signed registration into an empty buffer.
+    pub fn write<'b, B: ExtendableBuffer + ?Sized, S: SrpSigner + ?Sized>(
+        &self,
+        buffer: &'b mut B,
+        id: u16,
+        signer: &mut S,
+    ) -> Result<Message<'b, B, true>, ()> {
+        let host_name = DomainName { host: Some(self.host.name), domain: self.domain };
+        let public_key = signer.public_key();
+        let algorithm = signer.algorithm();
+
+        let mut message = Message::new_mut(buffer)?;
+        message.header_mut()?.set_id(id);
+        let mut body = message.update_body_mut()?
+            .append_zone()
+            .name()
+            .label(DomainName { host: None, domain: self.domain })?
+            .finish()?
+            .qtype(QType::SOA)?
+            .qclass(QClass::IN)?
+            .finish()?
+            .to_prerequisite_section()
+            .to_update_section();
+
+        for service in self.services {
+            let instance = ServiceNamePart::new(service.instance, service.service, self.domain);
+            body = append_record(
+                body.append_update(),
+                ServiceNamePart::service_type(service.service, self.domain),
+                QType::PTR.into(),
+                self.ttl,
+                |buffer| write_name(buffer, &instance),
+            )??;
+            body = body.delete_all_rrsets(&instance)?;
+            body = append_record(body.append_update(), &instance, QType::SRV.into(), self.ttl, |buffer| {
+                // Priority and weight.
+                buffer.extend_from_slice(&[0, 0, 0, 0])?;
+                buffer.extend_from_slice(&service.port.to_be_bytes())?;
+                write_name(buffer, &host_name)
+            })??;
+            body = append_record(body.append_update(), &instance, QType::TXT.into(), self.ttl, |buffer| {
+                // A TXT record holds at least one, possibly empty, string.
+                if service.txt.is_empty() {
+                    return buffer.extend_from_slice(&[0]);
+                }
+                for string in service.txt {
+                    buffer.extend_from_slice(&[u8::try_from(string.len()).map_err(|_| ())?])?;
+                    buffer.extend_from_slice(string)?;
+                }
+                Ok(())
+            })??;
+        }
+
+        body = body.delete_all_rrsets(&host_name)?;
+        if let Some(ipv4) = self.host.ipv4 {
+            body = append_record(body.append_update(), &host_name, QType::A.into(), self.ttl, |buffer| {
+                buffer.extend_from_slice(&ipv4.octets())
+            })??;
+        }
+        if let Some(ipv6) = self.host.ipv6 {
+            body = append_record(body.append_update(), &host_name, QType::AAAA.into(), self.ttl, |buffer| {
+                buffer.extend_from_slice(&ipv6.octets())
+            })??;
+        }
+        body = append_record(body.append_update(), &host_name, KEY, self.ttl, |buffer| {
+            buffer.extend_from_slice(&KEY_FLAGS.to_be_bytes())?;
+            buffer.extend_from_slice(&[KEY_PROTOCOL, algorithm])?;
+            buffer.extend_from_slice(public_key)
+        })??;
+
+        let mut body = body.to_additional_section();
+        let mut opt = [0; 23];
+        opt[1..3].copy_from_slice(&OPT.to_be_bytes());
+        opt[3..5].copy_from_slice(&UDP_PAYLOAD_SIZE.to_be_bytes());
+        opt[9..11].copy_from_slice(&12u16.to_be_bytes());
+        opt[11..13].copy_from_slice(&UPDATE_LEASE.to_be_bytes());
+        opt[13..15].copy_from_slice(&8u16.to_be_bytes());
+        opt[15..19].copy_from_slice(&self.lease.to_be_bytes());
+        opt[19..23].copy_from_slice(&self.key_lease.to_be_bytes());
+        unsafe { &mut *(body.buffer as *mut B) }.extend_from_slice(&opt)?;
+        let header = unsafe { body.header_mut() };
+        header.set_additional_records_count(header.additional_records_count() + 1);
+
+        // The signature covers its own fields and the message without it.
+        //
+        // According to [RFC 2931 Section 3.1](https://tools.ietf.org/rfc/rfc2931#section-3.1).
+        let fields = RrsigFields {
+            type_covered: 0,
+            algorithm,
+            labels: 0,
+            original_ttl: 0,
+            expiration: self.expiration,
+            inception: self.inception,
+            key_tag: key_tag(KEY_FLAGS, KEY_PROTOCOL, algorithm, public_key),
+        }
+        .to_bytes();
+        let mut signer_name = ArrayVec::<u8, 256>::new();
+        write_name(&mut signer_name, &host_name)?;
+        let mut signature = [0; MAX_SIGNATURE_LEN];
+        let len = signer.sign(
+            &[&fields, &signer_name, unsafe { &*body.buffer }.bytes()],
+            &mut signature,
+        )?;
+        let signature = signature.get(..len).ok_or(())?;
+
+        let builder = body.append_additional()
+            .name()
+            .finish()??
+            .atype()
+            .write_with(SIG, |buffer| {
+                buffer.extend_from_slice(&fields)?;
+                buffer.extend_from_slice(&signer_name)?;
+                buffer.extend_from_slice(signature)
+            })??
+            .cache_flush(false)?
+            .aclass(AClass::ANY)?
+            .ttl(0)?;
+        builder.finish()?;
+
+        Ok(message)
+    }
+}
+
+/// The response of an SRP server to a registration.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SrpResponse {
+    pub id: u16,
+    pub response_code: HeaderResponseCode,
+    /// The lease granted by the server, if it returned one.
+    pub lease: Option<u32>,
+    /// The key lease granted by the server, if it returned one.
+    pub key_lease: Option<u32>,
+}
+
+impl SrpResponse {
+    /// Reads the response code and the granted leases. Fails if the message is not an
+    /// UPDATE response or malformed.
+    ///
+    /// According to [RFC 9665 Section 3.3.5](https://tools.ietf.org/rfc/rfc9665#section-3.3.5).
+    pub fn parse<B: Buffer + ?Sized, const WRITE: bool>(message: &Message<'_, B, WRITE>) -> Result<Self, Error> {
+        let header = message.header()?;
+        if header.kind() != HeaderKind::Response || header.opcode() != HeaderOpcode::Update {
+            return Err(Error);
+        }
+
+        let mut response = Self {
+            id: header.id(),
+            response_code: header.response_code(),
+            lease: None,
+            key_lease: None,
+        };
+        let mut body = message.update_body()?.to_prerequisite_section().to_update_section().to_additional_section();
+        for record in body.additionals() {
+            let AType::OPT(_, _, _, options) = record.atype() else {
+                continue;
+            };
+            let mut i = 0;
+            while let Some(option) = options.get(i..i + 4) {
+                let code = u16::from_be_bytes([option[0], option[1]]);
+                let len = u16::from_be_bytes([option[2], option[3]]) as usize;
+                let data = options.get(i + 4..i + 4 + len).ok_or(Error)?;
+                if code == UPDATE_LEASE {
+                    // The key lease may be left out, if it equals the lease.
+                    let u32_at = |j: usize| data.get(j..j + 4).map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()));
+                    response.lease = u32_at(0);
+                    response.key_lease = u32_at(4).or(response.lease);
+                }
+                i += 4 + len;
+            }
+        }
+        if body.additional_count != 0 {
+            return Err(Error);
+        }
+
+        Ok(response)
+    }
+}
+
+/// Computes the tag of a key, which identifies it in signatures.
+///
+/// According to [RFC 4034 Appendix B](https://tools.ietf.org/rfc/rfc4034#appendix-B).
+pub fn key_tag(flags: u16, protocol: u8, algorithm: u8, public_key: &[u8]) -> u16 {
+    let [flags_high, flags_low] = flags.to_be_bytes();
+    let rdata = [flags_high, flags_low, protocol, algorithm].into_iter().chain(public_key.iter().copied());
+    let sum = rdata
+        .enumerate()
+        .map(|(i, byte)| if i % 2 == 0 { (byte as u32) << 8 } else { byte as u32 })
+        .fold(0u32, u32::wrapping_add);
+
+    (sum.wrapping_add(sum >> 16) & 0xffff) as u16
+}
+
+/// A name within the domain, optionally with a single host label.
+struct DomainName<'s> {
+    host: Option<&'s [u8]>,
+    domain: &'s [u8],
+}
+
+impl NamePart for &DomainName<'_> {
+    fn to_bytes<B: ExtendableBuffer + ?Sized>(self, buf: &mut B) -> Result<(), ()> {
+        if let Some(host) = self.host {
+            host.to_bytes(buf)?;
+        }
+        for label in self.domain.split(|c| *c == b'.').filter(|label| !label.is_empty()) {
+            label.to_bytes(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl NamePart for DomainName<'_> {
+    #[inline(always)]
+    fn to_bytes<B: ExtendableBuffer + ?Sized>(self, buf: &mut B) -> Result<(), ()> {
+        (&self).to_bytes(buf)
+    }
+}
+
+/// Writes a name uncompressed into record data.
+fn write_name<B: ExtendableBuffer + ?Sized>(buffer: &mut B, name: impl NamePart) -> Result<(), ()> {
+    name.to_bytes(buffer)?;
+    buffer.extend_from_slice(&[0])
+}
+
+fn append_record<'a, B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O>(
+    builder: AnswerBuilder<'a, B, P, O, F, false, false, false, false, false>,
+    name: impl NamePart,
+    atype: u16,
+    ttl: u32,
+    rdata: impl FnOnce(&mut B) -> Result<(), ()>,
+) -> Result<O, ()> {
+    Ok(builder.name()
+        .label(name)?
+        .finish()??
+        .atype()
+        .write_with(atype, rdata)??
+        .cache_flush(false)?
+        .aclass(AClass::IN)?
+        .ttl(ttl)?
+        .finish())
+}
@@ -0,0 +1,205 @@
+use crate::Error;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// The key of the `mandatory` SvcParam.
+pub const SVC_PARAM_MANDATORY: u16 = 0;
+/// The key of the `alpn` SvcParam.
+pub const SVC_PARAM_ALPN: u16 = 1;
+/// The key of the `no-default-alpn` SvcParam.
+pub const SVC_PARAM_NO_DEFAULT_ALPN: u16 = 2;
+/// The key of the `port` SvcParam.
+pub const SVC_PARAM_PORT: u16 = 3;
+/// The key of the `ipv4hint` SvcParam.
+pub const SVC_PARAM_IPV4HINT: u16 = 4;
+/// The key of the `ech` SvcParam.
+pub const SVC_PARAM_ECH: u16 = 5;
+/// The key of the `ipv6hint` SvcParam.
+pub const SVC_PARAM_IPV6HINT: u16 = 6;
+
+/// A service parameter of an `SVCB` or `HTTPS` record.
+///
+/// According to [RFC 9460 Section 7](https://tools.ietf.org/rfc/rfc9460#section-7).
+#[derive(Clone, Debug, PartialEq)]
+pub enum SvcParam<'a> {
+    /// The keys of the parameters a client must understand to use the record
+    Mandatory(SvcParamKeys<'a>),
+    /// The supported protocols, as ALPN protocol identifiers
+    Alpn(AlpnIds<'a>),
+    /// The default protocol of the scheme is not supported
+    NoDefaultAlpn,
+    /// The port to connect to instead of the default one
+    Port(u16),
+    /// Addresses of the target, which may be used before they are resolved
+    Ipv4Hint(Ipv4Hints<'a>),
+    /// An encrypted ClientHello configuration list
+    Ech(&'a [u8]),
+    /// Addresses of the target, which may be used before they are resolved
+    Ipv6Hint(Ipv6Hints<'a>),
+    /// A parameter without typed decoding, with its key and raw value
+    Unknown(u16, &'a [u8]),
+}
+
+impl<'a> SvcParam<'a> {
+    /// Decodes a parameter value, failing if its length does not fit the key.
+    pub fn parse(key: u16, value: &'a [u8]) -> Result<Self, Error> {
+        match key {
+            SVC_PARAM_MANDATORY if !value.is_empty() && value.len().is_multiple_of(2) => {
+                Ok(SvcParam::Mandatory(SvcParamKeys { bytes: value }))
+            }
+            SVC_PARAM_ALPN if !value.is_empty() => {
+                // Every identifier is a non-empty length-prefixed string.
+                let mut i = 0;
+                while let Some(&len) = value.get(i) {
+                    if len == 0 || value.len() < i + 1 + len as usize {
+                        return Err(Error);
+                    }
+                    i += 1 + len as usize;
+                }
+                Ok(SvcParam::Alpn(AlpnIds { bytes: value }))
+            }
+            SVC_PARAM_NO_DEFAULT_ALPN if value.is_empty() => Ok(SvcParam::NoDefaultAlpn),
+            SVC_PARAM_PORT if value.len() == 2 => Ok(SvcParam::Port(u16::from_be_bytes([value[0], value[1]]))),
+            SVC_PARAM_IPV4HINT if !value.is_empty() && value.len().is_multiple_of(4) => {
+                Ok(SvcParam::Ipv4Hint(Ipv4Hints { bytes: value }))
+            }
+            SVC_PARAM_ECH => Ok(SvcParam::Ech(value)),
+            SVC_PARAM_IPV6HINT if !value.is_empty() && value.len().is_multiple_of(16) => {
+                Ok(SvcParam::Ipv6Hint(Ipv6Hints { bytes: value }))
+            }
+            SVC_PARAM_MANDATORY..=SVC_PARAM_IPV6HINT => Err(Error),
+            _ => Ok(SvcParam::Unknown(key, value)),
+        }
+    }
+
+    /// Returns the key of the parameter.
+    pub fn key(&self) -> u16 {
+        match self {
+            SvcParam::Mandatory(_) => SVC_PARAM_MANDATORY,
+            SvcParam::Alpn(_) => SVC_PARAM_ALPN,
+            SvcParam::NoDefaultAlpn => SVC_PARAM_NO_DEFAULT_ALPN,
+            SvcParam::Port(_) => SVC_PARAM_PORT,
+            SvcParam::Ipv4Hint(_) => SVC_PARAM_IPV4HINT,
+            SvcParam::Ech(_) => SVC_PARAM_ECH,
+            SvcParam::Ipv6Hint(_) => SVC_PARAM_IPV6HINT,
+            SvcParam::Unknown(key, _) => *key,
+        }
+    }
+}
+
+/// Checks that the SvcParams of a record are well-formed, with their keys in strictly
+/// increasing order.
+///
+/// According to [RFC 9460 Section 2.2](https://tools.ietf.org/rfc/rfc9460#section-2.2).
+pub(crate) fn check_svc_params(params: &[u8]) -> Result<(), ()> {
+    let mut last = None;
+    let mut i = 0;
+    while i < params.len() {
+        let (key, value) = svc_param_at(params, &mut i).ok_or(())?;
+        if last.is_some_and(|last| key <= last) {
+            return Err(());
+        }
+        SvcParam::parse(key, value)?;
+        last = Some(key);
+    }
+
+    Ok(())
+}
+
+fn svc_param_at<'a>(params: &'a [u8], i: &mut usize) -> Option<(u16, &'a [u8])> {
+    let key = u16::from_be_bytes([*params.get(*i)?, *params.get(*i + 1)?]);
+    let len = u16::from_be_bytes([*params.get(*i + 2)?, *params.get(*i + 3)?]) as usize;
+    let value = params.get(*i + 4..*i + 4 + len)?;
+    *i += 4 + len;
+
+    Some((key, value))
+}
+
+/// An iterator over the SvcParams of an `SVCB` or `HTTPS` record, see [`AType::svc_params`](crate::AType::svc_params).
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvcParams<'a> {
+    pub(crate) params: &'a [u8],
+    pub(crate) i: usize,
+}
+
+impl<'a> SvcParams<'a> {
+    /// Returns the keys and raw values of the remaining parameters.
+    pub fn raw(&self) -> impl Iterator<Item = (u16, &'a [u8])> + 'a {
+        let params = self.params;
+        let mut i = self.i;
+        core::iter::from_fn(move || svc_param_at(params, &mut i))
+    }
+}
+
+impl<'a> Iterator for SvcParams<'a> {
+    type Item = SvcParam<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = svc_param_at(self.params, &mut self.i)?;
+        SvcParam::parse(key, value).ok()
+    }
+}
+
+/// An iterator over the keys of a `mandatory` SvcParam.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvcParamKeys<'a> {
+    bytes: &'a [u8],
+}
+
+impl Iterator for SvcParamKeys<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, rest) = self.bytes.split_first_chunk::<2>()?;
+        self.bytes = rest;
+        Some(u16::from_be_bytes(*key))
+    }
+}
+
+/// An iterator over the protocol identifiers of an `alpn` SvcParam.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlpnIds<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for AlpnIds<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&len, rest) = self.bytes.split_first()?;
+        let id = rest.get(..len as usize)?;
+        self.bytes = &rest[len as usize..];
+        Some(id)
+    }
+}
+
+/// An iterator over the addresses of an `ipv4hint` SvcParam.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ipv4Hints<'a> {
+    bytes: &'a [u8],
+}
+
+impl Iterator for Ipv4Hints<'_> {
+    type Item = Ipv4Addr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (addr, rest) = self.bytes.split_first_chunk::<4>()?;
+        self.bytes = rest;
+        Some(Ipv4Addr::from(*addr))
+    }
+}
+
+/// An iterator over the addresses of an `ipv6hint` SvcParam.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ipv6Hints<'a> {
+    bytes: &'a [u8],
+}
+
+impl Iterator for Ipv6Hints<'_> {
+    type Item = Ipv6Addr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (addr, rest) = self.bytes.split_first_chunk::<16>()?;
+        self.bytes = rest;
+        Some(Ipv6Addr::from(*addr))
+    }
+}
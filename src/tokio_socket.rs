@@ -0,0 +1,172 @@
+use crate::socket::{accept, MAX_DATAGRAM};
+use crate::{Buffer, Message, MdnsSocket, PacketMeta};
+use bytes::{Bytes, BytesMut};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use tokio::net::UdpSocket;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// A message owning its bytes, which unlike [`Message`] can be held across `.await` and sent
+/// between tasks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageBuf {
+    bytes: Bytes,
+}
+
+impl MessageBuf {
+    /// Wraps the bytes of a message, which have to hold at least a header.
+    pub fn new(bytes: Bytes) -> Result<Self, ()> {
+        Message::new(&bytes[..])?;
+        Ok(Self { bytes })
+    }
+
+    /// Copies a parsed or written message.
+    pub fn copy_from<B: Buffer + ?Sized, const WRITE: bool>(message: &Message<'_, B, WRITE>) -> Result<Self, ()> {
+        Self::new(Bytes::copy_from_slice(message.bytes()))
+    }
+
+    /// Returns a view of the message.
+    #[inline(always)]
+    pub fn message(&self) -> Message<'_, [u8], false> {
+        Message::new(&self.bytes[..]).expect("checked in MessageBuf::new")
+    }
+
+    /// Returns the bytes of the message.
+    #[inline(always)]
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    /// Returns the bytes of the message.
+    #[inline(always)]
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+}
+
+/// The async version of [`MdnsSocket`], registered with the tokio runtime.
+pub struct AsyncMdnsSocket {
+    socket: UdpSocket,
+    group: SocketAddr,
+}
+
+impl AsyncMdnsSocket {
+    /// Binds and joins like [`MdnsSocket::ipv4`].
+    pub fn ipv4(interfaces: &[Ipv4Addr]) -> io::Result<Self> {
+        Self::from_std(MdnsSocket::ipv4(interfaces)?)
+    }
+
+    /// Binds and joins like [`MdnsSocket::ipv6`].
+    pub fn ipv6(interfaces: &[u32]) -> io::Result<Self> {
+        Self::from_std(MdnsSocket::ipv6(interfaces)?)
+    }
+
+    /// Registers a blocking socket with the runtime. Has to be called within a runtime.
+    pub fn from_std(socket: MdnsSocket) -> io::Result<Self> {
+        let group = socket.group();
+        let socket = socket.into_socket();
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket: UdpSocket::from_std(socket)?,
+            group,
+        })
+    }
+
+    /// Returns the underlying socket, e.g. to use it with `UdpFramed` and [`MdnsCodec`].
+    #[inline(always)]
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Returns the underlying socket.
+    #[inline(always)]
+    pub fn into_socket(self) -> UdpSocket {
+        self.socket
+    }
+
+    /// Returns the multicast group and port messages are sent to.
+    #[inline(always)]
+    pub fn group(&self) -> SocketAddr {
+        self.group
+    }
+
+    /// Sends a message to the multicast group.
+    pub async fn send(&self, message: &MessageBuf) -> io::Result<()> {
+        self.send_to(message, self.group).await
+    }
+
+    /// Sends a message to the given address, e.g. a unicast response to a querier.
+    pub async fn send_to(&self, message: &MessageBuf, destination: SocketAddr) -> io::Result<()> {
+        self.socket.send_to(&message.bytes, destination).await.map(|_| ())
+    }
+
+    /// Receives the next message, skipping datagrams like [`MdnsSocket::recv`].
+    ///
+    /// The message is split off the given buffer, whose allocation is reused by later calls
+    /// once the previous messages have been dropped.
+    pub async fn recv(&self, buffer: &mut BytesMut) -> io::Result<(MessageBuf, PacketMeta)> {
+        loop {
+            buffer.clear();
+            buffer.resize(MAX_DATAGRAM, 0);
+            let (len, meta) = self.recv_datagram(buffer).await?;
+            if accept(len, &meta) {
+                let bytes = buffer.split_to(len).freeze();
+                buffer.clear();
+                return Ok((MessageBuf { bytes }, meta));
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    async fn recv_datagram(&self, datagram: &mut [u8]) -> io::Result<(usize, PacketMeta)> {
+        self.socket
+            .async_io(tokio::io::Interest::READABLE, || crate::socket::recv_datagram(&self.socket, datagram))
+            .await
+    }
+
+    #[cfg(not(unix))]
+    async fn recv_datagram(&self, datagram: &mut [u8]) -> io::Result<(usize, PacketMeta)> {
+        let (len, source) = self.socket.recv_from(datagram).await?;
+        Ok((len, PacketMeta { source, ttl: None }))
+    }
+}
+
+/// A codec for `tokio_util::udp::UdpFramed`, decoding each datagram into a [`MessageBuf`].
+///
+/// Datagrams shorter than a header are reported as `InvalidData` errors, after which the
+/// stream continues with the next datagram.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MdnsCodec;
+
+impl Decoder for MdnsCodec {
+    type Item = MessageBuf;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.is_empty() {
+            return Ok(None);
+        }
+
+        MessageBuf::new(src.split().freeze())
+            .map(Some)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "shorter than a header"))
+    }
+}
+
+impl Encoder<MessageBuf> for MdnsCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: MessageBuf, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(&item, dst)
+    }
+}
+
+impl Encoder<&MessageBuf> for MdnsCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &MessageBuf, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&item.bytes);
+        Ok(())
+    }
+}
@@ -0,0 +1,558 @@
+use crate::{
+    answer::write_nsec_types, name::unescape, AClass, AnswerBuilder, AnswersSection, Error, ExtendableBuffer,
+    MessageBody, NamePart, PresentationName,
+};
+use core::fmt::{Display, Formatter};
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+/// The position of a zone file entry that could not be parsed or written.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ZoneError {
+    /// The line, starting at 1.
+    pub line: usize,
+    /// The column in characters, starting at 1.
+    pub column: usize,
+}
+
+impl ZoneError {
+    fn at(text: &str, position: usize) -> Self {
+        let before = &text[..position.min(text.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl Display for ZoneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid zone file entry at line {}, column {}", self.line, self.column)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ZoneError {}
+
+impl From<ZoneError> for Error {
+    #[inline(always)]
+    fn from(_: ZoneError) -> Self {
+        Error
+    }
+}
+
+/// The record types a zone file entry may have, and which may be listed in `NSEC` records.
+const TYPES: &[(&str, u16)] = &[
+    ("A", 1),
+    ("NS", 2),
+    ("MD", 3),
+    ("MF", 4),
+    ("CNAME", 5),
+    ("SOA", 6),
+    ("MB", 7),
+    ("MG", 8),
+    ("MR", 9),
+    ("NULL", 10),
+    ("WKS", 11),
+    ("PTR", 12),
+    ("HINFO", 13),
+    ("MINFO", 14),
+    ("MX", 15),
+    ("TXT", 16),
+    ("AAAA", 28),
+    ("SRV", 33),
+    ("OPT", 41),
+    ("DS", 43),
+    ("RRSIG", 46),
+    ("NSEC", 47),
+    ("DNSKEY", 48),
+    ("NSEC3", 50),
+    ("NSEC3PARAM", 51),
+    ("SVCB", 64),
+    ("HTTPS", 65),
+];
+
+/// The record types which can be written from a zone file.
+const SUPPORTED_TYPES: &[u16] = &[1, 2, 5, 6, 12, 15, 16, 28, 33, 47];
+
+/// A parser for the records of a zone file, also known as master file.
+///
+/// Supports the `$ORIGIN` and `$TTL` directives, relative names, `@`, parentheses, comments
+/// and the `A`, `AAAA`, `CNAME`, `MX`, `NS`, `NSEC`, `PTR`, `SOA`, `SRV` and `TXT` record types.
+/// According to [RFC 1035 Section 5](https://tools.ietf.org/rfc/rfc1035#section-5)
+/// and [RFC 2308 Section 4](https://tools.ietf.org/rfc/rfc2308#section-4).
+///
+/// The iterator yields each record, which is written through [`ZoneRecord::write`]. After an
+/// error, parsing continues with the next line.
+pub struct Zone<'s> {
+    text: &'s str,
+    position: usize,
+    origin: Option<&'s str>,
+    ttl: Option<u32>,
+    aclass: AClass,
+    owner: Option<Owner<'s>>,
+}
+
+#[derive(Copy, Clone)]
+struct Owner<'s> {
+    name: &'s str,
+    origin: Option<&'s str>,
+    position: usize,
+}
+
+impl<'s> Zone<'s> {
+    #[inline(always)]
+    pub fn new(text: &'s str) -> Self {
+        Self {
+            text,
+            position: 0,
+            origin: None,
+            ttl: None,
+            aclass: AClass::IN,
+            owner: None,
+        }
+    }
+
+    /// Sets the origin relative names are completed with, until a `$ORIGIN` directive.
+    #[inline(always)]
+    pub fn with_origin(mut self, origin: &'s str) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Sets the TTL of records without one, until a `$TTL` directive.
+    #[inline(always)]
+    pub fn with_ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Writes all records into the answer section. A record which fails is removed from the
+    /// buffer again, keeping the records before it.
+    pub fn write_answers<'a, B: ExtendableBuffer + ?Sized>(
+        self,
+        mut body: MessageBody<'a, B, AnswersSection, true>,
+    ) -> Result<MessageBody<'a, B, AnswersSection, true>, ZoneError> {
+        let text = self.text;
+        for record in self {
+            let record = record?;
+            let buffer = body.buffer as *mut B;
+            let start = unsafe { (*buffer).len() };
+            body = match record.write(body.append_answer(), false) {
+                Ok(Ok(body)) => body,
+                result => {
+                    unsafe { (*buffer).truncate(start) };
+                    return Err(result.err().unwrap_or(ZoneError::at(text, record.position)));
+                }
+            };
+        }
+
+        Ok(body)
+    }
+
+    fn entry(&mut self, tokens: &mut Tokens<'s>) -> Result<Option<ZoneRecord<'s>>, usize> {
+        let blank_owner = matches!(self.text.as_bytes().get(tokens.position), Some(b' ' | b'\t'));
+        let Some((first, first_position)) = tokens.next()? else {
+            return Ok(None);
+        };
+
+        if !blank_owner && first.starts_with('$') {
+            let (value, position) = tokens.next()?.ok_or(tokens.position)?;
+            match first {
+                "$ORIGIN" if is_absolute(value) => self.origin = Some(value),
+                "$TTL" => self.ttl = Some(parse_ttl(value).map_err(|_| position)?),
+                "$ORIGIN" => return Err(position),
+                _ => return Err(first_position),
+            }
+            return match tokens.next()? {
+                Some((_, position)) => Err(position),
+                None => Ok(None),
+            };
+        }
+
+        let (owner, mut token) = if blank_owner {
+            (self.owner.ok_or(first_position)?, Some((first, first_position)))
+        } else {
+            if !is_absolute(first) && self.origin.is_none() {
+                return Err(first_position);
+            }
+            let owner = Owner { name: first, origin: self.origin, position: first_position };
+            self.owner = Some(owner);
+            (owner, tokens.next()?)
+        };
+
+        // The TTL and class are optional and may appear in either order.
+        let mut ttl = None;
+        let mut aclass = None;
+        let (atype, atype_position) = loop {
+            let (text, position) = token.ok_or(tokens.position)?;
+            if ttl.is_none() && text.starts_with(|c: char| c.is_ascii_digit()) {
+                ttl = Some(parse_ttl(text).map_err(|_| position)?);
+            } else if let (None, Some(value)) = (aclass, parse_class(text)) {
+                aclass = Some(value);
+            } else {
+                break (text, position);
+            }
+            token = tokens.next()?;
+        };
+        let atype = parse_type(atype)
+            .filter(|atype| SUPPORTED_TYPES.contains(atype))
+            .ok_or(atype_position)?;
+        let ttl = ttl.or(self.ttl).ok_or(atype_position)?;
+        if let Some(aclass) = aclass {
+            self.aclass = aclass;
+        }
+
+        let rdata = *tokens;
+        while tokens.next()?.is_some() {}
+
+        Ok(Some(ZoneRecord {
+            text: self.text,
+            position: first_position,
+            owner,
+            ttl,
+            aclass: self.aclass,
+            atype,
+            origin: self.origin,
+            rdata,
+        }))
+    }
+}
+
+impl<'s> Iterator for Zone<'s> {
+    type Item = Result<ZoneRecord<'s>, ZoneError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.text.len() {
+            let mut tokens = Tokens { text: self.text, position: self.position, parens: 0, open: 0 };
+            let entry = self.entry(&mut tokens);
+
+            // Continue after the end of the entry, or the line with the error.
+            let end = match entry {
+                Ok(_) => tokens.position,
+                Err(position) => position,
+            };
+            self.position = self.text[end.min(self.text.len())..]
+                .find('\n')
+                .map_or(self.text.len(), |i| end + i + 1);
+
+            match entry {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => {}
+                Err(position) => return Some(Err(ZoneError::at(self.text, position))),
+            }
+        }
+
+        None
+    }
+}
+
+/// A record of a zone file.
+#[derive(Copy, Clone)]
+pub struct ZoneRecord<'s> {
+    text: &'s str,
+    position: usize,
+    owner: Owner<'s>,
+    ttl: u32,
+    aclass: AClass,
+    atype: u16,
+    origin: Option<&'s str>,
+    rdata: Tokens<'s>,
+}
+
+impl ZoneRecord<'_> {
+    #[inline(always)]
+    pub fn ttl(&self) -> u32 {
+        self.ttl
+    }
+
+    #[inline(always)]
+    pub fn aclass(&self) -> AClass {
+        self.aclass
+    }
+
+    #[inline(always)]
+    pub fn type_id(&self) -> u16 {
+        self.atype
+    }
+
+    /// Writes the record through an answer builder of any section. Errors in the record data
+    /// are reported here, names are written uncompressed.
+    pub fn write<B: ExtendableBuffer + ?Sized, P, O, F: Fn(P) -> O>(
+        &self,
+        builder: AnswerBuilder<'_, B, P, O, F, false, false, false, false, false>,
+        cache_flush: bool,
+    ) -> Result<O, ZoneError> {
+        let owner = self.owner;
+        let mut position = owner.position;
+        let builder = builder.name();
+        let builder = match owner.name {
+            "@" => builder.label(PresentationName(owner.origin.unwrap_or_default())),
+            name if is_absolute(name) => builder.label(PresentationName(name)),
+            name => builder
+                .label(PresentationName(name))
+                .and_then(|builder| builder.label(PresentationName(owner.origin.unwrap_or_default()))),
+        };
+
+        let mut rdata = RecordData { tokens: self.rdata, origin: self.origin, position: &mut position };
+        let atype = self.atype;
+        builder
+            .and_then(|builder| builder.finish()?)
+            .and_then(|builder| builder.atype().write_with(atype, |buffer| rdata.write(atype, buffer))?)
+            .and_then(|builder| builder.cache_flush(cache_flush))
+            .and_then(|builder| builder.aclass(self.aclass))
+            .and_then(|builder| builder.ttl(self.ttl))
+            .map(|builder| builder.finish())
+            .map_err(|_| ZoneError::at(self.text, position))
+    }
+}
+
+/// Splits the entries of a zone file into tokens, skipping comments and joining the lines
+/// within parentheses.
+#[derive(Copy, Clone)]
+struct Tokens<'s> {
+    text: &'s str,
+    position: usize,
+    parens: usize,
+    open: usize,
+}
+
+impl<'s> Tokens<'s> {
+    /// Returns the next token of the entry and its position, or the position of an error.
+    fn next(&mut self) -> Result<Option<(&'s str, usize)>, usize> {
+        let bytes = self.text.as_bytes();
+        loop {
+            match bytes.get(self.position) {
+                None if self.parens > 0 => return Err(self.open),
+                None | Some(b'\n') if self.parens == 0 => return Ok(None),
+                Some(b' ' | b'\t' | b'\r' | b'\n') => self.position += 1,
+                Some(b';') => {
+                    while bytes.get(self.position).is_some_and(|c| *c != b'\n') {
+                        self.position += 1;
+                    }
+                }
+                Some(b'(') => {
+                    if self.parens == 0 {
+                        self.open = self.position;
+                    }
+                    self.parens += 1;
+                    self.position += 1;
+                }
+                Some(b')') if self.parens > 0 => {
+                    self.parens -= 1;
+                    self.position += 1;
+                }
+                Some(b')') => return Err(self.position),
+                _ => break,
+            }
+        }
+
+        let start = self.position;
+        let quoted = bytes[start] == b'"';
+        if quoted {
+            self.position += 1;
+        }
+        loop {
+            match bytes.get(self.position) {
+                None | Some(b'\n') if quoted => return Err(start),
+                None => break,
+                Some(b'\\') => self.position += 2,
+                Some(b'"') if quoted => {
+                    self.position += 1;
+                    break;
+                }
+                Some(b' ' | b'\t' | b'\r' | b'\n' | b';' | b'(' | b')') if !quoted => break,
+                _ => self.position += 1,
+            }
+        }
+        self.position = self.position.min(bytes.len());
+
+        Ok(Some((&self.text[start..self.position], start)))
+    }
+}
+
+/// Writes the record data of an entry, keeping the position of the token being written.
+struct RecordData<'s, 'p> {
+    tokens: Tokens<'s>,
+    origin: Option<&'s str>,
+    position: &'p mut usize,
+}
+
+impl<'s> RecordData<'s, '_> {
+    fn next(&mut self) -> Result<Option<&'s str>, ()> {
+        match self.tokens.next() {
+            Ok(Some((token, position))) => {
+                *self.position = position;
+                Ok(Some(token))
+            }
+            Ok(None) => {
+                *self.position = self.tokens.position;
+                Ok(None)
+            }
+            Err(position) => {
+                *self.position = position;
+                Err(())
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<&'s str, ()> {
+        self.next()?.ok_or(())
+    }
+
+    fn number<T: core::str::FromStr>(&mut self) -> Result<T, ()> {
+        self.token()?.parse().map_err(|_| ())
+    }
+
+    fn name<B: ExtendableBuffer + ?Sized>(&mut self, buffer: &mut B) -> Result<(), ()> {
+        let name = self.token()?;
+        if name != "@" {
+            PresentationName(name).to_bytes(buffer)?;
+        }
+        if name == "@" || !is_absolute(name) {
+            PresentationName(self.origin.ok_or(())?).to_bytes(buffer)?;
+        }
+        buffer.extend_from_slice(&[0])
+    }
+
+    fn write<B: ExtendableBuffer + ?Sized>(&mut self, atype: u16, buffer: &mut B) -> Result<(), ()> {
+        match atype {
+            1 => buffer.extend_from_slice(&self.number::<Ipv4Addr>()?.octets())?,
+            28 => buffer.extend_from_slice(&self.number::<Ipv6Addr>()?.octets())?,
+            2 | 5 | 12 => self.name(buffer)?,
+            6 => {
+                self.name(buffer)?;
+                self.name(buffer)?;
+                buffer.extend_from_slice(&self.number::<u32>()?.to_be_bytes())?;
+                for _ in 0..4 {
+                    buffer.extend_from_slice(&parse_ttl(self.token()?)?.to_be_bytes())?;
+                }
+            }
+            15 => {
+                buffer.extend_from_slice(&self.number::<u16>()?.to_be_bytes())?;
+                self.name(buffer)?;
+            }
+            16 => {
+                write_character_string(self.token()?, buffer)?;
+                while let Some(token) = self.next()? {
+                    write_character_string(token, buffer)?;
+                }
+            }
+            33 => {
+                for _ in 0..3 {
+                    buffer.extend_from_slice(&self.number::<u16>()?.to_be_bytes())?;
+                }
+                self.name(buffer)?;
+            }
+            47 => {
+                self.name(buffer)?;
+                let types = self.tokens;
+                while let Some(atype) = self.next()? {
+                    parse_type(atype).ok_or(())?;
+                }
+                let mut tokens = types;
+                let types = core::iter::from_fn(move || tokens.next().ok().flatten())
+                    .filter_map(|(atype, _)| parse_type(atype));
+                write_nsec_types(types, buffer)?;
+            }
+            _ => return Err(()),
+        }
+
+        match self.next()? {
+            Some(_) => Err(()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether the name ends with an unescaped dot.
+fn is_absolute(name: &str) -> bool {
+    let Some(name) = name.strip_suffix('.') else {
+        return false;
+    };
+    let backslashes = name.bytes().rev().take_while(|c| *c == b'\\').count();
+
+    backslashes % 2 == 0
+}
+
+/// Parses a TTL in seconds, or with the `w`, `d`, `h`, `m` and `s` units as in `1h30m`.
+fn parse_ttl(text: &str) -> Result<u32, ()> {
+    if text.bytes().all(|c| c.is_ascii_digit()) {
+        return text.parse().map_err(|_| ());
+    }
+
+    let mut ttl = 0u32;
+    let mut value = None;
+    for c in text.bytes() {
+        if c.is_ascii_digit() {
+            let digit = (c - b'0') as u32;
+            value = Some(value.unwrap_or(0u32).checked_mul(10).and_then(|value| value.checked_add(digit)).ok_or(())?);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            b's' => 1,
+            b'm' => 60,
+            b'h' => 60 * 60,
+            b'd' => 24 * 60 * 60,
+            b'w' => 7 * 24 * 60 * 60,
+            _ => return Err(()),
+        };
+        let value = value.take().ok_or(())?;
+        ttl = value.checked_mul(unit).and_then(|value| ttl.checked_add(value)).ok_or(())?;
+    }
+    if value.is_some() {
+        return Err(());
+    }
+
+    Ok(ttl)
+}
+
+fn parse_class(text: &str) -> Option<AClass> {
+    [("IN", AClass::IN), ("CS", AClass::CS), ("CH", AClass::CH), ("HS", AClass::HS)]
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(text))
+        .map(|(_, aclass)| aclass)
+}
+
+/// Parses a type mnemonic, or the generic `TYPEn` form.
+fn parse_type(text: &str) -> Option<u16> {
+    if let Some(number) = text.get(..4).filter(|prefix| prefix.eq_ignore_ascii_case("TYPE")).and(text.get(4..)) {
+        if let Ok(atype) = number.parse() {
+            return Some(atype);
+        }
+    }
+
+    TYPES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(text))
+        .map(|(_, atype)| *atype)
+}
+
+/// Writes a possibly quoted character string of at most 255 bytes.
+fn write_character_string<B: ExtendableBuffer + ?Sized>(token: &str, buffer: &mut B) -> Result<(), ()> {
+    let text = token.as_bytes();
+    let text = match text {
+        [b'"', text @ .., b'"'] => text,
+        text => text,
+    };
+
+    let start = buffer.len();
+    buffer.extend_from_slice(&[0])?;
+    let mut i = 0;
+    while i < text.len() {
+        let c = match text[i] {
+            b'\\' => unescape(text, &mut i)?,
+            c => {
+                i += 1;
+                c
+            }
+        };
+        buffer.extend_from_slice(&[c])?;
+    }
+
+    let len = u8::try_from(buffer.len() - start - 1).map_err(|_| ())?;
+    buffer.bytes_mut_at(start, 1)?[0] = len;
+
+    Ok(())
+}